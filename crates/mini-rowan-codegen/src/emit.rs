@@ -0,0 +1,483 @@
+//! Emits the Rust code for a [Model].
+
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::model::{AstType, BOGUS, Model, Shape, SlotContent};
+
+/// The names of the generated language-level items.
+struct Names {
+    language: Ident,
+    kind: Ident,
+    factory: Ident,
+}
+
+pub fn emit(model: &Model, language: &str) -> String {
+    let names = Names {
+        language: format_ident!("{language}Language"),
+        kind: format_ident!("{language}SyntaxKind"),
+        factory: format_ident!("{language}SyntaxFactory"),
+    };
+
+    let uses_tokens = model.nodes.iter().any(|node| match &node.shape {
+        Shape::Node(slots) => slots
+            .iter()
+            .any(|slot| matches!(slot.content, SlotContent::Token(_))),
+        Shape::SeparatedList { .. } => true,
+        _ => false,
+    });
+    let token_import = uses_tokens.then(|| quote!(token::SyntaxToken,));
+
+    let kind_enum = emit_kind_enum(model, &names);
+    let language_item = emit_language(model, &names);
+    let ast_types = model.nodes.iter().map(|node| emit_ast_type(node, &names));
+    let default_bogus = (!model.has_bogus_rule).then(|| {
+        emit_ast_type(
+            &AstType {
+                name: "Bogus".to_string(),
+                kind: BOGUS.to_string(),
+                shape: Shape::Bogus,
+                to_bogus: None,
+            },
+            &names,
+        )
+    });
+    let factory = emit_factory(model, &names);
+
+    let file = quote! {
+        use mini_rowan::{
+            ast::{AstNode, SyntaxKindSet},
+            green::RawSyntaxKind,
+            syntax::{Language, SyntaxKind, element::SyntaxElement, node::SyntaxNode, #token_import},
+            syntax_factory::{
+                RawNodeSlots, SyntaxFactory,
+                parsed_children::ParsedChildren,
+                raw_syntax::RawSyntaxNode,
+                schema::{ErasedSlotSchema, SchemaSlot, SlotSchema},
+            },
+        };
+        use std::borrow::Cow;
+
+        #kind_enum
+        #language_item
+        #(#ast_types)*
+        #default_bogus
+        #factory
+    };
+
+    let file = syn::parse2::<syn::File>(file).expect("generated code to be valid Rust");
+    format!(
+        "// Generated by mini-rowan-codegen, do not edit.\n\n{}",
+        prettyplease::unparse(&file)
+    )
+}
+
+fn emit_kind_enum(model: &Model, names: &Names) -> TokenStream {
+    let Names { language, kind, .. } = names;
+
+    let tokens = model.tokens.iter().map(|token| {
+        let variant = format_ident!("{}", token.kind);
+        let text = token.text.as_ref().map(|text| quote!(#[token(#text)]));
+        quote!(#text #variant,)
+    });
+
+    let bogus_nodes: Vec<&AstType> = model
+        .nodes
+        .iter()
+        .filter(|node| matches!(node.shape, Shape::Bogus))
+        .collect();
+
+    let nodes = model
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| !matches!(node.shape, Shape::Union(_) | Shape::Bogus))
+        .map(|(index, node)| {
+            let variant = format_ident!("{}", node.kind);
+            let root = (index == 0).then(|| quote!(#[root]));
+            let list = match &node.shape {
+                Shape::List { .. } => Some(quote!(#[list])),
+                Shape::SeparatedList { separator, .. } => {
+                    let separator = format_ident!("{separator}");
+                    Some(quote!(#[separated_list(#separator)]))
+                }
+                _ => None,
+            };
+            let to_bogus = node.to_bogus.as_ref().map(|bogus| {
+                let bogus = format_ident!("{bogus}");
+                quote!(#[to_bogus(#bogus)])
+            });
+            quote!(#root #list #to_bogus #variant,)
+        });
+
+    // The default bogus kind must come first, `to_bogus` falls back to the first bogus kind
+    let default_bogus = format_ident!("{BOGUS}");
+    let other_bogus = bogus_nodes
+        .iter()
+        .filter(|node| node.kind != BOGUS)
+        .map(|node| format_ident!("{}", node.kind));
+
+    quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SyntaxKind)]
+        #[syntax_kind(language = #language)]
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+        pub enum #kind {
+            #[tombstone]
+            TOMBSTONE,
+            #[eof]
+            EOF,
+            #(#tokens)*
+            #(#nodes)*
+            #[bogus]
+            #default_bogus,
+            #(#[bogus] #other_bogus,)*
+        }
+    }
+}
+
+fn emit_language(model: &Model, names: &Names) -> TokenStream {
+    let Names { language, kind, .. } = names;
+    let root = format_ident!("{}", model.nodes[0].name);
+
+    let slot_arms = model.nodes.iter().filter_map(|node| {
+        let Shape::Node(slots) = &node.shape else {
+            return None;
+        };
+
+        let variant = format_ident!("{}", node.kind);
+        let slots = slots.iter().map(|slot| {
+            let name = &slot.name;
+            let optional = slot.optional;
+            let kinds = match &slot.content {
+                SlotContent::Node(ty) => {
+                    let ty = format_ident!("{ty}");
+                    quote!(#ty::KIND_SET)
+                }
+                SlotContent::Token(kinds) => {
+                    let kinds = kinds.iter().map(|token| format_ident!("{token}"));
+                    quote!(mini_rowan::syntax_kind_set![#(#kind::#kinds),*])
+                }
+            };
+
+            quote! {
+                SchemaSlot {
+                    name: Cow::Borrowed(#name),
+                    kinds: #kinds,
+                    optional: #optional,
+                }
+            }
+        });
+
+        Some(quote! {
+            #kind::#variant => {
+                const SLOTS: &[SchemaSlot<#language>] = &[#(#slots),*];
+                Some(SLOTS)
+            }
+        })
+    });
+
+    quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct #language;
+
+        impl Language for #language {
+            type Kind = #kind;
+            type Root = #root;
+
+            fn slot_schema() -> Option<ErasedSlotSchema<Self>> {
+                Some(ErasedSlotSchema::new())
+            }
+        }
+
+        impl SlotSchema for #language {
+            fn slots(kind: #kind) -> Option<&'static [SchemaSlot<Self>]> {
+                match kind {
+                    #(#slot_arms)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+fn emit_ast_type(node: &AstType, names: &Names) -> TokenStream {
+    match &node.shape {
+        Shape::Union(members) => emit_union(node, members, names),
+        _ => emit_node(node, names),
+    }
+}
+
+fn emit_node(node: &AstType, names: &Names) -> TokenStream {
+    let Names { language, kind, .. } = names;
+    let name = format_ident!("{}", node.name);
+    let variant = format_ident!("{}", node.kind);
+
+    let accessors = match &node.shape {
+        Shape::Node(slots) => {
+            let accessors = slots.iter().enumerate().map(|(index, slot)| {
+                let accessor = field_ident(&slot.name);
+                let index = Literal::u32_unsuffixed(index as u32);
+
+                match &slot.content {
+                    SlotContent::Node(ty) => {
+                        let ty = format_ident!("{ty}");
+                        quote! {
+                            pub fn #accessor(&self) -> Option<#ty> {
+                                self.syntax.element_in_slot(#index)?.into_node().and_then(#ty::cast)
+                            }
+                        }
+                    }
+                    SlotContent::Token(_) => quote! {
+                        pub fn #accessor(&self) -> Option<SyntaxToken<#language>> {
+                            self.syntax.element_in_slot(#index)?.into_token()
+                        }
+                    },
+                }
+            });
+
+            quote!(#(#accessors)*)
+        }
+        Shape::List { element } => {
+            let element = format_ident!("{element}");
+            quote! {
+                /// Returns the elements of the list.
+                pub fn iter(&self) -> impl Iterator<Item = #element> {
+                    self.syntax.children().filter_map(#element::cast)
+                }
+
+                pub fn len(&self) -> usize {
+                    self.syntax.slots().len()
+                }
+
+                pub fn is_empty(&self) -> bool {
+                    self.len() == 0
+                }
+            }
+        }
+        Shape::SeparatedList { element, .. } => {
+            let element = format_ident!("{element}");
+            quote! {
+                /// Returns the elements of the list, skipping missing elements.
+                pub fn iter(&self) -> impl Iterator<Item = #element> {
+                    self.syntax.children().filter_map(#element::cast)
+                }
+
+                /// Returns the separators of the list.
+                pub fn separators(&self) -> impl Iterator<Item = SyntaxToken<#language>> {
+                    self.syntax
+                        .children_with_tokens()
+                        .filter_map(|element| element.into_token())
+                }
+
+                pub fn is_empty(&self) -> bool {
+                    self.syntax.slots().len() == 0
+                }
+            }
+        }
+        Shape::Bogus => quote! {
+            /// Returns the nodes and tokens of the bogus node.
+            pub fn items(&self) -> impl Iterator<Item = SyntaxElement<#language>> {
+                self.syntax.children_with_tokens()
+            }
+        },
+        Shape::Union(_) => unreachable!("unions are emitted by `emit_union`"),
+    };
+
+    quote! {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct #name {
+            syntax: SyntaxNode<#language>,
+        }
+
+        impl #name {
+            pub fn cast(syntax: SyntaxNode<#language>) -> Option<Self> {
+                Self::can_cast(syntax.kind()).then_some(Self { syntax })
+            }
+
+            pub fn syntax(&self) -> &SyntaxNode<#language> {
+                &self.syntax
+            }
+
+            pub fn into_syntax(self) -> SyntaxNode<#language> {
+                self.syntax
+            }
+
+            #accessors
+        }
+
+        impl AstNode for #name {
+            type Language = #language;
+
+            const KIND_SET: SyntaxKindSet<#language> =
+                SyntaxKindSet::from_raw(RawSyntaxKind(#kind::#variant as u16));
+
+            fn can_cast(kind: #kind) -> bool {
+                kind == #kind::#variant
+            }
+        }
+    }
+}
+
+fn emit_union(node: &AstType, members: &[String], names: &Names) -> TokenStream {
+    let Names { language, kind, .. } = names;
+    let name = format_ident!("{}", node.name);
+    let members: Vec<Ident> = members
+        .iter()
+        .map(|member| format_ident!("{member}"))
+        .collect();
+
+    quote! {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        #[allow(clippy::enum_variant_names)]
+        pub enum #name {
+            #(#members(#members),)*
+        }
+
+        impl #name {
+            pub fn cast(syntax: SyntaxNode<#language>) -> Option<Self> {
+                let kind = syntax.kind();
+                #(
+                    if #members::can_cast(kind) {
+                        return #members::cast(syntax).map(Self::#members);
+                    }
+                )*
+                None
+            }
+
+            pub fn syntax(&self) -> &SyntaxNode<#language> {
+                match self {
+                    #(Self::#members(it) => it.syntax(),)*
+                }
+            }
+
+            pub fn into_syntax(self) -> SyntaxNode<#language> {
+                match self {
+                    #(Self::#members(it) => it.into_syntax(),)*
+                }
+            }
+        }
+
+        impl AstNode for #name {
+            type Language = #language;
+
+            const KIND_SET: SyntaxKindSet<#language> =
+                SyntaxKindSet::empty() #(.union(#members::KIND_SET))*;
+
+            fn can_cast(kind: #kind) -> bool {
+                #(#members::can_cast(kind))||*
+            }
+        }
+
+        #(
+            impl From<#members> for #name {
+                fn from(node: #members) -> Self {
+                    Self::#members(node)
+                }
+            }
+        )*
+    }
+}
+
+fn emit_factory(model: &Model, names: &Names) -> TokenStream {
+    let Names { kind, factory, .. } = names;
+
+    let arms = model.nodes.iter().filter_map(|node| {
+        let body = match &node.shape {
+            Shape::Node(slots) => {
+                let count = Literal::usize_unsuffixed(slots.len());
+                let checks = slots.iter().map(|slot| {
+                    let predicate = match &slot.content {
+                        SlotContent::Node(ty) => {
+                            let ty = format_ident!("{ty}");
+                            quote!(#ty::can_cast(element.kind()))
+                        }
+                        SlotContent::Token(kinds) => {
+                            let kinds = kinds.iter().map(|token| format_ident!("{token}"));
+                            quote!(matches!(element.kind(), #(#kind::#kinds)|*))
+                        }
+                    };
+
+                    quote! {
+                        if current_element.as_ref().is_some_and(|element| #predicate) {
+                            slots.mark_present();
+                            current_element = elements.next();
+                        }
+                        slots.next_slot();
+                    }
+                });
+
+                quote! {
+                    let mut elements = (&children).into_iter();
+                    let mut slots: RawNodeSlots<#count> = RawNodeSlots::default();
+                    let mut current_element = elements.next();
+
+                    #(#checks)*
+
+                    if current_element.is_some() {
+                        return RawSyntaxNode::new(kind.to_bogus(), children.into_iter().map(Some));
+                    }
+
+                    slots.into_node(kind, children)
+                }
+            }
+            Shape::List { element } => {
+                let element = format_ident!("{element}");
+                quote!(Self::make_node_list_syntax(kind, children, #element::can_cast))
+            }
+            Shape::SeparatedList {
+                element,
+                separator,
+                allow_trailing,
+            } => {
+                let element = format_ident!("{element}");
+                let separator = format_ident!("{separator}");
+                quote! {
+                    Self::make_separated_list_syntax(
+                        kind,
+                        children,
+                        #element::can_cast,
+                        #kind::#separator,
+                        #allow_trailing,
+                    )
+                }
+            }
+            Shape::Union(_) | Shape::Bogus => return None,
+        };
+
+        let variant = format_ident!("{}", node.kind);
+        Some(quote!(#kind::#variant => { #body }))
+    });
+
+    quote! {
+        #[derive(Debug)]
+        pub struct #factory;
+
+        impl SyntaxFactory for #factory {
+            type Kind = #kind;
+
+            fn make_syntax(kind: #kind, children: ParsedChildren<#kind>) -> RawSyntaxNode<#kind> {
+                match kind {
+                    #(#arms)*
+                    _ => RawSyntaxNode::new(kind, children.into_iter().map(Some)),
+                }
+            }
+        }
+    }
+}
+
+/// Returns the identifier for an accessor, using a raw identifier for keywords.
+fn field_ident(name: &str) -> Ident {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe",
+        "use", "where", "while", "abstract", "become", "box", "do", "final", "macro", "override",
+        "priv", "try", "typeof", "unsized", "virtual", "yield",
+    ];
+
+    if KEYWORDS.contains(&name) {
+        Ident::new_raw(name, Span::call_site())
+    } else {
+        Ident::new(name, Span::call_site())
+    }
+}
@@ -0,0 +1,379 @@
+//! Parser for ungrammar-style grammar files.
+//!
+//! A grammar is a list of rules `Name = rule`. A rule is built from node references (`Expression`),
+//! tokens (`'+'`), labels (`left: Expression`), sequences, alternatives (`A | B`), groups (`( ... )`),
+//! optional (`?`) and repeated (`*`) rules. `//` starts a comment that extends to the end of the line.
+
+use crate::CodegenError;
+
+/// A parsed grammar file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    pub rules: Vec<RuleDefinition>,
+}
+
+/// A rule `name = rule`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleDefinition {
+    pub name: String,
+    /// The one-based line of the rule's name.
+    pub line: usize,
+    pub rule: Rule,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    Labeled { label: String, rule: Box<Rule> },
+    Node(String),
+    Token(String),
+    Sequence(Vec<Rule>),
+    Alternatives(Vec<Rule>),
+    Optional(Box<Rule>),
+    Repeated(Box<Rule>),
+}
+
+impl Grammar {
+    /// Parses the grammar in `source`.
+    pub fn parse(source: &str) -> Result<Self, CodegenError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let mut rules = Vec::new();
+
+        while let Some(token) = parser.current() {
+            let (Token::Ident(name), line) = token.clone() else {
+                return Err(parser.error("expected a rule name"));
+            };
+
+            parser.position += 1;
+            parser.expect(&Token::Eq, "expected `=` after the rule name")?;
+            let rule = parser.alternatives()?;
+
+            rules.push(RuleDefinition { name, line, rule });
+        }
+
+        Ok(Self { rules })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Literal(String),
+    Eq,
+    Colon,
+    Pipe,
+    Star,
+    QuestionMark,
+    LParen,
+    RParen,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, CodegenError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            '/' if chars.next_if_eq(&'/').is_some() => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                continue;
+            }
+            '=' => Token::Eq,
+            ':' => Token::Colon,
+            '|' => Token::Pipe,
+            '*' => Token::Star,
+            '?' => Token::QuestionMark,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '\'' => {
+                let mut text = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('\'' | '\\')) => text.push(escaped),
+                            _ => return Err(CodegenError::new(line, "invalid escape in a token")),
+                        },
+                        Some('\n') | None => {
+                            return Err(CodegenError::new(line, "unterminated token"));
+                        }
+                        Some(c) => text.push(c),
+                    }
+                }
+
+                if text.is_empty() {
+                    return Err(CodegenError::new(line, "empty token"));
+                }
+
+                Token::Literal(text)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut name = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                Token::Ident(name)
+            }
+            c => {
+                return Err(CodegenError::new(
+                    line,
+                    format!("unexpected character {c:?}"),
+                ));
+            }
+        };
+
+        tokens.push((token, line));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(Token, usize)],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn alternatives(&mut self) -> Result<Rule, CodegenError> {
+        let mut alternatives = vec![self.sequence()?];
+
+        while self.eat(&Token::Pipe) {
+            alternatives.push(self.sequence()?);
+        }
+
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Rule::Alternatives(alternatives)
+        })
+    }
+
+    fn sequence(&mut self) -> Result<Rule, CodegenError> {
+        let mut items = Vec::new();
+
+        while self.at_item_start() {
+            items.push(self.item()?);
+        }
+
+        match items.len() {
+            0 => Err(self.error("expected a rule")),
+            1 => Ok(items.pop().unwrap()),
+            _ => Ok(Rule::Sequence(items)),
+        }
+    }
+
+    /// Returns `true` if the current token starts an item of a sequence. An identifier followed by `=`
+    /// starts the next rule definition instead.
+    fn at_item_start(&self) -> bool {
+        match self.current() {
+            Some((Token::Ident(_), _)) => {
+                !matches!(self.tokens.get(self.position + 1), Some((Token::Eq, _)))
+            }
+            Some((Token::Literal(_) | Token::LParen, _)) => true,
+            _ => false,
+        }
+    }
+
+    fn item(&mut self) -> Result<Rule, CodegenError> {
+        if let Some((Token::Ident(label), _)) = self.current()
+            && matches!(self.tokens.get(self.position + 1), Some((Token::Colon, _)))
+        {
+            let label = label.clone();
+            self.position += 2;
+            let rule = self.postfix()?;
+
+            return Ok(Rule::Labeled {
+                label,
+                rule: Box::new(rule),
+            });
+        }
+
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Rule, CodegenError> {
+        let mut rule = self.primary()?;
+
+        loop {
+            if self.eat(&Token::QuestionMark) {
+                rule = Rule::Optional(Box::new(rule));
+            } else if self.eat(&Token::Star) {
+                rule = Rule::Repeated(Box::new(rule));
+            } else {
+                return Ok(rule);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Rule, CodegenError> {
+        let Some((token, _)) = self.current() else {
+            return Err(self.error("unexpected end of the grammar"));
+        };
+
+        let rule = match token {
+            Token::Ident(name) => Rule::Node(name.clone()),
+            Token::Literal(text) => Rule::Token(text.clone()),
+            Token::LParen => {
+                self.position += 1;
+                let rule = self.alternatives()?;
+                self.expect(&Token::RParen, "expected `)`")?;
+                return Ok(rule);
+            }
+            _ => return Err(self.error("expected a node, a token, or `(`")),
+        };
+
+        self.position += 1;
+        Ok(rule)
+    }
+
+    fn current(&self) -> Option<&(Token, usize)> {
+        self.tokens.get(self.position)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.current().is_some_and(|(current, _)| current == token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, message: &str) -> Result<(), CodegenError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn error(&self, message: &str) -> CodegenError {
+        let line = self
+            .current()
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line);
+        CodegenError::new(line, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(source: &str) -> (usize, String) {
+        let error = Grammar::parse(source).unwrap_err();
+        (error.line(), error.message().to_string())
+    }
+
+    fn node(name: &str) -> Rule {
+        Rule::Node(name.to_string())
+    }
+
+    fn token(text: &str) -> Rule {
+        Rule::Token(text.to_string())
+    }
+
+    #[test]
+    fn parses_rules() {
+        let grammar = Grammar::parse(
+            "// Comment\nList = (Item (',' Item)* ','?)\nItem = name: 'IDENT' value: ('+' | '\\'')? // trailing\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            grammar.rules,
+            [
+                RuleDefinition {
+                    name: "List".to_string(),
+                    line: 2,
+                    rule: Rule::Sequence(vec![
+                        node("Item"),
+                        Rule::Repeated(Box::new(Rule::Sequence(vec![token(","), node("Item")]))),
+                        Rule::Optional(Box::new(token(","))),
+                    ]),
+                },
+                RuleDefinition {
+                    name: "Item".to_string(),
+                    line: 3,
+                    rule: Rule::Sequence(vec![
+                        Rule::Labeled {
+                            label: "name".to_string(),
+                            rule: Box::new(token("IDENT")),
+                        },
+                        Rule::Labeled {
+                            label: "value".to_string(),
+                            rule: Box::new(Rule::Optional(Box::new(Rule::Alternatives(vec![
+                                token("+"),
+                                token("'"),
+                            ])))),
+                        },
+                    ]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_rule_ends_at_the_next_rule_name() {
+        let grammar = Grammar::parse("A = B C D = E").unwrap();
+
+        assert_eq!(grammar.rules.len(), 2);
+        assert_eq!(
+            grammar.rules[0].rule,
+            Rule::Sequence(vec![node("B"), node("C")])
+        );
+        assert_eq!(grammar.rules[1].rule, node("E"));
+    }
+
+    #[test]
+    fn token_errors() {
+        assert_eq!(
+            parse_error("A = 'a\n"),
+            (1, "unterminated token".to_string())
+        );
+        assert_eq!(parse_error("\nA = ''"), (2, "empty token".to_string()));
+        assert_eq!(
+            parse_error("A = '\\n'"),
+            (1, "invalid escape in a token".to_string())
+        );
+        assert_eq!(
+            parse_error("A = B\nB = C;"),
+            (2, "unexpected character ';'".to_string())
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(
+            parse_error("'a' = B"),
+            (1, "expected a rule name".to_string())
+        );
+        assert_eq!(
+            parse_error("A B"),
+            (1, "expected `=` after the rule name".to_string())
+        );
+        assert_eq!(parse_error("A ="), (1, "expected a rule".to_string()));
+        assert_eq!(
+            parse_error("A = B\nC = (D"),
+            (2, "expected `)`".to_string())
+        );
+        assert_eq!(
+            parse_error("A = B | | C"),
+            (1, "expected a rule".to_string())
+        );
+        assert_eq!(
+            parse_error("A = B\n\nC = *"),
+            (3, "expected a rule".to_string())
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Generates the syntax kinds, AST types and syntax factory of a language from an ungrammar-style grammar.
+//!
+//! ```text
+//! Root = statements: StatementList 'EOF'
+//! StatementList = Statement*
+//! Statement = ExpressionStatement | BogusStatement
+//! ExpressionStatement = Expression ';'?
+//! Expression = Literal | Binary | BogusExpression
+//! Literal = value: 'NUMBER'
+//! Binary = left: Expression operator: ('+' | '-') right: Expression
+//! BogusStatement = SyntaxElement*
+//! BogusExpression = SyntaxElement*
+//! ```
+//!
+//! For a language called `Calc`, [generate] emits:
+//!
+//! * the `CalcSyntaxKind` enum with a kind for every token, node and list, using `#[derive(SyntaxKind)]`,
+//! * the `CalcLanguage` whose root is the AST type of the first rule and that implements `SlotSchema`,
+//! * an AST type for every rule: a struct with an accessor per slot, an enum for unions, or a list wrapper,
+//! * the `CalcSyntaxFactory` whose `make_syntax` checks each child against its slot and converts nodes with
+//!   unexpected children to their bogus kind.
+//!
+//! Quoted tokens written in upper case, like `'NUMBER'`, name token kinds without fixed text. `'EOF'` is the
+//! end of file token. Other alphanumeric tokens are keywords (`'fn'` becomes `FN_KW`) and all remaining tokens
+//! punctuation (`'('` becomes `L_PAREN`). See [model] for how rules map to AST types.
+//!
+//! The generated code requires `mini-rowan` with the `macros` feature.
+
+use std::fmt;
+
+mod emit;
+pub mod grammar;
+pub mod model;
+
+use crate::{grammar::Grammar, model::Model};
+
+/// An error in a grammar file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodegenError {
+    line: usize,
+    message: String,
+}
+
+impl CodegenError {
+    pub(crate) fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the one-based line of the error in the grammar.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+/// Generates the Rust code for the language `language` from `grammar`.
+///
+/// `language` prefixes the names of the generated language, kind, and factory types.
+pub fn generate(grammar: &str, language: &str) -> Result<String, CodegenError> {
+    let grammar = Grammar::parse(grammar)?;
+    let model = Model::lower(&grammar)?;
+
+    Ok(emit::emit(&model, language))
+}
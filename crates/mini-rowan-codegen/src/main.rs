@@ -0,0 +1,76 @@
+//! Command line interface of the code generator:
+//!
+//! ```text
+//! mini-rowan-codegen <GRAMMAR> --language <NAME> [--output <FILE>]
+//! ```
+
+use std::{fs, process::ExitCode};
+
+const USAGE: &str = "usage: mini-rowan-codegen <GRAMMAR> --language <NAME> [--output <FILE>]";
+
+fn main() -> ExitCode {
+    let mut grammar_path = None;
+    let mut language = None;
+    let mut output = None;
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        let target = match arg.as_str() {
+            "--language" | "-l" => &mut language,
+            "--output" | "-o" => &mut output,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with('-') || grammar_path.is_some() => {
+                eprintln!("unexpected argument `{arg}`\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+            _ => {
+                grammar_path = Some(arg);
+                continue;
+            }
+        };
+
+        match args.next() {
+            Some(value) => *target = Some(value),
+            None => {
+                eprintln!("missing value for `{arg}`\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let (Some(grammar_path), Some(language)) = (grammar_path, language) else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    let grammar = match fs::read_to_string(&grammar_path) {
+        Ok(grammar) => grammar,
+        Err(error) => {
+            eprintln!("failed to read `{grammar_path}`: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let code = match mini_rowan_codegen::generate(&grammar, &language) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("{grammar_path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match output {
+        Some(output) => {
+            if let Err(error) = fs::write(&output, code) {
+                eprintln!("failed to write `{output}`: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{code}"),
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,909 @@
+//! Lowers a [Grammar] to the kinds and AST types to generate.
+//!
+//! Each rule becomes one of:
+//!
+//! * a union if it's an alternative of nodes only: `Expression = Literal | Binary`,
+//! * a list if it repeats a node: `Statements = Statement*`,
+//! * a separated list if it has the form `(Expression (',' Expression)* ','?)`. The list allows a trailing
+//!   separator if it ends with the optional separator,
+//! * a bogus node if its name starts with `Bogus`, regardless of its rule,
+//! * a node with one slot per item of its sequence otherwise.
+//!
+//! A slot holds a node, a token, or one of several tokens: `operator: ('+' | '-')`. Slots are named
+//! after their label or, if unlabeled, after their node or token: `expression`, `semicolon_token`.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    CodegenError,
+    grammar::{Grammar, Rule, RuleDefinition},
+};
+
+/// The kind of the default bogus node, which is generated if the grammar doesn't define a `Bogus` rule.
+pub const BOGUS: &str = "BOGUS";
+
+/// The kinds that every generated kind enum defines.
+const RESERVED_KINDS: [&str; 3] = ["TOMBSTONE", "EOF", BOGUS];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model {
+    /// The token kinds in the order of their first use, not including `EOF`.
+    pub tokens: Vec<TokenKind>,
+    /// The AST types in the order of their rules. The first one is the root.
+    pub nodes: Vec<AstType>,
+    /// Whether the grammar defines the default `Bogus` node.
+    pub has_bogus_rule: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenKind {
+    /// The name of the kind, for example `PLUS` or `FN_KW`.
+    pub kind: String,
+    /// The text of keywords and punctuation or `None` for tokens like identifiers.
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AstType {
+    /// The name of the rule and generated type.
+    pub name: String,
+    /// The name of the kind. Unions have no kind of their own.
+    pub kind: String,
+    pub shape: Shape,
+    /// The bogus kind this node converts to if it isn't the default.
+    pub to_bogus: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    Node(Vec<Slot>),
+    Union(Vec<String>),
+    List {
+        element: String,
+    },
+    SeparatedList {
+        element: String,
+        separator: String,
+        allow_trailing: bool,
+    },
+    Bogus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slot {
+    pub name: String,
+    pub content: SlotContent,
+    /// Whether the slot is marked with `?` in the grammar.
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotContent {
+    /// A node of the AST type with the given name.
+    Node(String),
+    /// A token of one of the kinds.
+    Token(Vec<String>),
+}
+
+impl Model {
+    pub fn lower(grammar: &Grammar) -> Result<Self, CodegenError> {
+        let mut lowering = Lowering {
+            rules: HashMap::new(),
+            tokens: Vec::new(),
+        };
+
+        for definition in &grammar.rules {
+            if lowering
+                .rules
+                .insert(definition.name.as_str(), definition)
+                .is_some()
+            {
+                return Err(CodegenError::new(
+                    definition.line,
+                    format!("duplicate rule `{}`", definition.name),
+                ));
+            }
+        }
+
+        if grammar.rules.is_empty() {
+            return Err(CodegenError::new(1, "the grammar has no rules"));
+        }
+
+        let mut nodes = grammar
+            .rules
+            .iter()
+            .map(|definition| lowering.lower_rule(definition))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if matches!(nodes[0].shape, Shape::Union(_)) {
+            return Err(CodegenError::new(
+                grammar.rules[0].line,
+                "the root rule can't be a union",
+            ));
+        }
+
+        assign_bogus_kinds(&mut nodes);
+        check_union_cycles(&nodes, grammar)?;
+        check_kind_collisions(&nodes, &lowering.tokens, grammar)?;
+
+        Ok(Self {
+            tokens: lowering.tokens,
+            has_bogus_rule: nodes.iter().any(|node| node.kind == BOGUS),
+            nodes,
+        })
+    }
+
+    pub fn node(&self, name: &str) -> &AstType {
+        self.nodes
+            .iter()
+            .find(|node| node.name == name)
+            .expect("nodes to reference existing rules")
+    }
+}
+
+struct Lowering<'a> {
+    rules: HashMap<&'a str, &'a RuleDefinition>,
+    tokens: Vec<TokenKind>,
+}
+
+impl Lowering<'_> {
+    fn lower_rule(&mut self, definition: &RuleDefinition) -> Result<AstType, CodegenError> {
+        let shape = if definition.name.starts_with("Bogus") {
+            Shape::Bogus
+        } else {
+            self.lower_shape(definition)?
+        };
+
+        let kind = match shape {
+            Shape::Union(_) => String::new(),
+            _ => screaming_snake_case(&definition.name),
+        };
+
+        let node = AstType {
+            name: definition.name.clone(),
+            kind,
+            shape,
+            to_bogus: None,
+        };
+
+        self.check_references(&node, definition.line)?;
+        Ok(node)
+    }
+
+    fn lower_shape(&mut self, definition: &RuleDefinition) -> Result<Shape, CodegenError> {
+        let line = definition.line;
+        let rule = match &definition.rule {
+            // An empty list is valid anyway
+            Rule::Optional(inner) if is_list(inner) => &**inner,
+            rule => rule,
+        };
+
+        match rule {
+            Rule::Alternatives(alternatives)
+                if alternatives
+                    .iter()
+                    .all(|alternative| matches!(alternative, Rule::Node(_))) =>
+            {
+                let members = alternatives
+                    .iter()
+                    .map(|alternative| match alternative {
+                        Rule::Node(name) => name.clone(),
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                return Ok(Shape::Union(members));
+            }
+            Rule::Repeated(element) => {
+                return match &**element {
+                    Rule::Node(element) => Ok(Shape::List {
+                        element: element.clone(),
+                    }),
+                    _ => Err(CodegenError::new(
+                        line,
+                        "lists must repeat a single node: `Element*`",
+                    )),
+                };
+            }
+            _ => {}
+        }
+
+        if let Some(shape) = self.lower_separated_list(rule) {
+            return Ok(shape);
+        }
+
+        let items = match rule {
+            Rule::Sequence(items) => items.as_slice(),
+            rule => std::slice::from_ref(rule),
+        };
+
+        let mut slots: Vec<Slot> = Vec::with_capacity(items.len());
+
+        for item in items {
+            let slot = self.lower_slot(item, line)?;
+
+            if slots.iter().any(|existing| existing.name == slot.name) {
+                return Err(CodegenError::new(
+                    line,
+                    format!(
+                        "`{}` has two slots called `{}`, label one of them",
+                        definition.name, slot.name
+                    ),
+                ));
+            }
+
+            slots.push(slot);
+        }
+
+        Ok(Shape::Node(slots))
+    }
+
+    /// Lowers `(Element (',' Element)* ','?)`.
+    fn lower_separated_list(&mut self, rule: &Rule) -> Option<Shape> {
+        let Rule::Sequence(items) = rule else {
+            return None;
+        };
+
+        let (Rule::Node(element), Rule::Repeated(repeated)) = (items.first()?, items.get(1)?)
+        else {
+            return None;
+        };
+
+        let Rule::Sequence(repeated) = &**repeated else {
+            return None;
+        };
+
+        let [Rule::Token(separator), Rule::Node(repeated_element)] = repeated.as_slice() else {
+            return None;
+        };
+
+        if repeated_element != element {
+            return None;
+        }
+
+        let allow_trailing = match &items[2..] {
+            [] => false,
+            [Rule::Optional(trailing)] if **trailing == Rule::Token(separator.clone()) => true,
+            _ => return None,
+        };
+
+        Some(Shape::SeparatedList {
+            element: element.clone(),
+            separator: self.token_kind(separator),
+            allow_trailing,
+        })
+    }
+
+    fn lower_slot(&mut self, item: &Rule, line: usize) -> Result<Slot, CodegenError> {
+        let (label, rule) = match item {
+            Rule::Labeled { label, rule } => (Some(label.clone()), &**rule),
+            rule => (None, rule),
+        };
+
+        // All slots can be empty, `?` only marks the slot as optional in the slot schema
+        let (rule, optional) = match rule {
+            Rule::Optional(inner) => (&**inner, true),
+            rule => (rule, false),
+        };
+
+        let content = match rule {
+            Rule::Node(name) => SlotContent::Node(name.clone()),
+            Rule::Token(text) => SlotContent::Token(vec![self.token_kind(text)]),
+            Rule::Alternatives(alternatives) => {
+                let kinds = alternatives
+                    .iter()
+                    .map(|alternative| match alternative {
+                        Rule::Token(text) => Ok(self.token_kind(text)),
+                        _ => Err(CodegenError::new(
+                            line,
+                            "alternatives in a slot must all be tokens, define a union rule for nodes",
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if label.is_none() {
+                    return Err(CodegenError::new(
+                        line,
+                        "a slot with alternative tokens needs a label",
+                    ));
+                }
+
+                SlotContent::Token(kinds)
+            }
+            Rule::Repeated(_) => {
+                return Err(CodegenError::new(
+                    line,
+                    "repetitions must be separate list rules: `Elements = Element*`",
+                ));
+            }
+            _ => {
+                return Err(CodegenError::new(
+                    line,
+                    "slots must be a node, a token, or alternative tokens",
+                ));
+            }
+        };
+
+        let name = match (label, &content) {
+            (Some(label), _) => label,
+            (None, SlotContent::Node(name)) => snake_case(name),
+            (None, SlotContent::Token(kinds)) => format!("{}_token", kinds[0].to_lowercase()),
+        };
+
+        Ok(Slot {
+            name,
+            content,
+            optional,
+        })
+    }
+
+    /// Returns the kind of the token `text` and registers it.
+    fn token_kind(&mut self, text: &str) -> String {
+        let (kind, text) = if text == "EOF" {
+            return "EOF".to_string();
+        } else if text
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        {
+            // A token without fixed text
+            (text.to_string(), None)
+        } else if text.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            (
+                format!("{}_KW", text.to_uppercase()),
+                Some(text.to_string()),
+            )
+        } else {
+            (punctuation_kind(text), Some(text.to_string()))
+        };
+
+        if !self.tokens.iter().any(|token| token.kind == kind) {
+            self.tokens.push(TokenKind {
+                kind: kind.clone(),
+                text,
+            });
+        }
+
+        kind
+    }
+
+    fn check_references(&self, node: &AstType, line: usize) -> Result<(), CodegenError> {
+        let references: Vec<&String> = match &node.shape {
+            Shape::Node(slots) => slots
+                .iter()
+                .filter_map(|slot| match &slot.content {
+                    SlotContent::Node(name) => Some(name),
+                    SlotContent::Token(_) => None,
+                })
+                .collect(),
+            Shape::Union(members) => members.iter().collect(),
+            Shape::List { element } | Shape::SeparatedList { element, .. } => vec![element],
+            Shape::Bogus => Vec::new(),
+        };
+
+        for reference in references {
+            if !self.rules.contains_key(reference.as_str()) {
+                return Err(CodegenError::new(
+                    line,
+                    format!(
+                        "`{}` references the undefined rule `{reference}`",
+                        node.name
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_list(rule: &Rule) -> bool {
+    match rule {
+        Rule::Repeated(_) => true,
+        Rule::Sequence(items) => matches!(items.get(1), Some(Rule::Repeated(_))),
+        _ => false,
+    }
+}
+
+/// Converts the nodes of unions with a bogus member to that member's kind.
+fn assign_bogus_kinds(nodes: &mut [AstType]) {
+    let mut assignments: Vec<(String, String)> = Vec::new();
+
+    for node in nodes.iter() {
+        let Shape::Union(members) = &node.shape else {
+            continue;
+        };
+
+        let Some(bogus) = members.iter().find_map(|member| {
+            let member = nodes.iter().find(|node| &node.name == member)?;
+            matches!(member.shape, Shape::Bogus).then(|| member.kind.clone())
+        }) else {
+            continue;
+        };
+
+        let mut pending: Vec<&String> = members.iter().collect();
+        let mut visited = HashSet::new();
+
+        while let Some(member) = pending.pop() {
+            if !visited.insert(member) {
+                continue;
+            }
+
+            let Some(member) = nodes.iter().find(|node| &node.name == member) else {
+                continue;
+            };
+
+            match &member.shape {
+                Shape::Union(nested) => pending.extend(nested),
+                Shape::Bogus => {}
+                _ => {
+                    if !assignments.iter().any(|(name, _)| name == &member.name) {
+                        assignments.push((member.name.clone(), bogus.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    for (name, bogus) in assignments {
+        if let Some(node) = nodes.iter_mut().find(|node| node.name == name) {
+            node.to_bogus = Some(bogus);
+        }
+    }
+}
+
+fn check_union_cycles(nodes: &[AstType], grammar: &Grammar) -> Result<(), CodegenError> {
+    fn visit<'a>(
+        nodes: &'a [AstType],
+        name: &'a str,
+        stack: &mut Vec<&'a str>,
+    ) -> Result<(), String> {
+        if stack.contains(&name) {
+            return Err(name.to_string());
+        }
+
+        let Some(AstType {
+            shape: Shape::Union(members),
+            ..
+        }) = nodes.iter().find(|node| node.name == name)
+        else {
+            return Ok(());
+        };
+
+        stack.push(name);
+        for member in members {
+            visit(nodes, member, stack)?;
+        }
+        stack.pop();
+
+        Ok(())
+    }
+
+    for (node, definition) in nodes.iter().zip(&grammar.rules) {
+        visit(nodes, &node.name, &mut Vec::new()).map_err(|name| {
+            CodegenError::new(
+                definition.line,
+                format!("the union `{name}` contains itself"),
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Rejects kinds that are generated more than once, which would emit duplicate enum variants.
+fn check_kind_collisions(
+    nodes: &[AstType],
+    tokens: &[TokenKind],
+    grammar: &Grammar,
+) -> Result<(), CodegenError> {
+    for token in tokens {
+        if RESERVED_KINDS.contains(&token.kind.as_str()) {
+            let line = nodes
+                .iter()
+                .zip(&grammar.rules)
+                .find(|(node, _)| uses_token(node, &token.kind))
+                .map_or(1, |(_, definition)| definition.line);
+
+            return Err(CodegenError::new(
+                line,
+                format!("the token kind `{}` is reserved", token.kind),
+            ));
+        }
+    }
+
+    let mut node_kinds: HashMap<&str, &str> = HashMap::new();
+
+    for (node, definition) in nodes.iter().zip(&grammar.rules) {
+        // Unions have no kind of their own
+        if node.kind.is_empty() {
+            continue;
+        }
+
+        let message = if let Some(other) = node_kinds.insert(&node.kind, &node.name) {
+            format!(
+                "`{}` and `{other}` both have the kind `{}`",
+                node.name, node.kind
+            )
+        } else if tokens.iter().any(|token| token.kind == node.kind) {
+            format!(
+                "the kind `{}` of `{}` is also a token kind",
+                node.kind, node.name
+            )
+        } else if RESERVED_KINDS.contains(&node.kind.as_str())
+            // The `Bogus` rule defines the default bogus kind
+            && !(node.kind == BOGUS && matches!(node.shape, Shape::Bogus))
+        {
+            format!("the kind `{}` of `{}` is reserved", node.kind, node.name)
+        } else {
+            continue;
+        };
+
+        return Err(CodegenError::new(definition.line, message));
+    }
+
+    Ok(())
+}
+
+fn uses_token(node: &AstType, kind: &str) -> bool {
+    match &node.shape {
+        Shape::Node(slots) => slots.iter().any(|slot| match &slot.content {
+            SlotContent::Token(kinds) => kinds.iter().any(|token| token == kind),
+            SlotContent::Node(_) => false,
+        }),
+        Shape::SeparatedList { separator, .. } => separator == kind,
+        Shape::Union(_) | Shape::List { .. } | Shape::Bogus => false,
+    }
+}
+
+/// Returns the kind name of a punctuation token, for example `L_PAREN` for `(`.
+fn punctuation_kind(text: &str) -> String {
+    let name = match text {
+        "==" => "EQ2",
+        "!=" => "NEQ",
+        "<=" => "LTEQ",
+        ">=" => "GTEQ",
+        "&&" => "AMP2",
+        "||" => "PIPE2",
+        "::" => "COLON2",
+        "->" => "THIN_ARROW",
+        "=>" => "FAT_ARROW",
+        "..." => "DOT3",
+        ".." => "DOT2",
+        _ => {
+            return text
+                .chars()
+                .map(|c| match c {
+                    '+' => "PLUS".to_string(),
+                    '-' => "MINUS".to_string(),
+                    '*' => "STAR".to_string(),
+                    '/' => "SLASH".to_string(),
+                    '%' => "PERCENT".to_string(),
+                    '^' => "CARET".to_string(),
+                    '~' => "TILDE".to_string(),
+                    '!' => "BANG".to_string(),
+                    '?' => "QUESTION".to_string(),
+                    '=' => "EQ".to_string(),
+                    '<' => "L_ANGLE".to_string(),
+                    '>' => "R_ANGLE".to_string(),
+                    '(' => "L_PAREN".to_string(),
+                    ')' => "R_PAREN".to_string(),
+                    '[' => "L_BRACK".to_string(),
+                    ']' => "R_BRACK".to_string(),
+                    '{' => "L_CURLY".to_string(),
+                    '}' => "R_CURLY".to_string(),
+                    ',' => "COMMA".to_string(),
+                    ';' => "SEMICOLON".to_string(),
+                    ':' => "COLON".to_string(),
+                    '.' => "DOT".to_string(),
+                    '&' => "AMP".to_string(),
+                    '|' => "PIPE".to_string(),
+                    '@' => "AT".to_string(),
+                    '#' => "HASH".to_string(),
+                    '$' => "DOLLAR".to_string(),
+                    '\'' => "QUOTE".to_string(),
+                    '"' => "DOUBLE_QUOTE".to_string(),
+                    '`' => "BACKTICK".to_string(),
+                    '\\' => "BACKSLASH".to_string(),
+                    c => format!("U{:04X}", c as u32),
+                })
+                .collect::<Vec<_>>()
+                .join("_");
+        }
+    };
+
+    name.to_string()
+}
+
+/// Converts `BinaryExpression` to `BINARY_EXPRESSION`.
+pub fn screaming_snake_case(name: &str) -> String {
+    snake_case(name).to_uppercase()
+}
+
+/// Converts `BinaryExpression` to `binary_expression`.
+pub fn snake_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    let mut previous: Option<char> = None;
+    let mut chars = name.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_uppercase() {
+            let next_is_lower = chars.peek().is_some_and(|next| next.is_lowercase());
+            let starts_word = match previous {
+                Some(previous) => {
+                    previous.is_lowercase()
+                        || previous.is_ascii_digit()
+                        || (previous.is_uppercase() && next_is_lower)
+                }
+                None => false,
+            };
+
+            if starts_word {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+
+        previous = Some(c);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lower(source: &str) -> Model {
+        Model::lower(&Grammar::parse(source).unwrap()).unwrap()
+    }
+
+    fn lower_error(source: &str) -> (usize, String) {
+        let error = Model::lower(&Grammar::parse(source).unwrap()).unwrap_err();
+        (error.line(), error.message().to_string())
+    }
+
+    #[test]
+    fn lowers_shapes() {
+        let model = lower(
+            "Root = items: Items 'EOF'\n\
+             Items = (Item (',' Item)* ','?)\n\
+             Item = Literal | Call | BogusItem\n\
+             Literal = 'NUMBER'\n\
+             Call = callee: 'IDENT' '(' Arguments ')'\n\
+             Arguments = Item*\n\
+             BogusItem = SyntaxElement*\n",
+        );
+
+        let shapes: Vec<(&str, &str, &Shape)> = model
+            .nodes
+            .iter()
+            .map(|node| (node.name.as_str(), node.kind.as_str(), &node.shape))
+            .collect();
+
+        assert_eq!(
+            shapes[1],
+            (
+                "Items",
+                "ITEMS",
+                &Shape::SeparatedList {
+                    element: "Item".to_string(),
+                    separator: "COMMA".to_string(),
+                    allow_trailing: true,
+                }
+            )
+        );
+        assert_eq!(
+            shapes[2],
+            (
+                "Item",
+                "",
+                &Shape::Union(vec![
+                    "Literal".to_string(),
+                    "Call".to_string(),
+                    "BogusItem".to_string()
+                ])
+            )
+        );
+        assert_eq!(
+            shapes[5],
+            (
+                "Arguments",
+                "ARGUMENTS",
+                &Shape::List {
+                    element: "Item".to_string()
+                }
+            )
+        );
+        assert_eq!(shapes[6], ("BogusItem", "BOGUS_ITEM", &Shape::Bogus));
+
+        let Shape::Node(slots) = &model.nodes[4].shape else {
+            panic!("`Call` should be a node");
+        };
+        let slot_names: Vec<&str> = slots.iter().map(|slot| slot.name.as_str()).collect();
+        assert_eq!(
+            slot_names,
+            ["callee", "l_paren_token", "arguments", "r_paren_token"]
+        );
+
+        let to_bogus: Vec<(&str, Option<&str>)> = model
+            .nodes
+            .iter()
+            .map(|node| (node.name.as_str(), node.to_bogus.as_deref()))
+            .collect();
+        assert_eq!(
+            to_bogus,
+            [
+                ("Root", None),
+                ("Items", None),
+                ("Item", None),
+                ("Literal", Some("BOGUS_ITEM")),
+                ("Call", Some("BOGUS_ITEM")),
+                ("Arguments", None),
+                ("BogusItem", None),
+            ]
+        );
+
+        assert_eq!(
+            model.tokens,
+            [
+                TokenKind {
+                    kind: "COMMA".to_string(),
+                    text: Some(",".to_string())
+                },
+                TokenKind {
+                    kind: "NUMBER".to_string(),
+                    text: None
+                },
+                TokenKind {
+                    kind: "IDENT".to_string(),
+                    text: None
+                },
+                TokenKind {
+                    kind: "L_PAREN".to_string(),
+                    text: Some("(".to_string())
+                },
+                TokenKind {
+                    kind: "R_PAREN".to_string(),
+                    text: Some(")".to_string())
+                },
+            ]
+        );
+        assert!(!model.has_bogus_rule);
+    }
+
+    #[test]
+    fn optional_slots() {
+        let model = lower("Root = Value? ';' 'EOF'\nValue = 'NUMBER'\n");
+        let Shape::Node(slots) = &model.nodes[0].shape else {
+            panic!("`Root` should be a node");
+        };
+        let optional: Vec<(&str, bool)> = slots
+            .iter()
+            .map(|slot| (slot.name.as_str(), slot.optional))
+            .collect();
+
+        assert_eq!(
+            optional,
+            [
+                ("value", true),
+                ("semicolon_token", false),
+                ("eof_token", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn rule_errors() {
+        assert_eq!(lower_error(""), (1, "the grammar has no rules".to_string()));
+        assert_eq!(
+            lower_error("Root = A\nA = 'a'\nA = 'b'"),
+            (3, "duplicate rule `A`".to_string())
+        );
+        assert_eq!(
+            lower_error("Root = A | B\nA = 'a'\nB = 'b'"),
+            (1, "the root rule can't be a union".to_string())
+        );
+        assert_eq!(
+            lower_error("Root = A\nA = B"),
+            (2, "`A` references the undefined rule `B`".to_string())
+        );
+        assert_eq!(
+            lower_error("Root = A\nA = B | C\nB = 'b'\nC = A | B"),
+            (2, "the union `A` contains itself".to_string())
+        );
+    }
+
+    #[test]
+    fn slot_errors() {
+        assert_eq!(
+            lower_error("Root = ('a' 'b')*"),
+            (1, "lists must repeat a single node: `Element*`".to_string())
+        );
+        assert_eq!(
+            lower_error("Root = A A\nA = 'a'"),
+            (
+                1,
+                "`Root` has two slots called `a`, label one of them".to_string()
+            )
+        );
+        assert_eq!(
+            lower_error("Root = ('+' | '-')"),
+            (
+                1,
+                "a slot with alternative tokens needs a label".to_string()
+            )
+        );
+        assert_eq!(
+            lower_error("Root = value: (A | 'b')\nA = 'a'"),
+            (
+                1,
+                "alternatives in a slot must all be tokens, define a union rule for nodes"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            lower_error("Root = 'a' A*\nA = 'a'"),
+            (
+                1,
+                "repetitions must be separate list rules: `Elements = Element*`".to_string()
+            )
+        );
+        assert_eq!(
+            lower_error("Root = 'a' (A 'b')\nA = 'a'"),
+            (
+                1,
+                "slots must be a node, a token, or alternative tokens".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn kind_collisions() {
+        assert_eq!(
+            lower_error("Root = a: JsxName b: JSXName\nJsxName = 'a'\nJSXName = 'b'"),
+            (
+                3,
+                "`JSXName` and `JsxName` both have the kind `JSX_NAME`".to_string()
+            )
+        );
+        assert_eq!(
+            lower_error("Root = Number\nNumber = 'NUMBER'"),
+            (
+                2,
+                "the kind `NUMBER` of `Number` is also a token kind".to_string()
+            )
+        );
+        assert_eq!(
+            lower_error("Root = Tombstone\nTombstone = 'a'"),
+            (
+                2,
+                "the kind `TOMBSTONE` of `Tombstone` is reserved".to_string()
+            )
+        );
+        assert_eq!(
+            lower_error("Root = A\nA = 'a' 'BOGUS'"),
+            (2, "the token kind `BOGUS` is reserved".to_string())
+        );
+    }
+
+    #[test]
+    fn the_bogus_rule_defines_the_default_bogus_kind() {
+        let model = lower("Root = 'a'\nBogus = SyntaxElement*");
+
+        assert!(model.has_bogus_rule);
+        assert_eq!(model.nodes[1].kind, BOGUS);
+        assert_eq!(model.nodes[1].shape, Shape::Bogus);
+    }
+
+    #[test]
+    fn case_conversions() {
+        assert_eq!(snake_case("BinaryExpression"), "binary_expression");
+        assert_eq!(snake_case("JSXElement"), "jsx_element");
+        assert_eq!(snake_case("Utf8String"), "utf8_string");
+        assert_eq!(screaming_snake_case("CallArguments"), "CALL_ARGUMENTS");
+        assert_eq!(punctuation_kind("=="), "EQ2");
+        assert_eq!(punctuation_kind("+="), "PLUS_EQ");
+        assert_eq!(punctuation_kind("€"), "U20AC");
+    }
+}
@@ -0,0 +1,55 @@
+//! Golden test of the code generated for the grammar in the crate documentation.
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to overwrite the snapshot with the current output.
+
+use std::{env, fs, path::Path};
+
+const GRAMMAR: &str = "\
+Root = statements: StatementList 'EOF'
+StatementList = Statement*
+Statement = ExpressionStatement | BogusStatement
+ExpressionStatement = Expression ';'?
+Expression = Literal | Binary | BogusExpression
+Literal = value: 'NUMBER'
+Binary = left: Expression operator: ('+' | '-') right: Expression
+BogusStatement = SyntaxElement*
+BogusExpression = SyntaxElement*
+";
+
+#[test]
+fn generates_the_documented_grammar() {
+    let generated = mini_rowan_codegen::generate(GRAMMAR, "Calc").unwrap();
+    let snapshot = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/calc.rs");
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&snapshot, &generated).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&snapshot).unwrap();
+    assert!(
+        generated == expected,
+        "the generated code differs from {}, rerun with UPDATE_SNAPSHOTS=1 to update it:\n{generated}",
+        snapshot.display()
+    );
+}
+
+#[test]
+fn generating_is_deterministic() {
+    let first = mini_rowan_codegen::generate(GRAMMAR, "Calc").unwrap();
+    let second = mini_rowan_codegen::generate(GRAMMAR, "Calc").unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn reports_errors_with_their_line() {
+    let error = mini_rowan_codegen::generate("Root = A\nA = B\n", "Calc").unwrap_err();
+
+    assert_eq!(error.line(), 2);
+    assert_eq!(error.message(), "`A` references the undefined rule `B`");
+    assert_eq!(
+        error.to_string(),
+        "line 2: `A` references the undefined rule `B`"
+    );
+}
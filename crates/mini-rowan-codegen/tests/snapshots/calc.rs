@@ -0,0 +1,610 @@
+// Generated by mini-rowan-codegen, do not edit.
+
+use mini_rowan::{
+    ast::{AstNode, SyntaxKindSet},
+    green::RawSyntaxKind,
+    syntax::{
+        Language, SyntaxKind, element::SyntaxElement, node::SyntaxNode,
+        token::SyntaxToken,
+    },
+    syntax_factory::{
+        RawNodeSlots, SyntaxFactory, parsed_children::ParsedChildren,
+        raw_syntax::RawSyntaxNode, schema::{ErasedSlotSchema, SchemaSlot, SlotSchema},
+    },
+};
+use std::borrow::Cow;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, SyntaxKind)]
+#[syntax_kind(language = CalcLanguage)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+pub enum CalcSyntaxKind {
+    #[tombstone]
+    TOMBSTONE,
+    #[eof]
+    EOF,
+    #[token(";")]
+    SEMICOLON,
+    NUMBER,
+    #[token("+")]
+    PLUS,
+    #[token("-")]
+    MINUS,
+    #[root]
+    ROOT,
+    #[list]
+    STATEMENT_LIST,
+    #[to_bogus(BOGUS_STATEMENT)]
+    EXPRESSION_STATEMENT,
+    #[to_bogus(BOGUS_EXPRESSION)]
+    LITERAL,
+    #[to_bogus(BOGUS_EXPRESSION)]
+    BINARY,
+    #[bogus]
+    BOGUS,
+    #[bogus]
+    BOGUS_STATEMENT,
+    #[bogus]
+    BOGUS_EXPRESSION,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalcLanguage;
+impl Language for CalcLanguage {
+    type Kind = CalcSyntaxKind;
+    type Root = Root;
+    fn slot_schema() -> Option<ErasedSlotSchema<Self>> {
+        Some(ErasedSlotSchema::new())
+    }
+}
+impl SlotSchema for CalcLanguage {
+    fn slots(kind: CalcSyntaxKind) -> Option<&'static [SchemaSlot<Self>]> {
+        match kind {
+            CalcSyntaxKind::ROOT => {
+                const SLOTS: &[SchemaSlot<CalcLanguage>] = &[
+                    SchemaSlot {
+                        name: Cow::Borrowed("statements"),
+                        kinds: StatementList::KIND_SET,
+                        optional: false,
+                    },
+                    SchemaSlot {
+                        name: Cow::Borrowed("eof_token"),
+                        kinds: mini_rowan::syntax_kind_set![CalcSyntaxKind::EOF],
+                        optional: false,
+                    },
+                ];
+                Some(SLOTS)
+            }
+            CalcSyntaxKind::EXPRESSION_STATEMENT => {
+                const SLOTS: &[SchemaSlot<CalcLanguage>] = &[
+                    SchemaSlot {
+                        name: Cow::Borrowed("expression"),
+                        kinds: Expression::KIND_SET,
+                        optional: false,
+                    },
+                    SchemaSlot {
+                        name: Cow::Borrowed("semicolon_token"),
+                        kinds: mini_rowan::syntax_kind_set![CalcSyntaxKind::SEMICOLON],
+                        optional: true,
+                    },
+                ];
+                Some(SLOTS)
+            }
+            CalcSyntaxKind::LITERAL => {
+                const SLOTS: &[SchemaSlot<CalcLanguage>] = &[
+                    SchemaSlot {
+                        name: Cow::Borrowed("value"),
+                        kinds: mini_rowan::syntax_kind_set![CalcSyntaxKind::NUMBER],
+                        optional: false,
+                    },
+                ];
+                Some(SLOTS)
+            }
+            CalcSyntaxKind::BINARY => {
+                const SLOTS: &[SchemaSlot<CalcLanguage>] = &[
+                    SchemaSlot {
+                        name: Cow::Borrowed("left"),
+                        kinds: Expression::KIND_SET,
+                        optional: false,
+                    },
+                    SchemaSlot {
+                        name: Cow::Borrowed("operator"),
+                        kinds: mini_rowan::syntax_kind_set![
+                            CalcSyntaxKind::PLUS, CalcSyntaxKind::MINUS
+                        ],
+                        optional: false,
+                    },
+                    SchemaSlot {
+                        name: Cow::Borrowed("right"),
+                        kinds: Expression::KIND_SET,
+                        optional: false,
+                    },
+                ];
+                Some(SLOTS)
+            }
+            _ => None,
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Root {
+    syntax: SyntaxNode<CalcLanguage>,
+}
+impl Root {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        Self::can_cast(syntax.kind()).then_some(Self { syntax })
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        &self.syntax
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        self.syntax
+    }
+    pub fn statements(&self) -> Option<StatementList> {
+        self.syntax.element_in_slot(0)?.into_node().and_then(StatementList::cast)
+    }
+    pub fn eof_token(&self) -> Option<SyntaxToken<CalcLanguage>> {
+        self.syntax.element_in_slot(1)?.into_token()
+    }
+}
+impl AstNode for Root {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::from_raw(
+        RawSyntaxKind(CalcSyntaxKind::ROOT as u16),
+    );
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        kind == CalcSyntaxKind::ROOT
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatementList {
+    syntax: SyntaxNode<CalcLanguage>,
+}
+impl StatementList {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        Self::can_cast(syntax.kind()).then_some(Self { syntax })
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        &self.syntax
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        self.syntax
+    }
+    /// Returns the elements of the list.
+    pub fn iter(&self) -> impl Iterator<Item = Statement> {
+        self.syntax.children().filter_map(Statement::cast)
+    }
+    pub fn len(&self) -> usize {
+        self.syntax.slots().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+impl AstNode for StatementList {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::from_raw(
+        RawSyntaxKind(CalcSyntaxKind::STATEMENT_LIST as u16),
+    );
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        kind == CalcSyntaxKind::STATEMENT_LIST
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::enum_variant_names)]
+pub enum Statement {
+    ExpressionStatement(ExpressionStatement),
+    BogusStatement(BogusStatement),
+}
+impl Statement {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        let kind = syntax.kind();
+        if ExpressionStatement::can_cast(kind) {
+            return ExpressionStatement::cast(syntax).map(Self::ExpressionStatement);
+        }
+        if BogusStatement::can_cast(kind) {
+            return BogusStatement::cast(syntax).map(Self::BogusStatement);
+        }
+        None
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        match self {
+            Self::ExpressionStatement(it) => it.syntax(),
+            Self::BogusStatement(it) => it.syntax(),
+        }
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        match self {
+            Self::ExpressionStatement(it) => it.into_syntax(),
+            Self::BogusStatement(it) => it.into_syntax(),
+        }
+    }
+}
+impl AstNode for Statement {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::empty()
+        .union(ExpressionStatement::KIND_SET)
+        .union(BogusStatement::KIND_SET);
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        ExpressionStatement::can_cast(kind) || BogusStatement::can_cast(kind)
+    }
+}
+impl From<ExpressionStatement> for Statement {
+    fn from(node: ExpressionStatement) -> Self {
+        Self::ExpressionStatement(node)
+    }
+}
+impl From<BogusStatement> for Statement {
+    fn from(node: BogusStatement) -> Self {
+        Self::BogusStatement(node)
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExpressionStatement {
+    syntax: SyntaxNode<CalcLanguage>,
+}
+impl ExpressionStatement {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        Self::can_cast(syntax.kind()).then_some(Self { syntax })
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        &self.syntax
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        self.syntax
+    }
+    pub fn expression(&self) -> Option<Expression> {
+        self.syntax.element_in_slot(0)?.into_node().and_then(Expression::cast)
+    }
+    pub fn semicolon_token(&self) -> Option<SyntaxToken<CalcLanguage>> {
+        self.syntax.element_in_slot(1)?.into_token()
+    }
+}
+impl AstNode for ExpressionStatement {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::from_raw(
+        RawSyntaxKind(CalcSyntaxKind::EXPRESSION_STATEMENT as u16),
+    );
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        kind == CalcSyntaxKind::EXPRESSION_STATEMENT
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::enum_variant_names)]
+pub enum Expression {
+    Literal(Literal),
+    Binary(Binary),
+    BogusExpression(BogusExpression),
+}
+impl Expression {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        let kind = syntax.kind();
+        if Literal::can_cast(kind) {
+            return Literal::cast(syntax).map(Self::Literal);
+        }
+        if Binary::can_cast(kind) {
+            return Binary::cast(syntax).map(Self::Binary);
+        }
+        if BogusExpression::can_cast(kind) {
+            return BogusExpression::cast(syntax).map(Self::BogusExpression);
+        }
+        None
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        match self {
+            Self::Literal(it) => it.syntax(),
+            Self::Binary(it) => it.syntax(),
+            Self::BogusExpression(it) => it.syntax(),
+        }
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        match self {
+            Self::Literal(it) => it.into_syntax(),
+            Self::Binary(it) => it.into_syntax(),
+            Self::BogusExpression(it) => it.into_syntax(),
+        }
+    }
+}
+impl AstNode for Expression {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::empty()
+        .union(Literal::KIND_SET)
+        .union(Binary::KIND_SET)
+        .union(BogusExpression::KIND_SET);
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        Literal::can_cast(kind) || Binary::can_cast(kind)
+            || BogusExpression::can_cast(kind)
+    }
+}
+impl From<Literal> for Expression {
+    fn from(node: Literal) -> Self {
+        Self::Literal(node)
+    }
+}
+impl From<Binary> for Expression {
+    fn from(node: Binary) -> Self {
+        Self::Binary(node)
+    }
+}
+impl From<BogusExpression> for Expression {
+    fn from(node: BogusExpression) -> Self {
+        Self::BogusExpression(node)
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Literal {
+    syntax: SyntaxNode<CalcLanguage>,
+}
+impl Literal {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        Self::can_cast(syntax.kind()).then_some(Self { syntax })
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        &self.syntax
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        self.syntax
+    }
+    pub fn value(&self) -> Option<SyntaxToken<CalcLanguage>> {
+        self.syntax.element_in_slot(0)?.into_token()
+    }
+}
+impl AstNode for Literal {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::from_raw(
+        RawSyntaxKind(CalcSyntaxKind::LITERAL as u16),
+    );
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        kind == CalcSyntaxKind::LITERAL
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Binary {
+    syntax: SyntaxNode<CalcLanguage>,
+}
+impl Binary {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        Self::can_cast(syntax.kind()).then_some(Self { syntax })
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        &self.syntax
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        self.syntax
+    }
+    pub fn left(&self) -> Option<Expression> {
+        self.syntax.element_in_slot(0)?.into_node().and_then(Expression::cast)
+    }
+    pub fn operator(&self) -> Option<SyntaxToken<CalcLanguage>> {
+        self.syntax.element_in_slot(1)?.into_token()
+    }
+    pub fn right(&self) -> Option<Expression> {
+        self.syntax.element_in_slot(2)?.into_node().and_then(Expression::cast)
+    }
+}
+impl AstNode for Binary {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::from_raw(
+        RawSyntaxKind(CalcSyntaxKind::BINARY as u16),
+    );
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        kind == CalcSyntaxKind::BINARY
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BogusStatement {
+    syntax: SyntaxNode<CalcLanguage>,
+}
+impl BogusStatement {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        Self::can_cast(syntax.kind()).then_some(Self { syntax })
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        &self.syntax
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        self.syntax
+    }
+    /// Returns the nodes and tokens of the bogus node.
+    pub fn items(&self) -> impl Iterator<Item = SyntaxElement<CalcLanguage>> {
+        self.syntax.children_with_tokens()
+    }
+}
+impl AstNode for BogusStatement {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::from_raw(
+        RawSyntaxKind(CalcSyntaxKind::BOGUS_STATEMENT as u16),
+    );
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        kind == CalcSyntaxKind::BOGUS_STATEMENT
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BogusExpression {
+    syntax: SyntaxNode<CalcLanguage>,
+}
+impl BogusExpression {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        Self::can_cast(syntax.kind()).then_some(Self { syntax })
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        &self.syntax
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        self.syntax
+    }
+    /// Returns the nodes and tokens of the bogus node.
+    pub fn items(&self) -> impl Iterator<Item = SyntaxElement<CalcLanguage>> {
+        self.syntax.children_with_tokens()
+    }
+}
+impl AstNode for BogusExpression {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::from_raw(
+        RawSyntaxKind(CalcSyntaxKind::BOGUS_EXPRESSION as u16),
+    );
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        kind == CalcSyntaxKind::BOGUS_EXPRESSION
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bogus {
+    syntax: SyntaxNode<CalcLanguage>,
+}
+impl Bogus {
+    pub fn cast(syntax: SyntaxNode<CalcLanguage>) -> Option<Self> {
+        Self::can_cast(syntax.kind()).then_some(Self { syntax })
+    }
+    pub fn syntax(&self) -> &SyntaxNode<CalcLanguage> {
+        &self.syntax
+    }
+    pub fn into_syntax(self) -> SyntaxNode<CalcLanguage> {
+        self.syntax
+    }
+    /// Returns the nodes and tokens of the bogus node.
+    pub fn items(&self) -> impl Iterator<Item = SyntaxElement<CalcLanguage>> {
+        self.syntax.children_with_tokens()
+    }
+}
+impl AstNode for Bogus {
+    type Language = CalcLanguage;
+    const KIND_SET: SyntaxKindSet<CalcLanguage> = SyntaxKindSet::from_raw(
+        RawSyntaxKind(CalcSyntaxKind::BOGUS as u16),
+    );
+    fn can_cast(kind: CalcSyntaxKind) -> bool {
+        kind == CalcSyntaxKind::BOGUS
+    }
+}
+#[derive(Debug)]
+pub struct CalcSyntaxFactory;
+impl SyntaxFactory for CalcSyntaxFactory {
+    type Kind = CalcSyntaxKind;
+    fn make_syntax(
+        kind: CalcSyntaxKind,
+        children: ParsedChildren<CalcSyntaxKind>,
+    ) -> RawSyntaxNode<CalcSyntaxKind> {
+        match kind {
+            CalcSyntaxKind::ROOT => {
+                let mut elements = (&children).into_iter();
+                let mut slots: RawNodeSlots<2> = RawNodeSlots::default();
+                let mut current_element = elements.next();
+                if current_element
+                    .as_ref()
+                    .is_some_and(|element| StatementList::can_cast(element.kind()))
+                {
+                    slots.mark_present();
+                    current_element = elements.next();
+                }
+                slots.next_slot();
+                if current_element
+                    .as_ref()
+                    .is_some_and(|element| matches!(element.kind(), CalcSyntaxKind::EOF))
+                {
+                    slots.mark_present();
+                    current_element = elements.next();
+                }
+                slots.next_slot();
+                if current_element.is_some() {
+                    return RawSyntaxNode::new(
+                        kind.to_bogus(),
+                        children.into_iter().map(Some),
+                    );
+                }
+                slots.into_node(kind, children)
+            }
+            CalcSyntaxKind::STATEMENT_LIST => {
+                Self::make_node_list_syntax(kind, children, Statement::can_cast)
+            }
+            CalcSyntaxKind::EXPRESSION_STATEMENT => {
+                let mut elements = (&children).into_iter();
+                let mut slots: RawNodeSlots<2> = RawNodeSlots::default();
+                let mut current_element = elements.next();
+                if current_element
+                    .as_ref()
+                    .is_some_and(|element| Expression::can_cast(element.kind()))
+                {
+                    slots.mark_present();
+                    current_element = elements.next();
+                }
+                slots.next_slot();
+                if current_element
+                    .as_ref()
+                    .is_some_and(|element| {
+                        matches!(element.kind(), CalcSyntaxKind::SEMICOLON)
+                    })
+                {
+                    slots.mark_present();
+                    current_element = elements.next();
+                }
+                slots.next_slot();
+                if current_element.is_some() {
+                    return RawSyntaxNode::new(
+                        kind.to_bogus(),
+                        children.into_iter().map(Some),
+                    );
+                }
+                slots.into_node(kind, children)
+            }
+            CalcSyntaxKind::LITERAL => {
+                let mut elements = (&children).into_iter();
+                let mut slots: RawNodeSlots<1> = RawNodeSlots::default();
+                let mut current_element = elements.next();
+                if current_element
+                    .as_ref()
+                    .is_some_and(|element| {
+                        matches!(element.kind(), CalcSyntaxKind::NUMBER)
+                    })
+                {
+                    slots.mark_present();
+                    current_element = elements.next();
+                }
+                slots.next_slot();
+                if current_element.is_some() {
+                    return RawSyntaxNode::new(
+                        kind.to_bogus(),
+                        children.into_iter().map(Some),
+                    );
+                }
+                slots.into_node(kind, children)
+            }
+            CalcSyntaxKind::BINARY => {
+                let mut elements = (&children).into_iter();
+                let mut slots: RawNodeSlots<3> = RawNodeSlots::default();
+                let mut current_element = elements.next();
+                if current_element
+                    .as_ref()
+                    .is_some_and(|element| Expression::can_cast(element.kind()))
+                {
+                    slots.mark_present();
+                    current_element = elements.next();
+                }
+                slots.next_slot();
+                if current_element
+                    .as_ref()
+                    .is_some_and(|element| {
+                        matches!(
+                            element.kind(), CalcSyntaxKind::PLUS | CalcSyntaxKind::MINUS
+                        )
+                    })
+                {
+                    slots.mark_present();
+                    current_element = elements.next();
+                }
+                slots.next_slot();
+                if current_element
+                    .as_ref()
+                    .is_some_and(|element| Expression::can_cast(element.kind()))
+                {
+                    slots.mark_present();
+                    current_element = elements.next();
+                }
+                slots.next_slot();
+                if current_element.is_some() {
+                    return RawSyntaxNode::new(
+                        kind.to_bogus(),
+                        children.into_iter().map(Some),
+                    );
+                }
+                slots.into_node(kind, children)
+            }
+            _ => RawSyntaxNode::new(kind, children.into_iter().map(Some)),
+        }
+    }
+}
@@ -0,0 +1,637 @@
+//! Derive macros for `mini-rowan`.
+//!
+//! [`#[derive(SyntaxKind)]`](derive_syntax_kind) implements `mini_rowan::syntax::SyntaxKind` for a fieldless enum.
+//! The crate is re-exported by `mini-rowan` with the `macros` feature; depend on it directly only if you
+//! don't use that feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Fields, LitStr, Path, Token, parse_macro_input,
+    punctuated::Punctuated,
+};
+
+/// Implements `SyntaxKind` for a fieldless enum and generates a kind-name table and `SyntaxKindSet` constants.
+///
+/// The raw kind of a variant is its index, so the enum must not have explicit discriminants.
+/// The enum must also derive `Debug`, `PartialEq`, `Copy` and `Clone`, which `SyntaxKind` requires.
+///
+/// The variants are classified with attributes:
+///
+/// * `#[tombstone]` and `#[eof]` mark the `TOMBSTONE` and `EOF` kinds. Both are required exactly once.
+/// * `#[token("+")]` gives the text of a keyword or punctuation token, returned by `to_string`.
+/// * `#[list]`, `#[root]` and `#[trivia]` mark the kinds for which `is_list`, `is_root` and `is_trivia` return `true`.
+/// * `#[separated_list(COMMA)]` marks a list whose elements are separated by `COMMA` tokens, returned by
+///   `list_separator`.
+/// * `#[bogus]` marks a bogus kind. At least one is required; `to_bogus` returns the first one unless a
+///   kind names another with `#[to_bogus(BOGUS_EXPRESSION)]`. Bogus kinds convert to themselves.
+/// * `#[kind_set(EXPRESSIONS, ...)]` adds the kind to the named `SyntaxKindSet` constants.
+///
+/// The enum gets the inherent constants `VARIANTS` and `NAMES` with all kinds and their names, ordered by
+/// raw kind, and the methods `name` and `from_name`. If the enum names its language with
+/// `#[syntax_kind(language = MyLanguage)]`, it also gets the `SyntaxKindSet<MyLanguage>` constants
+/// `TOKEN_KINDS`, `LIST_KINDS`, `BOGUS_KINDS` and `TRIVIA_KINDS`, and one for each `#[kind_set]` name.
+///
+/// ```ignore
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, SyntaxKind)]
+/// #[syntax_kind(language = MyLanguage)]
+/// enum MyKind {
+///     #[tombstone]
+///     TOMBSTONE,
+///     #[eof]
+///     EOF,
+///     #[token("+")]
+///     PLUS,
+///     #[token(",")]
+///     COMMA,
+///     #[trivia]
+///     WHITESPACE,
+///     #[root]
+///     ROOT,
+///     #[list]
+///     STATEMENT_LIST,
+///     #[separated_list(COMMA)]
+///     ARGUMENT_LIST,
+///     #[kind_set(EXPRESSIONS)]
+///     #[to_bogus(BOGUS_EXPRESSION)]
+///     BINARY_EXPRESSION,
+///     #[bogus]
+///     BOGUS,
+///     #[bogus]
+///     #[kind_set(EXPRESSIONS)]
+///     BOGUS_EXPRESSION,
+/// }
+/// ```
+#[proc_macro_derive(
+    SyntaxKind,
+    attributes(
+        syntax_kind,
+        token,
+        list,
+        separated_list,
+        root,
+        bogus,
+        to_bogus,
+        trivia,
+        tombstone,
+        eof,
+        kind_set
+    )
+)]
+pub fn derive_syntax_kind(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_syntax_kind(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// A variant and its attributes.
+struct KindVariant {
+    name: Ident,
+    token: Option<LitStr>,
+    list: bool,
+    separator: Option<Ident>,
+    root: bool,
+    bogus: bool,
+    to_bogus: Option<Ident>,
+    trivia: bool,
+    tombstone: bool,
+    eof: bool,
+    kind_sets: Vec<Ident>,
+}
+
+fn expand_syntax_kind(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`SyntaxKind` can only be derived for enums",
+        ));
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`SyntaxKind` can't be derived for generic enums",
+        ));
+    }
+
+    let language = parse_language(&input.attrs)?;
+    let mut variants = Vec::with_capacity(data.variants.len());
+
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "`SyntaxKind` variants can't have fields",
+            ));
+        }
+
+        if let Some((_, discriminant)) = &variant.discriminant {
+            return Err(syn::Error::new_spanned(
+                discriminant,
+                "the raw kind of a variant is its index, remove the explicit discriminant",
+            ));
+        }
+
+        variants.push(parse_variant(&variant.ident, &variant.attrs)?);
+    }
+
+    let count = u16::try_from(variants.len())
+        .ok()
+        .filter(|count| usize::from(*count) <= 5 * 128)
+        .ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "too many kinds, at most 640 are supported",
+            )
+        })?;
+
+    let name = |kind: &KindVariant| kind.name.clone();
+    let single = |predicate: fn(&KindVariant) -> bool, attribute: &str| {
+        let mut matching = variants.iter().filter(|kind| predicate(kind));
+
+        match (matching.next(), matching.next()) {
+            (Some(kind), None) => Ok(name(kind)),
+            (_, Some(second)) => Err(syn::Error::new_spanned(
+                name(second),
+                format!("only one variant can be marked with `#[{attribute}]`"),
+            )),
+            (None, None) => Err(syn::Error::new(
+                Span::call_site(),
+                format!("one variant must be marked with `#[{attribute}]`"),
+            )),
+        }
+    };
+
+    let tombstone = single(|kind| kind.tombstone, "tombstone")?;
+    let eof = single(|kind| kind.eof, "eof")?;
+    let default_bogus = variants
+        .iter()
+        .find(|kind| kind.bogus)
+        .map(name)
+        .ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "at least one variant must be marked with `#[bogus]`",
+            )
+        })?;
+
+    for kind in &variants {
+        if let Some(target) = &kind.to_bogus
+            && !variants
+                .iter()
+                .any(|other| other.bogus && &other.name == target)
+        {
+            return Err(syn::Error::new_spanned(
+                target,
+                "`#[to_bogus]` must name a variant marked with `#[bogus]`",
+            ));
+        }
+    }
+
+    let names_where = |predicate: fn(&KindVariant) -> bool| -> Vec<Ident> {
+        variants
+            .iter()
+            .filter(|kind| predicate(kind))
+            .map(name)
+            .collect()
+    };
+
+    let matches_any = |kinds: &[Ident]| {
+        if kinds.is_empty() {
+            quote!(false)
+        } else {
+            quote!(matches!(self, #(Self::#kinds)|*))
+        }
+    };
+
+    let ident = &input.ident;
+    let all: Vec<Ident> = variants.iter().map(name).collect();
+    let all_names: Vec<String> = all.iter().map(Ident::to_string).collect();
+    let count_usize = usize::from(count);
+
+    let is_bogus = matches_any(&names_where(|kind| kind.bogus));
+    let is_list = matches_any(&names_where(|kind| kind.list));
+    let is_root = matches_any(&names_where(|kind| kind.root));
+    let is_trivia = matches_any(&names_where(|kind| kind.trivia));
+
+    let to_bogus_arms = variants.iter().filter_map(|kind| {
+        let target = kind.to_bogus.as_ref()?;
+        let source = name(kind);
+        Some(quote!(Self::#source => Self::#target,))
+    });
+
+    let separator_arms = variants.iter().filter_map(|kind| {
+        let separator = kind.separator.as_ref()?;
+        let source = name(kind);
+        Some(quote!(Self::#source => Some(Self::#separator),))
+    });
+
+    let token_arms = variants.iter().filter_map(|kind| {
+        let text = kind.token.as_ref()?;
+        let source = name(kind);
+        Some(quote!(Self::#source => #text,))
+    });
+
+    let kind_sets = language.map(|language| {
+        let mut sets = vec![
+            (
+                Ident::new("TOKEN_KINDS", Span::call_site()),
+                names_where(|kind| kind.token.is_some()),
+            ),
+            (
+                Ident::new("LIST_KINDS", Span::call_site()),
+                names_where(|kind| kind.list),
+            ),
+            (
+                Ident::new("BOGUS_KINDS", Span::call_site()),
+                names_where(|kind| kind.bogus),
+            ),
+            (
+                Ident::new("TRIVIA_KINDS", Span::call_site()),
+                names_where(|kind| kind.trivia),
+            ),
+        ];
+
+        for kind in &variants {
+            for set in &kind.kind_sets {
+                match sets.iter_mut().find(|(name, _)| name == set) {
+                    Some((_, kinds)) => kinds.push(name(kind)),
+                    None => sets.push((set.clone(), vec![name(kind)])),
+                }
+            }
+        }
+
+        let sets = sets.into_iter().map(|(set, kinds)| {
+            quote! {
+                pub const #set: ::mini_rowan::ast::SyntaxKindSet<#language> =
+                    ::mini_rowan::ast::SyntaxKindSet::empty()
+                        #(.union(::mini_rowan::ast::SyntaxKindSet::from_raw(
+                            ::mini_rowan::green::RawSyntaxKind(Self::#kinds as u16)
+                        )))*;
+            }
+        });
+
+        quote!(#(#sets)*)
+    });
+
+    Ok(quote! {
+        impl ::mini_rowan::syntax::SyntaxKind for #ident {
+            const TOMBSTONE: Self = Self::#tombstone;
+            const EOF: Self = Self::#eof;
+            const KIND_COUNT: u16 = #count;
+
+            fn is_bogus(&self) -> bool {
+                #is_bogus
+            }
+
+            fn to_bogus(&self) -> Self {
+                if ::mini_rowan::syntax::SyntaxKind::is_bogus(self) {
+                    return *self;
+                }
+
+                match self {
+                    #(#to_bogus_arms)*
+                    _ => Self::#default_bogus,
+                }
+            }
+
+            fn to_raw(&self) -> ::mini_rowan::green::RawSyntaxKind {
+                ::mini_rowan::green::RawSyntaxKind(*self as u16)
+            }
+
+            fn from_raw(raw: ::mini_rowan::green::RawSyntaxKind) -> Self {
+                assert!(raw.0 < #count, "raw kind {} is out of bounds", raw.0);
+                Self::VARIANTS[usize::from(raw.0)]
+            }
+
+            fn is_root(&self) -> bool {
+                #is_root
+            }
+
+            fn is_list(&self) -> bool {
+                #is_list
+            }
+
+            fn list_separator(&self) -> Option<Self> {
+                match self {
+                    #(#separator_arms)*
+                    _ => None,
+                }
+            }
+
+            fn is_trivia(self) -> bool {
+                #is_trivia
+            }
+
+            fn to_string(&self) -> Option<&'static str> {
+                let text = match self {
+                    #(#token_arms)*
+                    _ => return None,
+                };
+                Some(text)
+            }
+        }
+
+        impl #ident {
+            /// All kinds, ordered by their raw kind.
+            pub const VARIANTS: [Self; #count_usize] = [#(Self::#all),*];
+
+            /// The names of all kinds, ordered by their raw kind.
+            pub const NAMES: [&'static str; #count_usize] = [#(#all_names),*];
+
+            #kind_sets
+
+            /// Returns the name of the kind's variant.
+            pub const fn name(self) -> &'static str {
+                Self::NAMES[self as usize]
+            }
+
+            /// Returns the kind whose variant is called `name`.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#all_names => Some(Self::#all),)*
+                    _ => None,
+                }
+            }
+        }
+    })
+}
+
+/// Parses `#[syntax_kind(language = Path)]`.
+fn parse_language(attributes: &[Attribute]) -> syn::Result<Option<Path>> {
+    let mut language = None;
+
+    for attribute in attributes {
+        if !attribute.path().is_ident("syntax_kind") {
+            continue;
+        }
+
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("language") {
+                language = Some(meta.value()?.parse::<Path>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported `syntax_kind` argument, expected `language`"))
+            }
+        })?;
+    }
+
+    Ok(language)
+}
+
+fn parse_variant(name: &Ident, attributes: &[Attribute]) -> syn::Result<KindVariant> {
+    let mut kind = KindVariant {
+        name: name.clone(),
+        token: None,
+        list: false,
+        separator: None,
+        root: false,
+        bogus: false,
+        to_bogus: None,
+        trivia: false,
+        tombstone: false,
+        eof: false,
+        kind_sets: Vec::new(),
+    };
+
+    for attribute in attributes {
+        let path = attribute.path();
+        let flag = if path.is_ident("list") {
+            &mut kind.list
+        } else if path.is_ident("root") {
+            &mut kind.root
+        } else if path.is_ident("bogus") {
+            &mut kind.bogus
+        } else if path.is_ident("trivia") {
+            &mut kind.trivia
+        } else if path.is_ident("tombstone") {
+            &mut kind.tombstone
+        } else if path.is_ident("eof") {
+            &mut kind.eof
+        } else if path.is_ident("token") {
+            kind.token = Some(attribute.parse_args()?);
+            continue;
+        } else if path.is_ident("separated_list") {
+            kind.list = true;
+            kind.separator = Some(attribute.parse_args()?);
+            continue;
+        } else if path.is_ident("to_bogus") {
+            kind.to_bogus = Some(attribute.parse_args()?);
+            continue;
+        } else if path.is_ident("kind_set") {
+            let sets =
+                attribute.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+            kind.kind_sets.extend(sets);
+            continue;
+        } else {
+            continue;
+        };
+
+        attribute.meta.require_path_only()?;
+        *flag = true;
+    }
+
+    if kind.bogus
+        && let Some(target) = &kind.to_bogus
+    {
+        return Err(syn::Error::new_spanned(
+            target,
+            "a `#[bogus]` variant converts to itself and can't have `#[to_bogus]`",
+        ));
+    }
+
+    Ok(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn expand_error(input: DeriveInput) -> String {
+        expand_syntax_kind(&input)
+            .expect_err("the expansion should fail")
+            .to_string()
+    }
+
+    #[test]
+    fn expands_a_valid_enum() {
+        let input: DeriveInput = parse_quote! {
+            #[syntax_kind(language = TestLanguage)]
+            enum TestKind {
+                #[tombstone]
+                TOMBSTONE,
+                #[eof]
+                EOF,
+                #[separated_list(COMMA)]
+                LIST,
+                #[bogus]
+                BOGUS,
+            }
+        };
+
+        let expanded = expand_syntax_kind(&input).unwrap().to_string();
+        assert!(
+            expanded.contains("const KIND_COUNT : u16 = 4u16"),
+            "{expanded}"
+        );
+        assert!(expanded.contains("pub const LIST_KINDS"), "{expanded}");
+    }
+
+    #[test]
+    fn requires_an_eof_variant() {
+        let input = parse_quote! {
+            enum TestKind {
+                #[tombstone]
+                TOMBSTONE,
+                #[bogus]
+                BOGUS,
+            }
+        };
+
+        assert_eq!(
+            expand_error(input),
+            "one variant must be marked with `#[eof]`"
+        );
+    }
+
+    #[test]
+    fn rejects_a_second_tombstone() {
+        let input = parse_quote! {
+            enum TestKind {
+                #[tombstone]
+                TOMBSTONE,
+                #[tombstone]
+                OTHER_TOMBSTONE,
+                #[eof]
+                EOF,
+                #[bogus]
+                BOGUS,
+            }
+        };
+
+        assert_eq!(
+            expand_error(input),
+            "only one variant can be marked with `#[tombstone]`"
+        );
+    }
+
+    #[test]
+    fn requires_a_bogus_variant() {
+        let input = parse_quote! {
+            enum TestKind {
+                #[tombstone]
+                TOMBSTONE,
+                #[eof]
+                EOF,
+            }
+        };
+
+        assert_eq!(
+            expand_error(input),
+            "at least one variant must be marked with `#[bogus]`"
+        );
+    }
+
+    #[test]
+    fn rejects_explicit_discriminants() {
+        let input = parse_quote! {
+            enum TestKind {
+                #[tombstone]
+                TOMBSTONE,
+                #[eof]
+                EOF = 5,
+                #[bogus]
+                BOGUS,
+            }
+        };
+
+        assert_eq!(
+            expand_error(input),
+            "the raw kind of a variant is its index, remove the explicit discriminant"
+        );
+    }
+
+    #[test]
+    fn rejects_to_bogus_targets_that_are_not_bogus() {
+        let input = parse_quote! {
+            enum TestKind {
+                #[tombstone]
+                TOMBSTONE,
+                #[eof]
+                EOF,
+                #[to_bogus(EOF)]
+                EXPRESSION,
+                #[bogus]
+                BOGUS,
+            }
+        };
+
+        assert_eq!(
+            expand_error(input),
+            "`#[to_bogus]` must name a variant marked with `#[bogus]`"
+        );
+    }
+
+    #[test]
+    fn rejects_to_bogus_on_bogus_variants() {
+        let input = parse_quote! {
+            enum TestKind {
+                #[tombstone]
+                TOMBSTONE,
+                #[eof]
+                EOF,
+                #[bogus]
+                #[to_bogus(BOGUS)]
+                BOGUS,
+            }
+        };
+
+        assert_eq!(
+            expand_error(input),
+            "a `#[bogus]` variant converts to itself and can't have `#[to_bogus]`"
+        );
+    }
+
+    #[test]
+    fn rejects_variants_with_fields_and_structs() {
+        let input = parse_quote! {
+            enum TestKind {
+                #[tombstone]
+                TOMBSTONE(u16),
+            }
+        };
+        assert_eq!(
+            expand_error(input),
+            "`SyntaxKind` variants can't have fields"
+        );
+
+        let input = parse_quote! {
+            struct TestKind;
+        };
+        assert_eq!(
+            expand_error(input),
+            "`SyntaxKind` can only be derived for enums"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_syntax_kind_arguments() {
+        let input = parse_quote! {
+            #[syntax_kind(lang = TestLanguage)]
+            enum TestKind {
+                #[tombstone]
+                TOMBSTONE,
+            }
+        };
+
+        assert_eq!(
+            expand_error(input),
+            "unsupported `syntax_kind` argument, expected `language`"
+        );
+    }
+}
@@ -37,6 +37,11 @@ where
         Self(bits, PhantomData)
     }
 
+    /// Returns an empty set
+    pub const fn empty() -> Self {
+        Self([0; 5], PhantomData)
+    }
+
     /// Returns the union of the two sets `self` and `other`
     pub const fn union(self, other: Self) -> Self {
         Self(
@@ -51,6 +56,82 @@ where
         )
     }
 
+    /// Returns the set of the kinds contained in both `self` and `other`
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(
+            [
+                self.0[0] & other.0[0],
+                self.0[1] & other.0[1],
+                self.0[2] & other.0[2],
+                self.0[3] & other.0[3],
+                self.0[4] & other.0[4],
+            ],
+            PhantomData,
+        )
+    }
+
+    /// Returns the set of the kinds contained in `self` but not in `other`
+    pub const fn difference(self, other: Self) -> Self {
+        Self(
+            [
+                self.0[0] & !other.0[0],
+                self.0[1] & !other.0[1],
+                self.0[2] & !other.0[2],
+                self.0[3] & !other.0[3],
+                self.0[4] & !other.0[4],
+            ],
+            PhantomData,
+        )
+    }
+
+    /// Returns the set of all kinds of the language that aren't contained in this set,
+    /// bounded by [SyntaxKind::KIND_COUNT]
+    pub const fn complement(self) -> Self {
+        Self::all().difference(self)
+    }
+
+    /// Returns the set of all kinds of the language, the raw kinds less than [SyntaxKind::KIND_COUNT]
+    pub const fn all() -> Self {
+        let count = <L::Kind as SyntaxKind>::KIND_COUNT as usize;
+        let mut bits = [0; 5];
+        let mut index = 0;
+
+        while index < bits.len() {
+            let start = index * u128::BITS as usize;
+
+            bits[index] = if count >= start + u128::BITS as usize {
+                u128::MAX
+            } else if count > start {
+                (1 << (count - start)) - 1
+            } else {
+                0
+            };
+
+            index += 1;
+        }
+
+        Self(bits, PhantomData)
+    }
+
+    /// Returns the number of kinds in this set
+    pub const fn len(self) -> usize {
+        (self.0[0].count_ones()
+            + self.0[1].count_ones()
+            + self.0[2].count_ones()
+            + self.0[3].count_ones()
+            + self.0[4].count_ones()) as usize
+    }
+
+    /// Returns true if this set contains no kinds
+    pub const fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if all kinds of `other` are contained in this set
+    pub const fn contains_set(self, other: Self) -> bool {
+        other.difference(self).is_empty()
+    }
+
     /// Returns true if `kind` is contained in this set
     pub fn matches(self, kind: L::Kind) -> bool {
         let RawSyntaxKind(kind) = kind.to_raw();
@@ -79,6 +160,31 @@ where
     }
 }
 
+impl<L: Language> FromIterator<L::Kind> for SyntaxKindSet<L> {
+    fn from_iter<T: IntoIterator<Item = L::Kind>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(Self::empty(), |set, kind| set.union(Self::of(kind)))
+    }
+}
+
+/// Creates a [SyntaxKindSet] from a list of kinds.
+///
+/// The kinds must be variants of a fieldless enum, which allows using the macro in constants:
+///
+/// ```ignore
+/// const EXPRESSIONS: SyntaxKindSet<RawLanguage> = syntax_kind_set![
+///     RawLanguageKind::LITERAL_EXPRESSION,
+///     RawLanguageKind::NAME_EXPRESSION,
+/// ];
+/// ```
+#[macro_export]
+macro_rules! syntax_kind_set {
+    ($($kind:expr),* $(,)?) => {
+        $crate::ast::SyntaxKindSet::empty()
+            $(.union($crate::ast::SyntaxKindSet::from_raw($crate::green::RawSyntaxKind($kind as u16))))*
+    };
+}
+
 /// The main trait to go from untyped `SyntaxNode` to a typed ast. The
 /// conversion itself has zero runtime cost: ast and Syntax nodes have exactly
 /// the same representation: a pointer to the tree root and a pointer to the
@@ -99,3 +205,86 @@ pub trait AstNode: Clone {
     /// Returns `true` if a node with the give kind can be cased to this AST node.
     fn can_cast(kind: <Self::Language as Language>::Kind) -> bool;
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::{
+        RawLanguage,
+        RawLanguageKind::{self, *},
+    };
+
+    type KindSet = SyntaxKindSet<RawLanguage>;
+
+    const OPERATORS: KindSet = syntax_kind_set![PLUS, MINUS, STAR, SLASH];
+    const UNARY_OPERATORS: KindSet = syntax_kind_set![PLUS, MINUS];
+    const EMPTY: KindSet = syntax_kind_set![];
+    // A raw kind past the language's kinds, in a different word of the bitfield
+    const OUT_OF_BOUNDS: KindSet = KindSet::from_raw(RawSyntaxKind(300));
+
+    fn kinds(set: KindSet) -> Vec<RawLanguageKind> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn macro_in_const() {
+        assert_eq!(kinds(OPERATORS), [PLUS, MINUS, STAR, SLASH]);
+        assert_eq!(OPERATORS.len(), 4);
+        assert!(EMPTY.is_empty());
+        assert_eq!(OPERATORS.union(OUT_OF_BOUNDS).len(), 5);
+    }
+
+    #[test]
+    fn intersection_and_difference() {
+        let tokens = syntax_kind_set![PLUS, SEMICOLON, IDENT_TOKEN];
+
+        assert_eq!(kinds(OPERATORS.intersection(tokens)), [PLUS]);
+        assert_eq!(kinds(OPERATORS.difference(UNARY_OPERATORS)), [STAR, SLASH]);
+        assert_eq!(
+            kinds(tokens.difference(OPERATORS)),
+            [IDENT_TOKEN, SEMICOLON]
+        );
+        assert!(OPERATORS.intersection(EMPTY).is_empty());
+        assert_eq!(OPERATORS.difference(EMPTY), OPERATORS);
+        assert_eq!(
+            OPERATORS.union(OUT_OF_BOUNDS).difference(OUT_OF_BOUNDS),
+            OPERATORS
+        );
+        assert!(OPERATORS.intersection(OUT_OF_BOUNDS).is_empty());
+    }
+
+    #[test]
+    fn complement_and_all() {
+        let all = KindSet::all();
+
+        assert_eq!(all.len(), usize::from(RawLanguageKind::KIND_COUNT));
+        assert_eq!(kinds(all).last(), Some(&BOGUS_STATEMENT));
+        assert_eq!(OPERATORS.complement().len(), all.len() - 4);
+        assert!(!OPERATORS.complement().matches(PLUS));
+        assert!(OPERATORS.complement().matches(ROOT));
+        assert_eq!(OPERATORS.complement().complement(), OPERATORS);
+        assert_eq!(EMPTY.complement(), all);
+        // Bounded by the kind count
+        assert_eq!(OUT_OF_BOUNDS.complement(), all);
+    }
+
+    #[test]
+    fn contains_set() {
+        assert!(OPERATORS.contains_set(UNARY_OPERATORS));
+        assert!(!UNARY_OPERATORS.contains_set(OPERATORS));
+        assert!(OPERATORS.contains_set(OPERATORS));
+        assert!(OPERATORS.contains_set(EMPTY));
+        assert!(!KindSet::all().contains_set(OUT_OF_BOUNDS));
+        assert!(OPERATORS.union(OUT_OF_BOUNDS).contains_set(OUT_OF_BOUNDS));
+    }
+
+    #[test]
+    fn from_iterator() {
+        let set: KindSet = [SLASH, PLUS, STAR, MINUS, PLUS].into_iter().collect();
+
+        assert_eq!(set, OPERATORS);
+        assert_eq!(KindSet::from_iter([]), EMPTY);
+        assert!(set.matches(STAR));
+        assert!(!set.matches(ROOT));
+    }
+}
@@ -0,0 +1,729 @@
+//! Attaches the comments of a tree to its nodes.
+//!
+//! Comments are trivia and, therefore, owned by tokens. However, formatters and documentation
+//! generators usually want to know which *node* a comment belongs to. The [CommentsMap] walks
+//! a tree once and classifies every comment as:
+//!
+//! * leading: the comment comes before the node, e.g. `/* comment */ a + b`
+//! * trailing: the comment comes after the node, e.g. `a + b // comment`
+//! * dangling: the comment is inside of a node that has no child node the comment could
+//!   be attached to, e.g. `f(/* comment */)`
+//!
+//! The placement of a comment is decided in two steps. First, the language's [CommentStyle]
+//! can place the comment. If it returns [CommentPlacement::Default], the default rules apply:
+//!
+//! * end of line comments and comments directly following a node on the same line become
+//!   trailing comments of the preceding node
+//! * other comments become leading comments of the following node
+//! * if there's no preceding or following node, the comment becomes a dangling comment of the enclosing node
+//!
+//! List nodes are transparent for the comment placement: comments are never attached to a list
+//! and the elements of a list are treated as if they were direct children of the list's parent.
+//!
+//! The [doc_comments] module extracts the documentation comments preceding a node.
+
+pub mod doc_comments;
+
+use std::marker::PhantomData;
+
+use rustc_hash::FxHashMap;
+use text_size::TextRange;
+
+use crate::{
+    syntax::{
+        Language, SyntaxKind,
+        element::SyntaxElementKey,
+        node::SyntaxNode,
+        token::SyntaxToken,
+        trivia::{SyntaxTriviaPiece, SyntaxTriviaPieceComments, TriviaPieceKind},
+    },
+    utility_types::Direction,
+};
+
+/// The position of a comment relative to the surrounding tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentTextPosition {
+    /// The comment is the first non-whitespace content on its line.
+    ///
+    /// ```text
+    /// a;
+    /// // comment
+    /// b;
+    /// ```
+    OwnLine,
+    /// The comment follows a token on the same line and is followed by a line break.
+    ///
+    /// ```text
+    /// a; // comment
+    /// ```
+    EndOfLine,
+    /// The comment is preceded and followed by a token on the same line.
+    ///
+    /// ```text
+    /// a /* comment */ + b
+    /// ```
+    SameLine,
+}
+
+impl CommentTextPosition {
+    pub const fn is_own_line(&self) -> bool {
+        matches!(self, CommentTextPosition::OwnLine)
+    }
+
+    pub const fn is_end_of_line(&self) -> bool {
+        matches!(self, CommentTextPosition::EndOfLine)
+    }
+
+    pub const fn is_same_line(&self) -> bool {
+        matches!(self, CommentTextPosition::SameLine)
+    }
+}
+
+/// A comment that has been attached to a node.
+#[derive(Debug, Clone)]
+pub struct SourceComment<L: Language> {
+    piece: SyntaxTriviaPieceComments<L>,
+    text_position: CommentTextPosition,
+    lines_before: u32,
+    lines_after: u32,
+}
+
+impl<L: Language> SourceComment<L> {
+    /// Returns the comment's trivia piece.
+    pub fn piece(&self) -> &SyntaxTriviaPieceComments<L> {
+        &self.piece
+    }
+
+    pub fn text_position(&self) -> CommentTextPosition {
+        self.text_position
+    }
+
+    /// Number of line breaks between the comment and the preceding token or comment.
+    pub fn lines_before(&self) -> u32 {
+        self.lines_before
+    }
+
+    /// Number of line breaks between the comment and the following token or comment.
+    pub fn lines_after(&self) -> u32 {
+        self.lines_after
+    }
+}
+
+/// A comment together with the nodes surrounding it, passed to [CommentStyle::place_comment].
+#[derive(Debug, Clone)]
+pub struct DecoratedComment<L: Language> {
+    comment: SourceComment<L>,
+    enclosing: SyntaxNode<L>,
+    preceding: Option<SyntaxNode<L>>,
+    following: Option<SyntaxNode<L>>,
+    preceding_token: Option<SyntaxToken<L>>,
+    following_token: Option<SyntaxToken<L>>,
+    is_trailing_token_trivia: bool,
+}
+
+impl<L: Language> DecoratedComment<L> {
+    /// The innermost (non-list) node that fully contains the comment.
+    pub fn enclosing_node(&self) -> &SyntaxNode<L> {
+        &self.enclosing
+    }
+
+    /// The last child node of the enclosing node that ends before the comment.
+    pub fn preceding_node(&self) -> Option<&SyntaxNode<L>> {
+        self.preceding.as_ref()
+    }
+
+    /// The first child node of the enclosing node that starts after the comment.
+    pub fn following_node(&self) -> Option<&SyntaxNode<L>> {
+        self.following.as_ref()
+    }
+
+    /// The last token before the comment.
+    pub fn preceding_token(&self) -> Option<&SyntaxToken<L>> {
+        self.preceding_token.as_ref()
+    }
+
+    /// The first token after the comment.
+    pub fn following_token(&self) -> Option<&SyntaxToken<L>> {
+        self.following_token.as_ref()
+    }
+
+    /// Returns `true` if the comment is part of the trailing trivia of the preceding token.
+    pub fn is_trailing_token_trivia(&self) -> bool {
+        self.is_trailing_token_trivia
+    }
+
+    pub fn text_position(&self) -> CommentTextPosition {
+        self.comment.text_position
+    }
+
+    pub fn lines_before(&self) -> u32 {
+        self.comment.lines_before
+    }
+
+    pub fn lines_after(&self) -> u32 {
+        self.comment.lines_after
+    }
+
+    pub fn piece(&self) -> &SyntaxTriviaPieceComments<L> {
+        &self.comment.piece
+    }
+}
+
+impl<L: Language> From<DecoratedComment<L>> for SourceComment<L> {
+    fn from(decorated: DecoratedComment<L>) -> Self {
+        decorated.comment
+    }
+}
+
+/// The placement of a comment as decided by [CommentStyle::place_comment].
+#[derive(Debug, Clone)]
+pub enum CommentPlacement<L: Language> {
+    /// Makes `comment` a leading comment of `node`.
+    Leading {
+        node: SyntaxNode<L>,
+        comment: SourceComment<L>,
+    },
+    /// Makes `comment` a trailing comment of `node`.
+    Trailing {
+        node: SyntaxNode<L>,
+        comment: SourceComment<L>,
+    },
+    /// Makes `comment` a dangling comment of `node`.
+    Dangling {
+        node: SyntaxNode<L>,
+        comment: SourceComment<L>,
+    },
+    /// Places the comment using the default rules.
+    Default(DecoratedComment<L>),
+}
+
+impl<L: Language> CommentPlacement<L> {
+    pub fn leading(node: SyntaxNode<L>, comment: impl Into<SourceComment<L>>) -> Self {
+        Self::Leading {
+            node,
+            comment: comment.into(),
+        }
+    }
+
+    pub fn trailing(node: SyntaxNode<L>, comment: impl Into<SourceComment<L>>) -> Self {
+        Self::Trailing {
+            node,
+            comment: comment.into(),
+        }
+    }
+
+    pub fn dangling(node: SyntaxNode<L>, comment: impl Into<SourceComment<L>>) -> Self {
+        Self::Dangling {
+            node,
+            comment: comment.into(),
+        }
+    }
+}
+
+/// Language specific hook to customize the placement of comments.
+pub trait CommentStyle {
+    type Language: Language;
+
+    /// Called for every comment in the tree. Returning [CommentPlacement::Default] applies the
+    /// default placement rules.
+    fn place_comment(
+        &self,
+        comment: DecoratedComment<Self::Language>,
+    ) -> CommentPlacement<Self::Language> {
+        CommentPlacement::Default(comment)
+    }
+}
+
+/// [CommentStyle] that places all comments using the default rules.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultCommentStyle<L>(PhantomData<L>);
+
+impl<L: Language> CommentStyle for DefaultCommentStyle<L> {
+    type Language = L;
+}
+
+/// The comments attached to a single node.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeComments<'a, L: Language> {
+    pub leading: &'a [SourceComment<L>],
+    pub dangling: &'a [SourceComment<L>],
+    pub trailing: &'a [SourceComment<L>],
+}
+
+impl<L: Language> NodeComments<'_, L> {
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.dangling.is_empty() && self.trailing.is_empty()
+    }
+}
+
+/// The leading, trailing and dangling comments of all nodes in a tree, keyed by the node's
+/// [SyntaxElementKey].
+#[derive(Debug, Clone)]
+pub struct CommentsMap<L: Language> {
+    leading: FxHashMap<SyntaxElementKey, Vec<SourceComment<L>>>,
+    dangling: FxHashMap<SyntaxElementKey, Vec<SourceComment<L>>>,
+    trailing: FxHashMap<SyntaxElementKey, Vec<SourceComment<L>>>,
+}
+
+impl<L: Language> CommentsMap<L> {
+    /// Attaches the comments of the tree rooted at `root` using the default placement rules.
+    pub fn from_node(root: &SyntaxNode<L>) -> Self {
+        Self::from_node_with_style(root, &DefaultCommentStyle(PhantomData))
+    }
+
+    /// Attaches the comments of the tree rooted at `root`, giving `style` the chance
+    /// to place each comment before applying the default placement rules.
+    pub fn from_node_with_style<S>(root: &SyntaxNode<L>, style: &S) -> Self
+    where
+        S: CommentStyle<Language = L>,
+    {
+        let mut map = Self {
+            leading: FxHashMap::default(),
+            dangling: FxHashMap::default(),
+            trailing: FxHashMap::default(),
+        };
+
+        let pieces = collect_pieces(root);
+        // The flattened children of the enclosing nodes, computed once per node
+        let mut children = FxHashMap::default();
+
+        for (index, entry) in pieces.iter().enumerate() {
+            let TriviaEntry::Piece {
+                piece,
+                token,
+                is_trailing,
+            } = entry
+            else {
+                continue;
+            };
+
+            let Some(comment) = piece.as_comments() else {
+                continue;
+            };
+
+            let lines_before = count_lines(pieces[..index].iter().rev());
+            let lines_after = count_lines(pieces[index + 1..].iter());
+            let has_content_before = has_content(pieces[..index].iter().rev());
+            let has_content_after = has_content(pieces[index + 1..].iter());
+
+            let text_position = if lines_before > 0 || !has_content_before {
+                CommentTextPosition::OwnLine
+            } else if lines_after > 0 || !has_content_after {
+                CommentTextPosition::EndOfLine
+            } else {
+                CommentTextPosition::SameLine
+            };
+
+            let decorated = decorate(
+                root,
+                &mut children,
+                SourceComment {
+                    piece: comment,
+                    text_position,
+                    lines_before,
+                    lines_after,
+                },
+                token,
+                *is_trailing,
+            );
+
+            let placement = match style.place_comment(decorated) {
+                CommentPlacement::Default(decorated) => default_placement(decorated),
+                placement => placement,
+            };
+
+            map.insert(placement);
+        }
+
+        map
+    }
+
+    fn insert(&mut self, placement: CommentPlacement<L>) {
+        let (map, node, comment) = match placement {
+            CommentPlacement::Leading { node, comment } => (&mut self.leading, node, comment),
+            CommentPlacement::Trailing { node, comment } => (&mut self.trailing, node, comment),
+            CommentPlacement::Dangling { node, comment } => (&mut self.dangling, node, comment),
+            CommentPlacement::Default(_) => {
+                unreachable!("default placements are resolved before inserting")
+            }
+        };
+
+        map.entry(node.key()).or_default().push(comment);
+    }
+
+    /// Returns the comments attached to the node with the given `key`.
+    pub fn get(&self, key: &SyntaxElementKey) -> NodeComments<'_, L> {
+        NodeComments {
+            leading: self.leading.get(key).map_or(&[], Vec::as_slice),
+            dangling: self.dangling.get(key).map_or(&[], Vec::as_slice),
+            trailing: self.trailing.get(key).map_or(&[], Vec::as_slice),
+        }
+    }
+
+    /// Returns all comments attached to `node`.
+    pub fn comments(&self, node: &SyntaxNode<L>) -> NodeComments<'_, L> {
+        self.get(&node.key())
+    }
+
+    pub fn leading_comments(&self, node: &SyntaxNode<L>) -> &[SourceComment<L>] {
+        self.comments(node).leading
+    }
+
+    pub fn dangling_comments(&self, node: &SyntaxNode<L>) -> &[SourceComment<L>] {
+        self.comments(node).dangling
+    }
+
+    pub fn trailing_comments(&self, node: &SyntaxNode<L>) -> &[SourceComment<L>] {
+        self.comments(node).trailing
+    }
+
+    /// Returns `true` if `node` has any leading, dangling or trailing comments.
+    pub fn has_comments(&self, node: &SyntaxNode<L>) -> bool {
+        !self.comments(node).is_empty()
+    }
+
+    /// Returns the total number of attached comments.
+    pub fn len(&self) -> usize {
+        [&self.leading, &self.dangling, &self.trailing]
+            .into_iter()
+            .flat_map(|map| map.values())
+            .map(Vec::len)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+enum TriviaEntry<L: Language> {
+    Piece {
+        piece: SyntaxTriviaPiece<L>,
+        /// The token owning the trivia piece
+        token: SyntaxToken<L>,
+        is_trailing: bool,
+    },
+    Token,
+}
+
+/// Flattens the trivia pieces and tokens of the tree into a single list in source order.
+fn collect_pieces<L: Language>(root: &SyntaxNode<L>) -> Vec<TriviaEntry<L>> {
+    let mut entries = Vec::new();
+
+    for token in root.descendants_tokens(Direction::Next) {
+        for piece in token.leading_trivia().pieces() {
+            entries.push(TriviaEntry::Piece {
+                piece,
+                token: token.clone(),
+                is_trailing: false,
+            });
+        }
+
+        if !token.text_trimmed().is_empty() {
+            entries.push(TriviaEntry::Token);
+        }
+
+        for piece in token.trailing_trivia().pieces() {
+            entries.push(TriviaEntry::Piece {
+                piece,
+                token: token.clone(),
+                is_trailing: true,
+            });
+        }
+    }
+
+    entries
+}
+
+/// Counts the line breaks until the next token, comment, or skipped trivia.
+fn count_lines<'a, L: Language + 'a>(entries: impl Iterator<Item = &'a TriviaEntry<L>>) -> u32 {
+    let mut lines = 0;
+
+    for entry in entries {
+        match entry {
+            TriviaEntry::Piece { piece, .. } => match piece.kind() {
+                TriviaPieceKind::Newline => lines += 1,
+                TriviaPieceKind::Whitespace => {}
+                _ => break,
+            },
+            TriviaEntry::Token => break,
+        }
+    }
+
+    lines
+}
+
+/// Returns `true` if there's any token, comment, or skipped trivia before the next line break.
+fn has_content<'a, L: Language + 'a>(
+    mut entries: impl Iterator<Item = &'a TriviaEntry<L>>,
+) -> bool {
+    entries
+        .find(|entry| match entry {
+            TriviaEntry::Piece { piece, .. } => !piece.is_whitespace(),
+            TriviaEntry::Token => true,
+        })
+        .is_some_and(|entry| match entry {
+            TriviaEntry::Piece { piece, .. } => !piece.is_newline(),
+            TriviaEntry::Token => true,
+        })
+}
+
+fn decorate<L: Language>(
+    root: &SyntaxNode<L>,
+    children: &mut FxHashMap<SyntaxElementKey, Vec<(TextRange, SyntaxNode<L>)>>,
+    comment: SourceComment<L>,
+    token: &SyntaxToken<L>,
+    is_trailing: bool,
+) -> DecoratedComment<L> {
+    let enclosing = enclosing_node(root, token, is_trailing);
+    let comment_range = comment.piece.text_range();
+
+    let children = children
+        .entry(enclosing.key())
+        .or_insert_with(|| flattened_children(&enclosing));
+
+    // The children are sorted and don't overlap
+    let index = children.partition_point(|(range, _)| range.end() <= comment_range.start());
+    let preceding = index.checked_sub(1).map(|index| children[index].1.clone());
+    let following = children
+        .get(index)
+        .filter(|(range, _)| range.start() >= comment_range.end())
+        .map(|(_, child)| child.clone());
+
+    let (preceding_token, following_token) = if is_trailing {
+        (Some(token.clone()), token.next_token())
+    } else {
+        (token.prev_token(), Some(token.clone()))
+    };
+
+    DecoratedComment {
+        comment,
+        enclosing,
+        preceding,
+        following,
+        preceding_token,
+        following_token,
+        is_trailing_token_trivia: is_trailing,
+    }
+}
+
+/// Returns the innermost non-list node that contains the comment in `token`'s leading
+/// or trailing trivia.
+///
+/// A comment in the leading trivia of the first token of a node is outside of that node,
+/// the same as a comment in the trailing trivia of the last token of a node.
+fn enclosing_node<L: Language>(
+    root: &SyntaxNode<L>,
+    token: &SyntaxToken<L>,
+    is_trailing: bool,
+) -> SyntaxNode<L> {
+    let mut node = token.parent().unwrap_or_else(|| root.clone());
+
+    while node != *root {
+        let boundary = if is_trailing {
+            node.last_token()
+        } else {
+            node.first_token()
+        };
+
+        if boundary.as_ref() != Some(token) && !node.kind().is_list() {
+            break;
+        }
+
+        match node.parent() {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+
+    node
+}
+
+/// Returns the non-empty child nodes of `node` and their trimmed ranges, replacing list nodes
+/// with their elements.
+fn flattened_children<L: Language>(node: &SyntaxNode<L>) -> Vec<(TextRange, SyntaxNode<L>)> {
+    let mut children = Vec::new();
+    let mut stack = vec![node.children()];
+
+    while let Some(iter) = stack.last_mut() {
+        match iter.next() {
+            Some(child) if child.kind().is_list() => stack.push(child.children()),
+            Some(child) => {
+                let range = child.text_trimmed_range();
+
+                if !range.is_empty() {
+                    children.push((range, child));
+                }
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    children
+}
+
+fn default_placement<L: Language>(comment: DecoratedComment<L>) -> CommentPlacement<L> {
+    let prefer_preceding = match comment.text_position() {
+        CommentTextPosition::OwnLine => false,
+        CommentTextPosition::EndOfLine => true,
+        // `a /* comment */, b` but not `a, /* comment */ b`
+        CommentTextPosition::SameLine => comment
+            .preceding
+            .as_ref()
+            .is_some_and(|preceding| preceding.last_token() == comment.preceding_token),
+    };
+
+    match (
+        prefer_preceding,
+        comment.preceding.clone(),
+        comment.following.clone(),
+    ) {
+        (true, Some(preceding), _) | (false, Some(preceding), None) => {
+            CommentPlacement::trailing(preceding, comment)
+        }
+        (_, _, Some(following)) => CommentPlacement::leading(following, comment),
+        (_, None, None) => CommentPlacement::dangling(comment.enclosing.clone(), comment),
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::{RawLanguage, RawLanguageKind, parse};
+
+    fn find(
+        root: &SyntaxNode<RawLanguage>,
+        kind: RawLanguageKind,
+        text: &str,
+    ) -> SyntaxNode<RawLanguage> {
+        root.descendants()
+            .find(|node| node.kind() == kind && node.text_trimmed() == text)
+            .unwrap_or_else(|| panic!("no {kind:?} node with the text {text:?}"))
+    }
+
+    fn texts(comments: &[SourceComment<RawLanguage>]) -> Vec<&str> {
+        comments
+            .iter()
+            .map(|comment| comment.piece().text())
+            .collect()
+    }
+
+    #[test]
+    fn end_of_line_comment_trails_preceding_node() {
+        let root = parse("a; // trailing\nb;");
+        let comments = CommentsMap::from_node(&root);
+
+        let a = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "a;");
+        let trailing = comments.trailing_comments(&a);
+
+        assert_eq!(texts(trailing), vec!["// trailing"]);
+        assert_eq!(trailing[0].text_position(), CommentTextPosition::EndOfLine);
+        assert_eq!(trailing[0].lines_before(), 0);
+        assert_eq!(trailing[0].lines_after(), 1);
+        assert_eq!(comments.len(), 1);
+    }
+
+    #[test]
+    fn own_line_comment_leads_following_node() {
+        let root = parse("a;\n\n// leading\nb;");
+        let comments = CommentsMap::from_node(&root);
+
+        let b = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "b;");
+        let leading = comments.leading_comments(&b);
+
+        assert_eq!(texts(leading), vec!["// leading"]);
+        assert_eq!(leading[0].text_position(), CommentTextPosition::OwnLine);
+        assert_eq!(leading[0].lines_before(), 2);
+        assert_eq!(leading[0].lines_after(), 1);
+
+        let a = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "a;");
+        assert!(!comments.has_comments(&a));
+    }
+
+    #[test]
+    fn own_line_comment_without_following_node_trails_preceding_node() {
+        let root = parse("a;\n// last\n");
+        let comments = CommentsMap::from_node(&root);
+
+        let a = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "a;");
+        assert_eq!(texts(comments.trailing_comments(&a)), vec!["// last"]);
+    }
+
+    #[test]
+    fn same_line_comment_trails_node_ending_at_preceding_token() {
+        let root = parse("a /* first */ + /* second */ b;");
+        let comments = CommentsMap::from_node(&root);
+
+        let a = find(&root, RawLanguageKind::NAME_EXPRESSION, "a");
+        let b = find(&root, RawLanguageKind::NAME_EXPRESSION, "b");
+
+        assert_eq!(texts(comments.trailing_comments(&a)), vec!["/* first */"]);
+        assert_eq!(
+            comments.trailing_comments(&a)[0].text_position(),
+            CommentTextPosition::SameLine
+        );
+        assert_eq!(texts(comments.leading_comments(&b)), vec!["/* second */"]);
+    }
+
+    #[test]
+    fn comment_in_empty_node_is_dangling() {
+        let root = parse("f(/* dangling */);");
+        let comments = CommentsMap::from_node(&root);
+
+        let arguments = find(&root, RawLanguageKind::CALL_ARGUMENTS, "(/* dangling */)");
+
+        assert_eq!(
+            texts(comments.dangling_comments(&arguments)),
+            vec!["/* dangling */"]
+        );
+        assert_eq!(comments.len(), 1);
+    }
+
+    #[test]
+    fn comments_are_not_attached_to_lists() {
+        let root = parse("// first\na;");
+        let comments = CommentsMap::from_node(&root);
+
+        let list = find(&root, RawLanguageKind::STATEMENT_LIST, "a;");
+        let a = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "a;");
+
+        assert!(!comments.has_comments(&list));
+        assert_eq!(texts(comments.leading_comments(&a)), vec!["// first"]);
+    }
+
+    /// Attaches comments starting with `//!` to the enclosing node and uses the default rules for
+    /// all other comments.
+    struct InnerDocCommentStyle;
+
+    impl CommentStyle for InnerDocCommentStyle {
+        type Language = RawLanguage;
+
+        fn place_comment(
+            &self,
+            comment: DecoratedComment<RawLanguage>,
+        ) -> CommentPlacement<RawLanguage> {
+            if comment.piece().text().starts_with("//!") {
+                CommentPlacement::dangling(comment.enclosing_node().clone(), comment)
+            } else {
+                CommentPlacement::Default(comment)
+            }
+        }
+    }
+
+    #[test]
+    fn comment_style_overrides_placement() {
+        let root = parse("//! module\na; // trailing\n");
+        let comments = CommentsMap::from_node_with_style(&root, &InnerDocCommentStyle);
+
+        let a = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "a;");
+
+        assert_eq!(texts(comments.dangling_comments(&root)), vec!["//! module"]);
+        assert!(comments.leading_comments(&a).is_empty());
+        assert_eq!(texts(comments.trailing_comments(&a)), vec!["// trailing"]);
+
+        let defaults = CommentsMap::from_node(&root);
+        assert_eq!(texts(defaults.leading_comments(&a)), vec!["//! module"]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Extraction of the documentation comments preceding a node.
+
+use text_size::TextRange;
+
+use crate::syntax::{Language, node::SyntaxNode, trivia::SyntaxTriviaPieceComments};
+
+/// The documentation comments preceding a node, returned by [SyntaxNode::doc_comments].
+#[derive(Debug, Clone)]
+pub struct DocComments<L: Language> {
+    pieces: Vec<SyntaxTriviaPieceComments<L>>,
+}
+
+impl<L: Language> DocComments<L> {
+    /// Returns the comment pieces in source order.
+    pub fn pieces(&self) -> &[SyntaxTriviaPieceComments<L>] {
+        &self.pieces
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    /// Returns the range from the start of the first to the end of the last comment.
+    pub fn text_range(&self) -> Option<TextRange> {
+        let first = self.pieces.first()?;
+        let last = self.pieces.last()?;
+
+        Some(first.text_range().cover(last.text_range()))
+    }
+
+    /// Returns the text of the comments with the comment syntax removed.
+    ///
+    /// Calls `strip_prefix` for every line of every comment. It returns the line without the
+    /// comment syntax, for example, without the leading `///` or `*`. The lines are joined with `\n`,
+    /// omitting empty lines at the start and end of multiline comments.
+    /// See [strip_doc_comment_prefix] for a callback that handles common comment syntaxes.
+    pub fn text<F>(&self, mut strip_prefix: F) -> String
+    where
+        F: FnMut(&str) -> &str,
+    {
+        let mut lines = Vec::new();
+
+        for piece in &self.pieces {
+            let mut piece_lines: Vec<_> = piece.text().lines().map(&mut strip_prefix).collect();
+
+            // Remove the empty lines of block comments' opening and closing lines
+            if piece_lines.len() > 1 {
+                while piece_lines.last().is_some_and(|line| line.is_empty()) {
+                    piece_lines.pop();
+                }
+
+                let leading_empty = piece_lines
+                    .iter()
+                    .take_while(|line| line.is_empty())
+                    .count();
+                piece_lines.drain(..leading_empty);
+            }
+
+            lines.extend(piece_lines);
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Strips the syntax of line and block comments from `line`: the comment markers `///`, `//!`,
+/// `//`, `/**`, `/*`, `*/`, `*`, and `#`, and a single space following the marker.
+pub fn strip_doc_comment_prefix(line: &str) -> &str {
+    let line = line.trim_start();
+    let line = line.strip_suffix("*/").unwrap_or(line).trim_end();
+
+    let stripped = ["///", "//!", "//", "/**", "/*", "*", "#"]
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+        .unwrap_or(line);
+
+    stripped.strip_prefix(' ').unwrap_or(stripped)
+}
+
+impl<L: Language> SyntaxNode<L> {
+    /// Returns the documentation comments in the leading trivia of the node's first token.
+    ///
+    /// Walks the leading trivia backwards from the first token and collects the comments for which
+    /// `is_doc_comment` returns `true`. Stops at the first blank line (two newlines without a comment in between),
+    /// at skipped trivia, or at a comment that isn't a documentation comment.
+    pub fn doc_comments<P>(&self, mut is_doc_comment: P) -> DocComments<L>
+    where
+        P: FnMut(&SyntaxTriviaPieceComments<L>) -> bool,
+    {
+        let mut pieces = Vec::new();
+
+        if let Some(leading) = self.first_leading_trivia() {
+            let mut newlines = 0;
+
+            for piece in leading.pieces().rev() {
+                if piece.is_whitespace() {
+                    continue;
+                }
+
+                if piece.is_newline() {
+                    newlines += 1;
+
+                    if newlines > 1 {
+                        break;
+                    }
+
+                    continue;
+                }
+
+                match piece.as_comments() {
+                    Some(comment) if is_doc_comment(&comment) => {
+                        pieces.push(comment);
+                        newlines = 0;
+                    }
+                    _ => break,
+                }
+            }
+        }
+
+        pieces.reverse();
+        DocComments { pieces }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::{RawLanguage, RawLanguageKind, parse};
+
+    fn is_doc_comment(comment: &SyntaxTriviaPieceComments<RawLanguage>) -> bool {
+        comment.text().starts_with("///") || comment.text().starts_with("/**")
+    }
+
+    /// Returns the doc comments of the last statement in `text`.
+    fn doc_comments(text: &str) -> DocComments<RawLanguage> {
+        parse(text)
+            .descendants()
+            .filter(|node| node.kind() == RawLanguageKind::EXPRESSION_STATEMENT)
+            .last()
+            .unwrap()
+            .doc_comments(is_doc_comment)
+    }
+
+    fn texts(comments: &DocComments<RawLanguage>) -> Vec<&str> {
+        comments.pieces().iter().map(|piece| piece.text()).collect()
+    }
+
+    #[test]
+    fn collects_consecutive_doc_comments() {
+        let comments = doc_comments("a;\n/// first\n  /// second\nb;");
+
+        assert_eq!(texts(&comments), vec!["/// first", "/// second"]);
+        assert_eq!(
+            comments.text_range(),
+            Some(TextRange::new(3.into(), 25.into()))
+        );
+        assert_eq!(comments.text(strip_doc_comment_prefix), "first\nsecond");
+    }
+
+    #[test]
+    fn stops_at_blank_lines() {
+        let comments = doc_comments("/// detached\n\n/// first\n/// second\na;");
+        assert_eq!(texts(&comments), vec!["/// first", "/// second"]);
+
+        let comments = doc_comments("/// detached\n  \t\n/// first\na;");
+        assert_eq!(texts(&comments), vec!["/// first"]);
+
+        let comments = doc_comments("/// detached\n\na;");
+        assert!(comments.is_empty());
+        assert_eq!(comments.text_range(), None);
+    }
+
+    #[test]
+    fn stops_at_non_doc_comments() {
+        let comments = doc_comments("/// not attached\n// regular\n/// first\na;");
+        assert_eq!(texts(&comments), vec!["/// first"]);
+
+        let comments = doc_comments("/// first\n// regular\na;");
+        assert!(comments.is_empty());
+    }
+
+    #[test]
+    fn stops_at_skipped_trivia() {
+        let comments = doc_comments("/// not attached\n$ /// first\na;");
+
+        assert_eq!(texts(&comments), vec!["/// first"]);
+    }
+
+    #[test]
+    fn ignores_trailing_comments_of_previous_token() {
+        let comments = doc_comments("a; /// trailing\nb;");
+
+        assert!(comments.is_empty());
+    }
+
+    #[test]
+    fn text_of_block_comments() {
+        let comments = doc_comments("/**\n * first\n *\n * second\n */\n/** third */\na;");
+
+        assert_eq!(comments.pieces().len(), 2);
+        assert_eq!(
+            comments.text(strip_doc_comment_prefix),
+            "first\n\nsecond\nthird"
+        );
+    }
+
+    #[test]
+    fn text_with_custom_prefix() {
+        let comments = doc_comments("///> first\n///> second\na;");
+
+        assert_eq!(
+            comments.text(|line| line.trim_start_matches("///>").trim()),
+            "first\nsecond"
+        );
+    }
+
+    #[test]
+    fn strip_prefix() {
+        assert_eq!(strip_doc_comment_prefix("/// text"), "text");
+        assert_eq!(strip_doc_comment_prefix("///text"), "text");
+        assert_eq!(strip_doc_comment_prefix("//!  indented"), " indented");
+        assert_eq!(strip_doc_comment_prefix("// text"), "text");
+        assert_eq!(strip_doc_comment_prefix("/** text */"), "text");
+        assert_eq!(strip_doc_comment_prefix("/* text */"), "text");
+        assert_eq!(strip_doc_comment_prefix("   * text"), "text");
+        assert_eq!(strip_doc_comment_prefix("  */"), "");
+        assert_eq!(strip_doc_comment_prefix("# text"), "text");
+        assert_eq!(strip_doc_comment_prefix("  text  "), "text");
+    }
+}
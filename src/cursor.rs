@@ -61,7 +61,7 @@ pub mod token;
 pub mod trivia;
 
 #[derive(Debug)]
-struct _SyntaxElement;
+pub(crate) struct _SyntaxElement;
 
 #[cfg(feature = "countme")]
 pub(crate) fn has_live() -> bool {
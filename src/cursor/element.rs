@@ -1,4 +1,4 @@
-use std::iter;
+use std::{cmp::Ordering, iter, ptr::NonNull};
 
 use text_size::{TextRange, TextSize};
 
@@ -81,6 +81,38 @@ impl SyntaxElement {
         iter::successors(first, SyntaxNode::parent)
     }
 
+    /// Compares the positions of two elements in a preorder traversal of their tree, where ancestors
+    /// come before their descendants. Returns `None` if the elements are in different trees.
+    pub fn cmp_position(&self, other: &Self) -> Option<Ordering> {
+        let (left_root, left_path) = self.slot_path();
+        let (right_root, right_path) = other.slot_path();
+
+        (left_root == right_root).then(|| left_path.cmp(&right_path))
+    }
+
+    /// Returns the key of the root of the element's tree and the slot indices from the root to the element.
+    fn slot_path(&self) -> ((NonNull<()>, TextSize), Vec<u32>) {
+        let mut path = Vec::new();
+        let mut node = match self {
+            Self::Node(node) => node.clone(),
+            Self::Token(token) => match token.parent() {
+                Some(parent) => {
+                    path.push(token.index() as u32);
+                    parent
+                }
+                None => return (token.key(), path),
+            },
+        };
+
+        while let Some(parent) = node.parent() {
+            path.push(node.index() as u32);
+            node = parent;
+        }
+
+        path.reverse();
+        (node.key(), path)
+    }
+
     pub fn first_token(&self) -> Option<SyntaxToken> {
         match self {
             Self::Node(it) => it.first_token(),
@@ -409,6 +409,92 @@ impl SyntaxNode {
             })
     }
 
+    /// Returns the deepest element containing `range` and all of its ancestors with the same range,
+    /// starting with the outermost. Returns an empty list if `range` isn't contained in this node.
+    pub fn covering_elements(&self, range: TextRange) -> Vec<SyntaxElement> {
+        if !self.text_range().contains_range(range) {
+            return Vec::new();
+        }
+
+        let mut elements: Vec<SyntaxElement> = vec![self.clone().into()];
+
+        while let Some(NodeOrToken::Node(node)) = elements.last() {
+            let child = if range.is_empty() {
+                node.child_or_token_at_offset(range.start())
+            } else {
+                node.child_or_token_at_range(range)
+            };
+
+            match child {
+                Some(child) => {
+                    if child.text_range() != elements[0].text_range() {
+                        elements.clear();
+                    }
+                    elements.push(child);
+                }
+                None => break,
+            }
+        }
+
+        elements
+    }
+
+    /// Returns the non-empty child containing `offset`, preferring the child starting at `offset`
+    /// over the child ending at it.
+    fn child_or_token_at_offset(&self, offset: TextSize) -> Option<SyntaxElement> {
+        let rel_offset = offset.checked_sub(self.offset())?;
+        let slots = self.green().slice();
+
+        // The first slot ending after the offset, which contains it if it doesn't start after it
+        let after = slots.partition_point(|slot| slot.rel_range().end() <= rel_offset);
+        let index = match slots.get(after) {
+            Some(slot) if slot.rel_range().start() <= rel_offset => after,
+            _ => slots[..after]
+                .iter()
+                .rposition(|slot| !slot.rel_range().is_empty())
+                .filter(|index| slots[*index].rel_range().end() == rel_offset)?,
+        };
+
+        let slot = &slots[index];
+        slot.as_ref().map(|green| {
+            SyntaxElement::new(
+                green,
+                self.clone(),
+                index as u32,
+                self.offset() + slot.rel_offset(),
+            )
+        })
+    }
+
+    /// Returns the deepest node that is an ancestor of both `self` and `other` or `None` if they
+    /// are in different trees.
+    pub fn common_ancestor(&self, other: &Self) -> Option<Self> {
+        let mut left = self.clone();
+        let mut right = other.clone();
+        let left_depth = self.ancestors().count();
+        let right_depth = other.ancestors().count();
+
+        for _ in right_depth..left_depth {
+            left = left.parent()?;
+        }
+
+        for _ in left_depth..right_depth {
+            right = right.parent()?;
+        }
+
+        while left != right {
+            left = left.parent()?;
+            right = right.parent()?;
+        }
+
+        Some(left)
+    }
+
+    /// Returns `true` if this node is `other` or one of its ancestors.
+    pub fn is_ancestor_of(&self, other: &Self) -> bool {
+        other.ancestors().any(|ancestor| &ancestor == self)
+    }
+
     #[must_use = "syntax elements are immutable, the result of update methods must be propagated to have any effect"]
     pub fn detach(self) -> Self {
         Self {
@@ -0,0 +1,156 @@
+//! Reports about the number of allocated green and red tree objects.
+//!
+//! The counts are collected by the [countme] crate. Counting is disabled at runtime by default,
+//! call [enable_counting] early on (for example, in `main` or at the start of a test) to turn it on.
+//!
+//! The counters are global for the whole process. Objects allocated by other threads, for
+//! example by tests running in parallel, are included in the counts.
+
+use std::fmt;
+
+use crate::{
+    cursor::_SyntaxElement,
+    green::{node::GreenNode, token::GreenToken, trivia::GreenTrivia},
+};
+
+/// Enables or disables the counting of allocated objects.
+pub fn enable_counting(yes: bool) {
+    countme::enable(yes);
+}
+
+/// The counts for a single object type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ObjectCounts {
+    /// The number of objects that were created but haven't been dropped yet.
+    pub live: usize,
+    /// The total number of created objects.
+    pub total: usize,
+    /// The historical maximum of `live`.
+    pub max_live: usize,
+}
+
+impl ObjectCounts {
+    fn of<T: 'static>() -> Self {
+        let counts = countme::get::<T>();
+
+        Self {
+            live: counts.live,
+            total: counts.total,
+            max_live: counts.max_live,
+        }
+    }
+}
+
+/// Snapshot of the object counts returned by [live_objects].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LiveObjects {
+    /// Green nodes, shared between all trees referencing them.
+    pub green_nodes: ObjectCounts,
+    /// Green tokens.
+    pub green_tokens: ObjectCounts,
+    /// Green trivia. Empty trivia isn't allocated and therefore not counted.
+    pub green_trivia: ObjectCounts,
+    /// The data of red `SyntaxNode` and `SyntaxToken` handles (cursor `NodeData`).
+    pub syntax_elements: ObjectCounts,
+}
+
+impl LiveObjects {
+    /// Returns `true` if any object is alive.
+    pub fn has_live(&self) -> bool {
+        self.entries().iter().any(|(_, counts)| counts.live > 0)
+    }
+
+    /// Returns the object types for which more objects are alive than in `baseline`
+    /// together with the number of additional live objects.
+    pub fn live_since(&self, baseline: &LiveObjects) -> Vec<(&'static str, usize)> {
+        self.entries()
+            .into_iter()
+            .zip(baseline.entries())
+            .filter_map(|((name, counts), (_, baseline))| {
+                let leaked = counts.live.saturating_sub(baseline.live);
+                (leaked > 0).then_some((name, leaked))
+            })
+            .collect()
+    }
+
+    fn entries(&self) -> [(&'static str, ObjectCounts); 4] {
+        [
+            ("GreenNode", self.green_nodes),
+            ("GreenToken", self.green_tokens),
+            ("GreenTrivia", self.green_trivia),
+            ("SyntaxElement", self.syntax_elements),
+        ]
+    }
+}
+
+impl fmt::Display for LiveObjects {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<16} {:>10} {:>10} {:>10}",
+            "object", "live", "total", "max_live"
+        )?;
+
+        for (name, counts) in self.entries() {
+            writeln!(
+                f,
+                "{:<16} {:>10} {:>10} {:>10}",
+                name, counts.live, counts.total, counts.max_live
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the current counts of green nodes, tokens, trivia and red syntax elements.
+///
+/// All counts are zero if counting isn't enabled, see [enable_counting].
+pub fn live_objects() -> LiveObjects {
+    LiveObjects {
+        green_nodes: ObjectCounts::of::<GreenNode>(),
+        green_tokens: ObjectCounts::of::<GreenToken>(),
+        green_trivia: ObjectCounts::of::<GreenTrivia>(),
+        syntax_elements: ObjectCounts::of::<_SyntaxElement>(),
+    }
+}
+
+/// Runs `f` and asserts that all green and red tree objects allocated by `f` have been dropped
+/// by the time it returns.
+///
+/// Enables counting if it isn't enabled yet. `f` must not store syntax nodes or tokens
+/// anywhere that outlives the call.
+///
+/// ## Panics
+/// If more objects are alive after `f` returns than before it was called. Because the counters
+/// are global, run leak checks on a single thread (`--test-threads=1`) to avoid false positives.
+///
+/// ```ignore
+/// assert_no_leaks(|| {
+///     let root = parse("a + b");
+///     let _ = root.descendants().count();
+/// });
+/// ```
+#[track_caller]
+pub fn assert_no_leaks<F>(f: F)
+where
+    F: FnOnce(),
+{
+    enable_counting(true);
+
+    let baseline = live_objects();
+    f();
+    let after = live_objects();
+
+    let leaks = after.live_since(&baseline);
+
+    if !leaks.is_empty() {
+        let leaks = leaks
+            .iter()
+            .map(|(name, count)| format!("{count} {name}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        panic!("leaked objects: {leaks}\n{after}");
+    }
+}
@@ -283,6 +283,23 @@ impl GreenNode {
         Self { ptr: data }
     }
 
+    /// Creates a node with the given `text_len` and `slots` without checking that the length and
+    /// the relative offsets of the slots match. Used to test the validation of malformed trees.
+    #[cfg(test)]
+    pub(crate) fn new_unchecked(kind: RawSyntaxKind, text_len: TextSize, slots: Vec<Slot>) -> Self {
+        let data = ThinArc::from_header_and_iter(
+            GreenNodeHead {
+                kind,
+                text_len,
+                #[cfg(feature = "countme")]
+                _c: countme::Count::new(),
+            },
+            slots.into_iter(),
+        );
+
+        Self { ptr: data }
+    }
+
     #[inline]
     pub(crate) fn into_raw(self) -> ptr::NonNull<GreenNodeData> {
         // SAFETY: Casting from `HeaderSlice<GreenNodeHead, [green::node::Slot]>` to `GreenNodeData`
@@ -319,7 +336,7 @@ impl Slot {
     }
 
     #[inline]
-    fn rel_range(&self) -> TextRange {
+    pub(crate) fn rel_range(&self) -> TextRange {
         let text_len = match self.as_ref() {
             None => TextSize::from(0),
             Some(element) => element.text_len(),
@@ -0,0 +1,600 @@
+//! An index of the nodes of a tree by kind.
+//!
+//! [KindIndex] answers "all nodes of these kinds" without traversing the tree. It's built in a single
+//! preorder pass over the green tree and can be updated for a new version of the tree, reusing the
+//! entries of all subtrees whose green node didn't change.
+
+use rustc_hash::FxHashMap;
+use text_size::{TextRange, TextSize};
+
+use crate::{
+    ast::SyntaxKindSet,
+    cursor,
+    green::{
+        RawSyntaxKind,
+        node::{GreenNodeData, Slot},
+    },
+    syntax::{Language, SyntaxKind, node::SyntaxNode},
+};
+
+/// The position of a node in a [KindIndex].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodePosition {
+    preorder_index: u32,
+    range: TextRange,
+}
+
+impl NodePosition {
+    /// Returns the index of the node in a preorder traversal of the indexed tree. The root has index 0.
+    pub fn preorder_index(&self) -> u32 {
+        self.preorder_index
+    }
+
+    /// Returns the node's range, including its trivia.
+    pub fn text_range(&self) -> TextRange {
+        self.range
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IndexedNode {
+    kind: RawSyntaxKind,
+    range: TextRange,
+    /// The preorder index following the last node in this node's subtree.
+    subtree_end: u32,
+    /// Identifies the green node. Only compared, never dereferenced, and valid as long as
+    /// the indexed root is alive.
+    green: *const GreenNodeData,
+}
+
+/// Maps kinds to the positions of the nodes of that kind in a tree.
+#[derive(Debug, Clone)]
+pub struct KindIndex<L: Language> {
+    root: SyntaxNode<L>,
+    /// All nodes in preorder.
+    nodes: Vec<IndexedNode>,
+    /// The preorder indices of the nodes of each kind, indexed by the raw kind.
+    by_kind: Vec<Vec<u32>>,
+}
+
+impl<L: Language> KindIndex<L> {
+    /// Indexes all nodes in the tree rooted at `root`, including `root`.
+    pub fn new(root: &SyntaxNode<L>) -> Self {
+        let raw = cursor::node::SyntaxNode::from(root.clone());
+        let mut nodes = Vec::new();
+        index_subtree(
+            &mut nodes,
+            raw.green(),
+            root.text_range_with_trivia().start(),
+        );
+
+        let mut index = Self {
+            root: root.clone(),
+            nodes,
+            by_kind: Vec::new(),
+        };
+        index.index_kinds();
+        index
+    }
+
+    /// Returns the root of the indexed tree.
+    pub fn root(&self) -> &SyntaxNode<L> {
+        &self.root
+    }
+
+    /// Returns the number of indexed nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the number of nodes with a kind in `kinds`.
+    pub fn count(&self, kinds: SyntaxKindSet<L>) -> usize {
+        kinds.iter().map(|kind| self.kind_indices(kind).len()).sum()
+    }
+
+    /// Returns the positions of all nodes with a kind in `kinds` in preorder.
+    pub fn positions(&self, kinds: SyntaxKindSet<L>) -> Vec<NodePosition> {
+        self.positions_between(0, self.nodes.len() as u32, kinds)
+    }
+
+    /// Returns the positions of the nodes with a kind in `kinds` in the subtree of `subtree`,
+    /// including `subtree` itself, in preorder.
+    ///
+    /// Returns an empty list if `subtree` isn't part of the indexed tree.
+    pub fn positions_in(
+        &self,
+        subtree: &SyntaxNode<L>,
+        kinds: SyntaxKindSet<L>,
+    ) -> Vec<NodePosition> {
+        match self.preorder_index_of(subtree) {
+            Some(index) => {
+                let end = self.nodes[index as usize].subtree_end;
+                self.positions_between(index, end, kinds)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns all nodes with a kind in `kinds` in preorder.
+    pub fn nodes(&self, kinds: SyntaxKindSet<L>) -> impl Iterator<Item = SyntaxNode<L>> + '_ {
+        self.positions(kinds)
+            .into_iter()
+            .map(|position| self.node_at(position))
+    }
+
+    /// Returns the nodes with a kind in `kinds` in the subtree of `subtree`, including `subtree` itself,
+    /// in preorder.
+    pub fn nodes_in<'a>(
+        &'a self,
+        subtree: &SyntaxNode<L>,
+        kinds: SyntaxKindSet<L>,
+    ) -> impl Iterator<Item = SyntaxNode<L>> + 'a {
+        let start = self
+            .preorder_index_of(subtree)
+            .map(|index| (subtree.clone(), index));
+
+        self.positions_in(subtree, kinds)
+            .into_iter()
+            .filter_map(move |position| {
+                let (subtree, index) = start.clone()?;
+                Some(self.descend(subtree, index, position.preorder_index))
+            })
+    }
+
+    /// Returns the node at `position`.
+    ///
+    /// ## Panics
+    /// If `position` isn't a position of this index.
+    pub fn node_at(&self, position: NodePosition) -> SyntaxNode<L> {
+        assert!(
+            (position.preorder_index as usize) < self.nodes.len(),
+            "position {position:?} is out of bounds"
+        );
+
+        self.descend(self.root.clone(), 0, position.preorder_index)
+    }
+
+    /// Updates the index for `new_root`, a new version of the indexed tree, for example, after
+    /// replacing a subtree.
+    ///
+    /// Only traverses the subtrees of `new_root` whose green nodes aren't part of the indexed tree;
+    /// the entries of all other subtrees are copied.
+    pub fn update(&mut self, new_root: &SyntaxNode<L>) {
+        let raw = cursor::node::SyntaxNode::from(new_root.clone());
+        let old_nodes = std::mem::take(&mut self.nodes);
+
+        // `self.root` keeps the old green nodes alive while their pointers are compared
+        update_subtree(
+            &mut self.nodes,
+            &old_nodes,
+            0,
+            raw.green(),
+            new_root.text_range_with_trivia().start(),
+        );
+
+        self.root = new_root.clone();
+        self.index_kinds();
+    }
+
+    fn index_kinds(&mut self) {
+        self.by_kind.iter_mut().for_each(Vec::clear);
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let kind = usize::from(node.kind.0);
+
+            if self.by_kind.len() <= kind {
+                self.by_kind.resize_with(kind + 1, Vec::new);
+            }
+
+            self.by_kind[kind].push(index as u32);
+        }
+    }
+
+    fn kind_indices(&self, kind: L::Kind) -> &[u32] {
+        self.by_kind
+            .get(usize::from(kind.to_raw().0))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the positions with a preorder index in `start..end` and a kind in `kinds`.
+    fn positions_between(
+        &self,
+        start: u32,
+        end: u32,
+        kinds: SyntaxKindSet<L>,
+    ) -> Vec<NodePosition> {
+        let mut indices: Vec<u32> = Vec::new();
+        let mut kind_count = 0;
+
+        for kind in kinds.iter() {
+            let list = self.kind_indices(kind);
+            let first = list.partition_point(|index| *index < start);
+            let last = list.partition_point(|index| *index < end);
+
+            indices.extend_from_slice(&list[first..last]);
+            kind_count += 1;
+        }
+
+        // The lists of the individual kinds are sorted already
+        if kind_count > 1 {
+            indices.sort_unstable();
+        }
+
+        indices
+            .into_iter()
+            .map(|index| NodePosition {
+                preorder_index: index,
+                range: self.nodes[index as usize].range,
+            })
+            .collect()
+    }
+
+    /// Returns the preorder index of `node` or `None` if it isn't part of the indexed tree.
+    fn preorder_index_of(&self, node: &SyntaxNode<L>) -> Option<u32> {
+        let mut path: Vec<SyntaxNode<L>> = Vec::new();
+        let mut ancestors = node.ancestors();
+
+        loop {
+            match ancestors.next() {
+                Some(ancestor) if ancestor == self.root => break,
+                Some(ancestor) => path.push(ancestor),
+                None => return None,
+            }
+        }
+
+        let mut parent = self.root.clone();
+        let mut index = 0;
+
+        for child in path.into_iter().rev() {
+            let raw = cursor::node::SyntaxNode::from(parent);
+            index += 1;
+
+            for slot in raw.green().slice().iter().take(child.index()) {
+                if let Slot::Node { .. } = slot {
+                    index = self.nodes[index as usize].subtree_end;
+                }
+            }
+
+            parent = child;
+        }
+
+        Some(index)
+    }
+
+    /// Returns the node with preorder index `target` in the subtree of `node`, which has the preorder index `index`.
+    fn descend(&self, mut node: SyntaxNode<L>, mut index: u32, target: u32) -> SyntaxNode<L> {
+        while index != target {
+            let raw = cursor::node::SyntaxNode::from(node.clone());
+            let mut child_index = index + 1;
+            let mut child_slot = None;
+
+            for (slot_index, slot) in raw.green().slice().iter().enumerate() {
+                if let Slot::Node { .. } = slot {
+                    let end = self.nodes[child_index as usize].subtree_end;
+
+                    if target < end {
+                        child_slot = Some(slot_index);
+                        break;
+                    }
+
+                    child_index = end;
+                }
+            }
+
+            let slot = child_slot.expect("target to be in the subtree of node");
+            node = node
+                .element_in_slot(slot as u32)
+                .and_then(|element| element.into_node())
+                .expect("slot to contain a node");
+            index = child_index;
+        }
+
+        node
+    }
+}
+
+/// Appends the entries for `green` and its descendants.
+fn index_subtree(nodes: &mut Vec<IndexedNode>, green: &GreenNodeData, offset: TextSize) {
+    let index = nodes.len();
+    nodes.push(IndexedNode {
+        kind: green.kind(),
+        range: TextRange::at(offset, green.text_len()),
+        subtree_end: 0,
+        green,
+    });
+
+    for slot in green.slice() {
+        if let Slot::Node { rel_offset, node } = slot {
+            index_subtree(nodes, node, offset + rel_offset);
+        }
+    }
+
+    nodes[index].subtree_end = nodes.len() as u32;
+}
+
+/// Appends the entries for `green`, the new version of the node at `old_index` in `old_nodes`.
+fn update_subtree(
+    nodes: &mut Vec<IndexedNode>,
+    old_nodes: &[IndexedNode],
+    old_index: u32,
+    green: &GreenNodeData,
+    offset: TextSize,
+) {
+    if std::ptr::eq(old_nodes[old_index as usize].green, green) {
+        copy_subtree(nodes, old_nodes, old_index, offset);
+        return;
+    }
+
+    let index = nodes.len();
+    nodes.push(IndexedNode {
+        kind: green.kind(),
+        range: TextRange::at(offset, green.text_len()),
+        subtree_end: 0,
+        green,
+    });
+
+    // The old children by their green node
+    let old_end = old_nodes[old_index as usize].subtree_end;
+    let mut old_children = FxHashMap::default();
+    let mut old_children_in_order = Vec::new();
+    let mut old_child = old_index + 1;
+
+    while old_child < old_end {
+        old_children
+            .entry(old_nodes[old_child as usize].green)
+            .or_insert(old_child);
+        old_children_in_order.push(old_child);
+        old_child = old_nodes[old_child as usize].subtree_end;
+    }
+
+    let child_nodes = green.slice().iter().filter_map(|slot| match slot {
+        Slot::Node { rel_offset, node } => Some((offset + rel_offset, &**node)),
+        _ => None,
+    });
+
+    for (position, (child_offset, child)) in child_nodes.enumerate() {
+        let child_ptr: *const GreenNodeData = child;
+
+        if let Some(old_child) = old_children.get(&child_ptr) {
+            copy_subtree(nodes, old_nodes, *old_child, child_offset);
+        } else if let Some(&old_child) = old_children_in_order
+            .get(position)
+            .filter(|old_child| old_nodes[**old_child as usize].kind == child.kind())
+        {
+            // Most likely the same node with a changed descendant
+            update_subtree(nodes, old_nodes, old_child, child, child_offset);
+        } else {
+            index_subtree(nodes, child, child_offset);
+        }
+    }
+
+    nodes[index].subtree_end = nodes.len() as u32;
+}
+
+/// Appends a copy of the entries of the subtree at `old_index` moved to `offset`.
+fn copy_subtree(
+    nodes: &mut Vec<IndexedNode>,
+    old_nodes: &[IndexedNode],
+    old_index: u32,
+    offset: TextSize,
+) {
+    let old_root = &old_nodes[old_index as usize];
+    let old_start = old_root.range.start();
+    let new_index = nodes.len() as u32;
+
+    nodes.extend(
+        old_nodes[old_index as usize..old_root.subtree_end as usize]
+            .iter()
+            .map(|old| IndexedNode {
+                kind: old.kind,
+                range: TextRange::at(old.range.start() - old_start + offset, old.range.len()),
+                subtree_end: old.subtree_end - old_index + new_index,
+                green: old.green,
+            }),
+    );
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::{
+        raw_language::{RawLanguage, RawLanguageKind, parse},
+        syntax_kind_set,
+    };
+
+    const EXPRESSIONS: SyntaxKindSet<RawLanguage> = syntax_kind_set![
+        RawLanguageKind::NAME_EXPRESSION,
+        RawLanguageKind::LITERAL_EXPRESSION,
+        RawLanguageKind::BINARY_EXPRESSION,
+    ];
+
+    fn find(
+        root: &SyntaxNode<RawLanguage>,
+        kind: RawLanguageKind,
+        text: &str,
+    ) -> SyntaxNode<RawLanguage> {
+        root.descendants()
+            .find(|node| node.kind() == kind && node.text_trimmed() == text)
+            .unwrap()
+    }
+
+    fn replace(
+        root: &SyntaxNode<RawLanguage>,
+        node: &SyntaxNode<RawLanguage>,
+        text: &str,
+    ) -> SyntaxNode<RawLanguage> {
+        // The innermost node spanning all of `text`
+        let replacement = parse(text)
+            .descendants()
+            .filter(|candidate| candidate.text_trimmed() == text.trim())
+            .last()
+            .unwrap()
+            .detach();
+
+        root.clone()
+            .replace_child(node.clone().into(), replacement.into())
+            .unwrap()
+    }
+
+    /// Asserts that `index` has the same entries as a new index of its root.
+    #[track_caller]
+    fn assert_same_as_new(index: &KindIndex<RawLanguage>) {
+        let expected = KindIndex::new(index.root());
+        let all = SyntaxKindSet::all();
+
+        assert_eq!(index.len(), expected.len());
+        assert_eq!(index.positions(all), expected.positions(all));
+        assert_eq!(
+            index.positions(EXPRESSIONS),
+            expected.positions(EXPRESSIONS)
+        );
+        assert_eq!(
+            index.nodes(all).collect::<Vec<_>>(),
+            index.root().descendants().collect::<Vec<_>>()
+        );
+
+        for node in index.root().descendants() {
+            assert_eq!(
+                index.nodes_in(&node, all).collect::<Vec<_>>(),
+                node.descendants().collect::<Vec<_>>()
+            );
+        }
+
+        for position in expected.positions(all) {
+            assert_eq!(index.node_at(position), expected.node_at(position));
+            assert_eq!(
+                index.node_at(position).text_range_with_trivia(),
+                position.text_range()
+            );
+        }
+    }
+
+    #[test]
+    fn positions_in_preorder() {
+        let root = parse("a + 1;\nf(b);");
+        let index = KindIndex::new(&root);
+
+        assert_eq!(index.len(), root.descendants().count());
+        assert_eq!(index.count(EXPRESSIONS), 5);
+
+        let nodes: Vec<_> = index
+            .nodes(EXPRESSIONS)
+            .map(|node| node.text_trimmed().to_string())
+            .collect();
+        assert_eq!(nodes, ["a + 1", "a", "1", "f", "b"]);
+
+        let positions = index.positions(syntax_kind_set![RawLanguageKind::ROOT]);
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].preorder_index(), 0);
+        assert_eq!(positions[0].text_range(), root.text_range_with_trivia());
+
+        assert_same_as_new(&index);
+    }
+
+    #[test]
+    fn positions_in_subtree() {
+        let root = parse("a + 1;\nf(b + c);");
+        let index = KindIndex::new(&root);
+        let call = find(&root, RawLanguageKind::CALL_EXPRESSION, "f(b + c)");
+
+        let nodes: Vec<_> = index
+            .nodes_in(&call, EXPRESSIONS)
+            .map(|node| node.text_trimmed().to_string())
+            .collect();
+        assert_eq!(nodes, ["f", "b + c", "b", "c"]);
+
+        let positions =
+            index.positions_in(&call, syntax_kind_set![RawLanguageKind::CALL_EXPRESSION]);
+        assert_eq!(positions.len(), 1);
+        assert_eq!(index.node_at(positions[0]), call);
+    }
+
+    #[test]
+    fn positions_in_node_outside_of_the_index() {
+        let root = parse("a + 1;");
+        let index = KindIndex::new(&root);
+        let all = SyntaxKindSet::all();
+
+        // A node of a different tree with the same text
+        let other = parse("a + 1;");
+        assert_eq!(index.positions_in(&other, all), vec![]);
+        assert_eq!(
+            index.nodes_in(&other.first_child().unwrap(), all).count(),
+            0
+        );
+
+        // A detached node
+        let statement = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "a + 1;");
+        assert_eq!(index.positions_in(&statement.clone().detach(), all), vec![]);
+        assert_eq!(index.positions_in(&statement, all).len(), 4);
+    }
+
+    #[test]
+    fn update_after_replacing_a_subtree() {
+        let root = parse("a + 1;\nf(b);\nc;");
+        let mut index = KindIndex::new(&root);
+
+        // Same kind, more nodes
+        let node = find(&root, RawLanguageKind::NAME_EXPRESSION, "b");
+        let root = replace(&root, &node, "(x * y)");
+        let node = find(&root, RawLanguageKind::PAREN_EXPRESSION, "(x * y)");
+        index.update(&root);
+        assert_same_as_new(&index);
+
+        // Fewer nodes and a different text length
+        let root = replace(&root, &node, "z");
+        index.update(&root);
+        assert_same_as_new(&index);
+
+        // A statement at the start, moving all following nodes
+        let node = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "a + 1;");
+        let root = replace(&root, &node, "g(h(1), 2 + 3);");
+        index.update(&root);
+        assert_same_as_new(&index);
+
+        // An unrelated tree
+        let root = parse("x;\ny;");
+        index.update(&root);
+        assert_same_as_new(&index);
+    }
+
+    #[test]
+    fn update_after_inserting_and_removing_statements() {
+        let root = parse("a;\nb;\nc;");
+        let mut index = KindIndex::new(&root);
+        let list = root.first_child().unwrap();
+        let new_statement = parse("\nd + e;")
+            .first_child()
+            .unwrap()
+            .first_child()
+            .unwrap()
+            .detach();
+
+        let inserted = list
+            .clone()
+            .splice_slots(1..1, [Some(new_statement.into())]);
+        let root = root
+            .clone()
+            .replace_child(list.into(), inserted.into())
+            .unwrap();
+        index.update(&root);
+        assert_same_as_new(&index);
+
+        let list = root.first_child().unwrap();
+        let removed = list.clone().splice_slots(0..2, []);
+        let root = root
+            .clone()
+            .replace_child(list.into(), removed.into())
+            .unwrap();
+        index.update(&root);
+        assert_same_as_new(&index);
+
+        // The nodes of the old tree aren't part of the updated index
+        let old = parse("a;");
+        assert_eq!(index.positions_in(&old, SyntaxKindSet::all()), vec![]);
+    }
+}
@@ -11,13 +11,26 @@ This structure should implement the following:
 pub mod api;
 pub mod arc;
 pub mod ast;
+pub mod comments;
 pub mod cow_mut;
 pub mod cursor;
+#[cfg(feature = "countme")]
+pub mod diagnostics;
 pub mod green;
+pub mod kind_index;
+pub mod line_index;
+pub mod query;
+#[cfg(feature = "testing")]
+pub mod raw_language;
 pub mod red;
+pub mod selector;
 pub mod sll;
+pub mod ssr;
+pub mod suppression;
 pub mod syntax;
 pub mod syntax_factory;
 pub mod syntax_token_text;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod token_text;
 pub mod utility_types;
@@ -0,0 +1,462 @@
+//! Conversion between [TextSize] offsets and line/column positions.
+//!
+//! Columns of [LineCol] are measured in UTF-8 bytes, the unit of [TextSize]. Editors usually
+//! count columns in UTF-16 code units (LSP's default) or in characters (UTF-32), use
+//! [LineIndex::to_wide] and [LineIndex::to_utf8] to convert between the encodings.
+//!
+//! Lines are separated by `\n`. A `\r` preceding a `\n` is part of the line it ends.
+
+use text_size::{TextRange, TextSize};
+
+use crate::syntax::{Language, node::SyntaxNode};
+
+/// A zero-based line and column, with the column measured in UTF-8 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+/// A zero-based line and column, with the column measured in the units of a [WideEncoding].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WideLineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+/// An encoding in which editors measure columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WideEncoding {
+    /// Columns are measured in UTF-16 code units.
+    Utf16,
+    /// Columns are measured in unicode scalar values (characters).
+    Utf32,
+}
+
+/// A character that's encoded with more than one byte in UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WideChar {
+    /// Absolute offset of the character.
+    start: TextSize,
+    /// Length of the character in UTF-8.
+    len: u8,
+}
+
+impl WideChar {
+    fn end(&self) -> TextSize {
+        self.start + TextSize::from(u32::from(self.len))
+    }
+
+    /// Returns the length of the character in the units of `encoding`.
+    fn wide_len(&self, encoding: WideEncoding) -> u32 {
+        match encoding {
+            WideEncoding::Utf16 if self.len == 4 => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// Maps offsets in a text to line/column positions and back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// The offsets at which lines start. The first line starts at offset 0.
+    line_starts: Vec<TextSize>,
+    /// All characters that are longer than a byte in UTF-8, sorted by offset.
+    wide_chars: Vec<WideChar>,
+    len: TextSize,
+}
+
+impl LineIndex {
+    /// Creates the index for `text`.
+    pub fn new(text: &str) -> Self {
+        let mut builder = LineIndexBuilder::default();
+        builder.push_chunk(text);
+        builder.finish()
+    }
+
+    /// Creates the index for the text of the tree rooted at `root`, including its trivia.
+    ///
+    /// The offsets of the index are relative to the start of `root`.
+    pub fn from_node<L: Language>(root: &SyntaxNode<L>) -> Self {
+        let mut builder = LineIndexBuilder::default();
+        root.text_with_trivia()
+            .for_each_chunk(|chunk| builder.push_chunk(chunk));
+        builder.finish()
+    }
+
+    /// Returns the length of the indexed text.
+    pub fn len(&self) -> TextSize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == TextSize::from(0)
+    }
+
+    /// Returns the number of lines. An empty text has one line.
+    pub fn line_count(&self) -> u32 {
+        self.line_starts.len() as u32
+    }
+
+    /// Returns the range of `line`, excluding its `\n`.
+    pub fn line_range(&self, line: u32) -> Option<TextRange> {
+        let start = *self.line_starts.get(line as usize)?;
+        let end = self
+            .line_starts
+            .get(line as usize + 1)
+            .map_or(self.len, |next| *next - TextSize::from(1));
+
+        Some(TextRange::new(start, end))
+    }
+
+    /// Returns the line and UTF-8 column of `offset` or `None` if `offset` is past the end of the text.
+    pub fn line_col(&self, offset: TextSize) -> Option<LineCol> {
+        if offset > self.len {
+            return None;
+        }
+
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let col = offset - self.line_starts[line];
+
+        Some(LineCol {
+            line: line as u32,
+            col: col.into(),
+        })
+    }
+
+    /// Returns the offset of `line_col` or `None` if the position is outside of the text.
+    pub fn offset(&self, line_col: LineCol) -> Option<TextSize> {
+        let range = self.line_range(line_col.line)?;
+        let offset = range.start() + TextSize::from(line_col.col);
+
+        (offset <= range.end()).then_some(offset)
+    }
+
+    /// Converts a position with a UTF-8 column to a position with a column in `encoding`.
+    pub fn to_wide(&self, encoding: WideEncoding, line_col: LineCol) -> Option<WideLineCol> {
+        let offset = self.offset(line_col)?;
+        let line_start = self.line_starts[line_col.line as usize];
+
+        let mut col = line_col.col;
+        for c in self.wide_chars_in(TextRange::new(line_start, offset)) {
+            col -= u32::from(c.len) - c.wide_len(encoding);
+        }
+
+        Some(WideLineCol {
+            line: line_col.line,
+            col,
+        })
+    }
+
+    /// Converts a position with a column in `encoding` to a position with a UTF-8 column.
+    ///
+    /// Returns `None` if the position is outside of the text. A column in the middle of a character is
+    /// rounded to the end of the character.
+    pub fn to_utf8(&self, encoding: WideEncoding, wide: WideLineCol) -> Option<LineCol> {
+        let range = self.line_range(wide.line)?;
+
+        let mut col = wide.col;
+        for c in self.wide_chars_in(range) {
+            let start = u32::from(c.start - range.start());
+
+            if start >= col {
+                break;
+            }
+
+            col += u32::from(c.len) - c.wide_len(encoding);
+            // The column points into the middle of `c`
+            col = col.max(u32::from(c.end() - range.start()));
+        }
+
+        let line_col = LineCol {
+            line: wide.line,
+            col,
+        };
+
+        self.offset(line_col).map(|_| line_col)
+    }
+
+    /// Updates the index for replacing the text in `range` with `new_text`.
+    ///
+    /// Only scans `new_text`, the positions after the edit are shifted without re-reading the text.
+    ///
+    /// ## Panics
+    /// If `range` extends past the end of the text.
+    pub fn apply_edit(&mut self, range: TextRange, new_text: &str) {
+        assert!(
+            range.end() <= self.len,
+            "edit range {range:?} is out of bounds for a text of length {:?}",
+            self.len
+        );
+
+        let inserted = LineIndex::new(new_text);
+        let new_len = self.len - range.len() + inserted.len;
+
+        let shift = |offset: TextSize| offset - range.end() + range.start() + inserted.len;
+
+        // Line starts in the replaced range are removed, the ones after it move
+        let first_removed = self
+            .line_starts
+            .partition_point(|start| *start <= range.start());
+        let first_kept = self
+            .line_starts
+            .partition_point(|start| *start <= range.end());
+
+        let line_starts = inserted.line_starts[1..]
+            .iter()
+            .map(|start| *start + range.start())
+            .chain(
+                self.line_starts[first_kept..]
+                    .iter()
+                    .map(|start| shift(*start)),
+            )
+            .collect::<Vec<_>>();
+        self.line_starts.truncate(first_removed);
+        self.line_starts.extend(line_starts);
+
+        let first_removed = self.wide_chars.partition_point(|c| c.start < range.start());
+        let first_kept = self.wide_chars.partition_point(|c| c.start < range.end());
+
+        let wide_chars = inserted
+            .wide_chars
+            .iter()
+            .map(|c| WideChar {
+                start: c.start + range.start(),
+                len: c.len,
+            })
+            .chain(self.wide_chars[first_kept..].iter().map(|c| WideChar {
+                start: shift(c.start),
+                len: c.len,
+            }))
+            .collect::<Vec<_>>();
+        self.wide_chars.truncate(first_removed);
+        self.wide_chars.extend(wide_chars);
+
+        self.len = new_len;
+    }
+
+    fn wide_chars_in(&self, range: TextRange) -> &[WideChar] {
+        let start = self.wide_chars.partition_point(|c| c.start < range.start());
+        let end = self.wide_chars.partition_point(|c| c.start < range.end());
+        &self.wide_chars[start..end]
+    }
+}
+
+#[derive(Debug)]
+struct LineIndexBuilder {
+    line_starts: Vec<TextSize>,
+    wide_chars: Vec<WideChar>,
+    offset: TextSize,
+}
+
+impl Default for LineIndexBuilder {
+    fn default() -> Self {
+        Self {
+            line_starts: vec![TextSize::from(0)],
+            wide_chars: Vec::new(),
+            offset: TextSize::from(0),
+        }
+    }
+}
+
+impl LineIndexBuilder {
+    fn push_chunk(&mut self, chunk: &str) {
+        for c in chunk.chars() {
+            let len = TextSize::of(c);
+
+            if c == '\n' {
+                self.line_starts.push(self.offset + len);
+            } else if !c.is_ascii() {
+                self.wide_chars.push(WideChar {
+                    start: self.offset,
+                    len: u32::from(len) as u8,
+                });
+            }
+
+            self.offset += len;
+        }
+    }
+
+    fn finish(self) -> LineIndex {
+        LineIndex {
+            line_starts: self.line_starts,
+            wide_chars: self.wide_chars,
+            len: self.offset,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::parse;
+
+    fn line_col(line: u32, col: u32) -> LineCol {
+        LineCol { line, col }
+    }
+
+    fn wide(line: u32, col: u32) -> WideLineCol {
+        WideLineCol { line, col }
+    }
+
+    #[test]
+    fn line_col_and_offset() {
+        let index = LineIndex::new("ab\ncd\n\nef");
+
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line_col(0.into()), Some(line_col(0, 0)));
+        assert_eq!(index.line_col(2.into()), Some(line_col(0, 2)));
+        assert_eq!(index.line_col(3.into()), Some(line_col(1, 0)));
+        assert_eq!(index.line_col(6.into()), Some(line_col(2, 0)));
+        assert_eq!(index.line_col(9.into()), Some(line_col(3, 2)));
+        assert_eq!(index.line_col(10.into()), None);
+
+        for offset in 0..=9u32 {
+            let line_col = index.line_col(offset.into()).unwrap();
+            assert_eq!(index.offset(line_col), Some(offset.into()));
+        }
+
+        assert_eq!(index.offset(line_col(0, 3)), None);
+        assert_eq!(index.offset(line_col(4, 0)), None);
+    }
+
+    #[test]
+    fn empty_text() {
+        let index = LineIndex::new("");
+
+        assert!(index.is_empty());
+        assert_eq!(index.line_count(), 1);
+        assert_eq!(index.line_range(0), Some(TextRange::empty(0.into())));
+        assert_eq!(index.line_col(0.into()), Some(line_col(0, 0)));
+    }
+
+    #[test]
+    fn carriage_return_line_feed() {
+        let index = LineIndex::new("ab\r\ncd\r\n");
+
+        assert_eq!(index.line_count(), 3);
+        // The `\r` is part of the line it ends
+        assert_eq!(
+            index.line_range(0),
+            Some(TextRange::new(0.into(), 3.into()))
+        );
+        assert_eq!(
+            index.line_range(1),
+            Some(TextRange::new(4.into(), 7.into()))
+        );
+        assert_eq!(index.line_range(2), Some(TextRange::empty(8.into())));
+        assert_eq!(index.line_col(3.into()), Some(line_col(0, 3)));
+        assert_eq!(index.line_col(4.into()), Some(line_col(1, 0)));
+        assert_eq!(index.offset(line_col(0, 4)), None);
+    }
+
+    #[test]
+    fn wide_round_trip() {
+        // `é` is 2 bytes, 1 UTF-16 unit; `€` is 3 bytes, 1 unit; `😀` is 4 bytes, 2 units
+        let text = "aé€😀b\n😀x";
+        let index = LineIndex::new(text);
+
+        let utf16 = |col| {
+            index
+                .to_wide(WideEncoding::Utf16, line_col(0, col))
+                .unwrap()
+        };
+        let utf32 = |col| {
+            index
+                .to_wide(WideEncoding::Utf32, line_col(0, col))
+                .unwrap()
+        };
+
+        assert_eq!(utf16(1), wide(0, 1));
+        assert_eq!(utf16(3), wide(0, 2));
+        assert_eq!(utf16(6), wide(0, 3));
+        assert_eq!(utf16(10), wide(0, 5));
+        assert_eq!(utf16(11), wide(0, 6));
+        assert_eq!(utf32(10), wide(0, 4));
+        assert_eq!(utf32(11), wide(0, 5));
+        assert_eq!(
+            index.to_wide(WideEncoding::Utf16, line_col(1, 5)),
+            Some(wide(1, 3))
+        );
+
+        for (offset, _) in text.char_indices().chain([(text.len(), ' ')]) {
+            let line_col = index.line_col(TextSize::try_from(offset).unwrap()).unwrap();
+
+            for encoding in [WideEncoding::Utf16, WideEncoding::Utf32] {
+                let wide = index.to_wide(encoding, line_col).unwrap();
+                assert_eq!(index.to_utf8(encoding, wide), Some(line_col));
+            }
+        }
+    }
+
+    #[test]
+    fn to_utf8_rounds_to_the_end_of_a_character() {
+        let index = LineIndex::new("a😀b");
+
+        // The second UTF-16 unit of `😀`
+        assert_eq!(
+            index.to_utf8(WideEncoding::Utf16, wide(0, 2)),
+            Some(line_col(0, 5))
+        );
+        assert_eq!(
+            index.to_utf8(WideEncoding::Utf16, wide(0, 3)),
+            Some(line_col(0, 5))
+        );
+        assert_eq!(
+            index.to_utf8(WideEncoding::Utf16, wide(0, 4)),
+            Some(line_col(0, 6))
+        );
+        assert_eq!(index.to_utf8(WideEncoding::Utf16, wide(0, 5)), None);
+        assert_eq!(index.to_utf8(WideEncoding::Utf32, wide(0, 4)), None);
+        assert_eq!(index.to_utf8(WideEncoding::Utf16, wide(1, 0)), None);
+    }
+
+    #[test]
+    fn from_node() {
+        let text = "a;\n// é\nb;";
+
+        assert_eq!(LineIndex::from_node(&parse(text)), LineIndex::new(text));
+    }
+
+    #[test]
+    fn apply_edit_matches_rebuilt_index() {
+        let texts = ["", "a", "ab\ncd", "\n\n", "a\r\nb\r\n", "é\n😀x\n€"];
+        let insertions = ["", "x", "\n", "é\n", "\n😀\n", "a\r\n\nb"];
+
+        for text in texts {
+            let boundaries: Vec<_> = text
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain([text.len()])
+                .collect();
+
+            for (i, &start) in boundaries.iter().enumerate() {
+                for &end in &boundaries[i..] {
+                    for insertion in insertions {
+                        let mut index = LineIndex::new(text);
+                        let range = TextRange::new(
+                            TextSize::try_from(start).unwrap(),
+                            TextSize::try_from(end).unwrap(),
+                        );
+                        index.apply_edit(range, insertion);
+
+                        let mut edited = text.to_string();
+                        edited.replace_range(start..end, insertion);
+
+                        assert_eq!(
+                            index,
+                            LineIndex::new(&edited),
+                            "replacing {range:?} of {text:?} with {insertion:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn apply_edit_out_of_bounds() {
+        LineIndex::new("ab").apply_edit(TextRange::new(1.into(), 3.into()), "");
+    }
+}
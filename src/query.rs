@@ -0,0 +1,1487 @@
+//! A tree-sitter style query language for finding patterns in syntax trees.
+//!
+//! A [Query] is compiled from one or more S-expression patterns. Each pattern matches a node or
+//! token and, optionally, its children:
+//!
+//! ```text
+//! (CALL_EXPRESSION callee: (NAME_EXPRESSION @fn) arguments: (CALL_ARGUMENTS) @args)
+//! ```
+//!
+//! * `(KIND child...)` matches a node of the kind named `KIND`. `(KIND)` without children also matches a token of that kind.
+//! * `(_ child...)` matches any node and `_` matches any node or token.
+//! * `"text"` matches a token whose trimmed text is `text`.
+//! * `field: pattern` matches the slot named `field` of the parent node, `!field` requires that the slot is empty.
+//! * Other children match the node's children in order, allowing any number of children between them.
+//!   `((A) (B))` groups sibling patterns into a sequence.
+//! * `[pattern...]` matches any of the alternatives.
+//! * `?`, `*`, and `+` after a child repeat it zero or one times, zero or more times, and one or more times.
+//! * `@name` after a pattern captures the matched element. A capture directly after the kind captures the node itself: `(KIND @name)`.
+//! * `(#eq? @capture "text")`, `(#eq? @capture @other)`, `(#match? @capture "regex")` and their negations `#not-eq?` and
+//!   `#not-match?` filter matches by the trimmed text of the captured elements. `#match?` and `#not-match?` require the `regex` feature.
+//! * `;` starts a comment that extends to the end of the line.
+//!
+//! The names of kinds and slots are provided by the language's [QueryLanguage] implementation.
+
+use std::fmt;
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+use text_size::{TextRange, TextSize};
+
+use crate::{
+    syntax::{
+        Language,
+        element::SyntaxElement,
+        node::{PreorderWithTokens, SyntaxNode},
+    },
+    utility_types::{Direction, NodeOrToken, WalkEvent},
+};
+
+/// Provides the names of a language's kinds and slots for use in queries.
+pub trait QueryLanguage: Language {
+    /// Returns the kind with the given name, for example, `CALL_EXPRESSION`.
+    fn kind_by_name(name: &str) -> Option<Self::Kind>;
+
+    /// Returns the name of `kind`, the inverse of [QueryLanguage::kind_by_name].
+    ///
+    /// Defaults to the kind's `Debug` representation.
+    fn kind_name(kind: Self::Kind) -> String {
+        format!("{kind:?}")
+    }
+
+    /// Returns the index of the slot called `name` in nodes of `kind`.
+    ///
+    /// Defaults to the slot names of the language's [slot_schema](Language::slot_schema).
+    fn slot_by_name(kind: Self::Kind, name: &str) -> Option<usize> {
+        Self::slot_schema()?.slot_index(kind, name)
+    }
+}
+
+/// An error found while compiling a [Query].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    /// The range in the query source.
+    pub range: TextRange,
+    pub kind: QueryErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryErrorKind {
+    /// The query ends in the middle of a pattern.
+    UnexpectedEnd,
+    /// A character that can't start or continue a pattern.
+    UnexpectedChar(char),
+    /// A string literal without a closing `"`.
+    UnterminatedString,
+    /// The language has no kind with this name.
+    UnknownKind(String),
+    /// The parent kind has no slot with this name.
+    UnknownField(String),
+    /// A field outside of a node pattern, for example, in a group.
+    FieldOutsideNode(String),
+    /// A predicate other than `#eq?`, `#not-eq?`, `#match?`, and `#not-match?`, or `#match?` and `#not-match?`
+    /// without the `regex` feature.
+    UnknownPredicate(String),
+    /// A predicate with the wrong number or type of arguments.
+    InvalidPredicateArguments(String),
+    /// A predicate that isn't a child of a node pattern or group.
+    MisplacedPredicate,
+    /// The pattern of a `#match?` predicate isn't a valid regular expression.
+    InvalidRegex(String),
+    /// A predicate references a capture that isn't defined in its pattern.
+    UnknownCapture(String),
+    /// A quantifier on a top-level pattern or `*` and `+` on a field.
+    InvalidQuantifier,
+    /// A capture on a group of sibling patterns.
+    CaptureOnGroup,
+    /// A group or an alternation without patterns.
+    Empty,
+    /// A top-level group with more than one pattern.
+    SequenceAtTopLevel,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            QueryErrorKind::UnexpectedEnd => write!(f, "unexpected end of the query"),
+            QueryErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {c:?}"),
+            QueryErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            QueryErrorKind::UnknownKind(name) => write!(f, "unknown kind `{name}`"),
+            QueryErrorKind::UnknownField(name) => write!(f, "unknown field `{name}`"),
+            QueryErrorKind::FieldOutsideNode(name) => {
+                write!(f, "field `{name}` must be a child of a node pattern")
+            }
+            QueryErrorKind::UnknownPredicate(name) => write!(f, "unknown predicate `#{name}`"),
+            QueryErrorKind::InvalidPredicateArguments(name) => {
+                write!(f, "invalid arguments for predicate `#{name}`")
+            }
+            QueryErrorKind::MisplacedPredicate => {
+                write!(f, "predicates must be children of a node pattern or group")
+            }
+            QueryErrorKind::InvalidRegex(error) => write!(f, "invalid regex: {error}"),
+            QueryErrorKind::UnknownCapture(name) => write!(f, "unknown capture `@{name}`"),
+            QueryErrorKind::InvalidQuantifier => write!(
+                f,
+                "quantifiers are only allowed on children, fields only support `?`"
+            ),
+            QueryErrorKind::CaptureOnGroup => write!(f, "groups can't be captured"),
+            QueryErrorKind::Empty => write!(f, "groups and alternations must not be empty"),
+            QueryErrorKind::SequenceAtTopLevel => {
+                write!(f, "a top-level group must contain exactly one pattern")
+            }
+        }?;
+
+        write!(f, " at {:?}", self.range)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// A compiled query. See the [module documentation](self) for the syntax.
+#[derive(Debug)]
+pub struct Query<L: QueryLanguage> {
+    patterns: Vec<TopLevelPattern<L::Kind>>,
+    capture_names: Vec<String>,
+}
+
+impl<L: QueryLanguage> Query<L> {
+    /// Compiles the patterns in `source`.
+    pub fn new(source: &str) -> Result<Self, QueryError> {
+        QueryParser::<L>::new(source).parse()
+    }
+
+    /// Returns the number of top-level patterns.
+    pub fn pattern_count(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Returns the names of all captures, indexed by [QueryCapture::index].
+    pub fn capture_names(&self) -> &[String] {
+        &self.capture_names
+    }
+
+    /// Returns the index of the capture called `name` (without the `@`).
+    pub fn capture_index(&self, name: &str) -> Option<usize> {
+        self.capture_names
+            .iter()
+            .position(|capture| capture == name)
+    }
+
+    /// Returns the matches of all patterns in the subtree rooted at `root`, including `root` itself.
+    ///
+    /// The matches are ordered by the position of the matched element and then by the pattern index.
+    /// Each pattern matches an element at most once, with the first assignment of children in source order.
+    pub fn matches<'q>(&'q self, root: &SyntaxNode<L>) -> QueryMatches<'q, L> {
+        QueryMatches {
+            query: self,
+            preorder: root.preorder_with_tokens(Direction::Next),
+            element: None,
+            next_pattern: 0,
+        }
+    }
+
+    /// Returns the match of the pattern at `pattern_index` for `element` if it matches.
+    pub fn match_element(
+        &self,
+        pattern_index: usize,
+        element: &SyntaxElement<L>,
+    ) -> Option<QueryMatch<'_, L>> {
+        let TopLevelPattern {
+            pattern,
+            predicates,
+        } = &self.patterns[pattern_index];
+        let mut captures = Vec::new();
+
+        let matched = match_pattern(pattern, element, &mut captures, &mut |captures| {
+            predicates.iter().all(|predicate| predicate.holds(captures))
+        });
+
+        matched.then(|| QueryMatch {
+            query: self,
+            pattern_index,
+            captures,
+        })
+    }
+}
+
+/// Iterator over the matches of a [Query], returned by [Query::matches].
+pub struct QueryMatches<'q, L: QueryLanguage> {
+    query: &'q Query<L>,
+    preorder: PreorderWithTokens<L>,
+    element: Option<SyntaxElement<L>>,
+    next_pattern: usize,
+}
+
+impl<'q, L: QueryLanguage> Iterator for QueryMatches<'q, L> {
+    type Item = QueryMatch<'q, L>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(element) = &self.element {
+                while self.next_pattern < self.query.patterns.len() {
+                    let pattern_index = self.next_pattern;
+                    self.next_pattern += 1;
+
+                    if let Some(query_match) = self.query.match_element(pattern_index, element) {
+                        return Some(query_match);
+                    }
+                }
+            }
+
+            self.element = loop {
+                match self.preorder.next()? {
+                    WalkEvent::Enter(element) => break Some(element),
+                    WalkEvent::Leave(_) => continue,
+                }
+            };
+            self.next_pattern = 0;
+        }
+    }
+}
+
+/// A match of a query pattern.
+#[derive(Debug, Clone)]
+pub struct QueryMatch<'q, L: QueryLanguage> {
+    query: &'q Query<L>,
+    pattern_index: usize,
+    captures: Vec<QueryCapture<L>>,
+}
+
+impl<L: QueryLanguage> QueryMatch<'_, L> {
+    /// Returns the index of the matched top-level pattern.
+    pub fn pattern_index(&self) -> usize {
+        self.pattern_index
+    }
+
+    /// Returns all captures in the order in which they were matched.
+    pub fn captures(&self) -> &[QueryCapture<L>] {
+        &self.captures
+    }
+
+    /// Returns the first element captured as `name`.
+    pub fn capture(&self, name: &str) -> Option<&SyntaxElement<L>> {
+        self.captures_named(name).next()
+    }
+
+    /// Returns all elements captured as `name`. Quantified captures can capture multiple elements.
+    pub fn captures_named(&self, name: &str) -> impl Iterator<Item = &SyntaxElement<L>> {
+        let index = self.query.capture_index(name);
+
+        self.captures
+            .iter()
+            .filter(move |capture| Some(capture.index) == index)
+            .map(|capture| &capture.element)
+    }
+}
+
+/// An element captured by a [QueryMatch].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCapture<L: Language> {
+    index: usize,
+    element: SyntaxElement<L>,
+}
+
+impl<L: Language> QueryCapture<L> {
+    /// Returns the index of the capture's name in [Query::capture_names].
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn element(&self) -> &SyntaxElement<L> {
+        &self.element
+    }
+}
+
+#[derive(Debug)]
+struct TopLevelPattern<K> {
+    pattern: Pattern<K>,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug)]
+struct Pattern<K> {
+    kind: PatternKind<K>,
+    /// The indices of the captures of the matched element.
+    captures: Vec<usize>,
+}
+
+#[derive(Debug)]
+enum PatternKind<K> {
+    /// `_`: Any node or token.
+    Any,
+    /// `"text"`: A token with the given trimmed text.
+    Text(String),
+    /// `(KIND ...)` or `(_ ...)`
+    Node {
+        /// The kind of the node or `None` for any node.
+        kind: Option<K>,
+        fields: Vec<FieldItem<K>>,
+        children: Vec<ChildItem<K>>,
+    },
+    /// `[...]`
+    Alternation(Vec<Pattern<K>>),
+    /// `((...) (...))`: A sequence of sibling patterns.
+    Group(Vec<ChildItem<K>>),
+}
+
+#[derive(Debug)]
+struct FieldItem<K> {
+    name: String,
+    /// The index of the slot if the kind of the parent is known.
+    slot: Option<usize>,
+    /// The pattern of the slot or `None` if the slot must be empty.
+    pattern: Option<Pattern<K>>,
+    optional: bool,
+}
+
+#[derive(Debug)]
+struct ChildItem<K> {
+    pattern: Pattern<K>,
+    quantifier: Quantifier,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quantifier {
+    One,
+    ZeroOrOne,
+    ZeroOrMore,
+    OneOrMore,
+}
+
+impl Quantifier {
+    /// Returns the minimum and maximum number of repetitions.
+    fn bounds(self) -> (usize, usize) {
+        match self {
+            Quantifier::One => (1, 1),
+            Quantifier::ZeroOrOne => (0, 1),
+            Quantifier::ZeroOrMore => (0, usize::MAX),
+            Quantifier::OneOrMore => (1, usize::MAX),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Predicate {
+    Eq {
+        capture: usize,
+        value: PredicateValue,
+        negated: bool,
+    },
+    #[cfg(feature = "regex")]
+    Match {
+        capture: usize,
+        regex: Regex,
+        negated: bool,
+    },
+}
+
+#[derive(Debug)]
+enum PredicateValue {
+    Capture(usize),
+    Text(String),
+}
+
+impl Predicate {
+    /// Returns `true` if the predicate holds for every element of the referenced captures.
+    fn holds<L: Language>(&self, captures: &[QueryCapture<L>]) -> bool {
+        let texts = |index: usize| {
+            captures
+                .iter()
+                .filter(move |capture| capture.index == index)
+                .map(|capture| element_text(&capture.element))
+        };
+
+        match self {
+            Predicate::Eq {
+                capture,
+                value: PredicateValue::Text(text),
+                negated,
+            } => texts(*capture).all(|captured| (captured == *text) != *negated),
+            Predicate::Eq {
+                capture,
+                value: PredicateValue::Capture(other),
+                negated,
+            } => texts(*capture)
+                .all(|captured| texts(*other).all(|other| (captured == other) != *negated)),
+            #[cfg(feature = "regex")]
+            Predicate::Match {
+                capture,
+                regex,
+                negated,
+            } => texts(*capture).all(|captured| regex.is_match(&captured) != *negated),
+        }
+    }
+}
+
+fn element_text<L: Language>(element: &SyntaxElement<L>) -> String {
+    match element {
+        NodeOrToken::Node(node) => node.text_trimmed().to_string(),
+        NodeOrToken::Token(token) => String::from(token.text_trimmed()),
+    }
+}
+
+type Captures<L> = Vec<QueryCapture<L>>;
+
+/// Matches `pattern` against `element` and calls `next` with the captures if it matches.
+///
+/// Returns `false` and restores the captures if neither the pattern nor `next` match.
+/// Backtracks into other assignments of the children if `next` returns `false`.
+fn match_pattern<L: QueryLanguage>(
+    pattern: &Pattern<L::Kind>,
+    element: &SyntaxElement<L>,
+    captures: &mut Captures<L>,
+    next: &mut dyn FnMut(&mut Captures<L>) -> bool,
+) -> bool {
+    let mark = captures.len();
+    captures.extend(pattern.captures.iter().map(|index| QueryCapture {
+        index: *index,
+        element: element.clone(),
+    }));
+
+    let matched = match &pattern.kind {
+        PatternKind::Any => next(captures),
+        PatternKind::Text(text) => {
+            matches!(element, NodeOrToken::Token(token) if token.text_trimmed() == text)
+                && next(captures)
+        }
+        PatternKind::Alternation(alternatives) => alternatives
+            .iter()
+            .any(|alternative| match_pattern(alternative, element, captures, next)),
+        PatternKind::Group(items) => match_sequence(
+            items,
+            std::slice::from_ref(element),
+            0,
+            captures,
+            &mut |captures, _| next(captures),
+        ),
+        PatternKind::Node {
+            kind,
+            fields,
+            children,
+        } => {
+            if kind.is_some_and(|kind| kind != element.kind()) {
+                false
+            } else {
+                match element {
+                    NodeOrToken::Token(_) => {
+                        kind.is_some() && fields.is_empty() && children.is_empty() && next(captures)
+                    }
+                    NodeOrToken::Node(node) => {
+                        let elements: Vec<_> = node.children_with_tokens().collect();
+                        match_fields(node, fields, &elements, children, captures, next)
+                    }
+                }
+            }
+        }
+    };
+
+    if !matched {
+        captures.truncate(mark);
+    }
+
+    matched
+}
+
+/// Matches the `fields` of `node` followed by the positional `children`.
+fn match_fields<L: QueryLanguage>(
+    node: &SyntaxNode<L>,
+    fields: &[FieldItem<L::Kind>],
+    elements: &[SyntaxElement<L>],
+    children: &[ChildItem<L::Kind>],
+    captures: &mut Captures<L>,
+    next: &mut dyn FnMut(&mut Captures<L>) -> bool,
+) -> bool {
+    let Some((field, rest)) = fields.split_first() else {
+        return match_sequence(children, elements, 0, captures, &mut |captures, _| {
+            next(captures)
+        });
+    };
+
+    let Some(slot) = field
+        .slot
+        .or_else(|| L::slot_by_name(node.kind(), &field.name))
+    else {
+        return false;
+    };
+
+    let element = (slot < node.slots().len())
+        .then(|| node.element_in_slot(slot as u32))
+        .flatten();
+
+    let mut match_rest =
+        |captures: &mut Captures<L>| match_fields(node, rest, elements, children, captures, next);
+
+    match (&field.pattern, element) {
+        (None, element) => element.is_none() && match_rest(captures),
+        (Some(pattern), Some(element)) => {
+            match_pattern(pattern, &element, captures, &mut match_rest)
+                || (field.optional && match_rest(captures))
+        }
+        (Some(_), None) => field.optional && match_rest(captures),
+    }
+}
+
+/// Matches `items` against `elements` starting at `position`, allowing gaps between the matched elements.
+/// Calls `next` with the position after the last matched element.
+fn match_sequence<L: QueryLanguage>(
+    items: &[ChildItem<L::Kind>],
+    elements: &[SyntaxElement<L>],
+    position: usize,
+    captures: &mut Captures<L>,
+    next: &mut dyn FnMut(&mut Captures<L>, usize) -> bool,
+) -> bool {
+    let Some((item, rest)) = items.split_first() else {
+        return next(captures, position);
+    };
+
+    match_repetitions(item, rest, elements, position, 0, captures, next)
+}
+
+/// Matches further repetitions of `item`, preferring more over fewer repetitions,
+/// then continues with `rest`.
+fn match_repetitions<L: QueryLanguage>(
+    item: &ChildItem<L::Kind>,
+    rest: &[ChildItem<L::Kind>],
+    elements: &[SyntaxElement<L>],
+    position: usize,
+    count: usize,
+    captures: &mut Captures<L>,
+    next: &mut dyn FnMut(&mut Captures<L>, usize) -> bool,
+) -> bool {
+    let (min, max) = item.quantifier.bounds();
+
+    if count < max {
+        for start in position..elements.len() {
+            let matched = match_unit(
+                &item.pattern,
+                elements,
+                start,
+                captures,
+                &mut |captures, end| {
+                    if end == position {
+                        // Stop repeating patterns that don't consume any element
+                        count + 1 >= min && match_sequence(rest, elements, end, captures, next)
+                    } else {
+                        match_repetitions(item, rest, elements, end, count + 1, captures, next)
+                    }
+                },
+            );
+
+            if matched {
+                return true;
+            }
+        }
+    }
+
+    count >= min && match_sequence(rest, elements, position, captures, next)
+}
+
+/// Matches `pattern` at `elements[start]` or, for groups, against the elements starting at `start`.
+fn match_unit<L: QueryLanguage>(
+    pattern: &Pattern<L::Kind>,
+    elements: &[SyntaxElement<L>],
+    start: usize,
+    captures: &mut Captures<L>,
+    next: &mut dyn FnMut(&mut Captures<L>, usize) -> bool,
+) -> bool {
+    match &pattern.kind {
+        PatternKind::Group(items) => match_sequence(items, elements, start, captures, next),
+        PatternKind::Alternation(alternatives) => alternatives
+            .iter()
+            .any(|alternative| match_unit(alternative, elements, start, captures, next)),
+        _ => elements.get(start).is_some_and(|element| {
+            match_pattern(pattern, element, captures, &mut |captures| {
+                next(captures, start + 1)
+            })
+        }),
+    }
+}
+
+type ParseResult<T> = Result<T, QueryError>;
+
+/// The fields and positional children of a node pattern.
+type NodeItems<K> = (Vec<FieldItem<K>>, Vec<ChildItem<K>>);
+
+struct QueryParser<'a, L: QueryLanguage> {
+    source: &'a str,
+    offset: usize,
+    capture_names: Vec<String>,
+    /// The captures defined by the current top-level pattern.
+    pattern_captures: Vec<usize>,
+    /// The predicates of the current top-level pattern.
+    predicates: Vec<Predicate>,
+    /// The captures referenced by the predicates of the current top-level pattern.
+    referenced_captures: Vec<(usize, TextRange)>,
+    patterns: Vec<TopLevelPattern<L::Kind>>,
+}
+
+impl<'a, L: QueryLanguage> QueryParser<'a, L> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            offset: 0,
+            capture_names: Vec::new(),
+            pattern_captures: Vec::new(),
+            predicates: Vec::new(),
+            referenced_captures: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+
+    fn parse(mut self) -> ParseResult<Query<L>> {
+        loop {
+            self.skip_trivia();
+
+            if self.peek().is_none() {
+                break;
+            }
+
+            let start = self.offset;
+            let (pattern, quantifier) = self.parse_pattern()?;
+
+            if quantifier != Quantifier::One {
+                return Err(self.error(start, QueryErrorKind::InvalidQuantifier));
+            }
+
+            let pattern = match pattern.kind {
+                PatternKind::Group(mut items) => {
+                    if items.len() != 1 || items[0].quantifier != Quantifier::One {
+                        return Err(self.error(start, QueryErrorKind::SequenceAtTopLevel));
+                    }
+                    items.remove(0).pattern
+                }
+                _ => pattern,
+            };
+
+            for (index, range) in std::mem::take(&mut self.referenced_captures) {
+                if !self.pattern_captures.contains(&index) {
+                    return Err(QueryError {
+                        range,
+                        kind: QueryErrorKind::UnknownCapture(self.capture_names[index].clone()),
+                    });
+                }
+            }
+
+            self.pattern_captures.clear();
+            self.patterns.push(TopLevelPattern {
+                pattern,
+                predicates: std::mem::take(&mut self.predicates),
+            });
+        }
+
+        Ok(Query {
+            patterns: self.patterns,
+            capture_names: self.capture_names,
+        })
+    }
+
+    /// Parses a pattern with its quantifier and captures.
+    fn parse_pattern(&mut self) -> ParseResult<(Pattern<L::Kind>, Quantifier)> {
+        let start = self.offset;
+        let mut pattern = self.parse_atom()?;
+        let quantifier = self.parse_quantifier();
+        let captures = self.parse_captures()?;
+
+        if !captures.is_empty() {
+            add_captures(&mut pattern, &captures).map_err(|kind| self.error(start, kind))?;
+        }
+
+        Ok((pattern, quantifier))
+    }
+
+    fn parse_atom(&mut self) -> ParseResult<Pattern<L::Kind>> {
+        self.skip_trivia();
+        let start = self.offset;
+
+        let kind = match self.peek() {
+            None => return Err(self.error(start, QueryErrorKind::UnexpectedEnd)),
+            Some('"') => PatternKind::Text(self.parse_string()?),
+            Some('[') => {
+                self.bump();
+                let mut alternatives = Vec::new();
+
+                loop {
+                    self.skip_trivia();
+
+                    if self.eat(']') {
+                        break;
+                    }
+
+                    let (alternative, quantifier) = self.parse_pattern()?;
+                    if quantifier != Quantifier::One {
+                        return Err(self.error(start, QueryErrorKind::InvalidQuantifier));
+                    }
+                    alternatives.push(alternative);
+                }
+
+                if alternatives.is_empty() {
+                    return Err(self.error(start, QueryErrorKind::Empty));
+                }
+
+                PatternKind::Alternation(alternatives)
+            }
+            Some('(') => {
+                self.bump();
+                self.skip_trivia();
+
+                match self.peek() {
+                    Some('(' | '[' | '"') => {
+                        let (fields, children) = self.parse_children(None, false)?;
+
+                        if children.is_empty() && fields.is_empty() {
+                            return Err(self.error(start, QueryErrorKind::Empty));
+                        }
+
+                        PatternKind::Group(children)
+                    }
+                    Some('#') => return Err(self.error(start, QueryErrorKind::MisplacedPredicate)),
+                    Some(c) if is_identifier_char(c) => {
+                        let name_start = self.offset;
+                        let name = self.parse_identifier();
+
+                        let kind = if name == "_" {
+                            None
+                        } else {
+                            let kind = L::kind_by_name(name).ok_or_else(|| {
+                                self.error(
+                                    name_start,
+                                    QueryErrorKind::UnknownKind(String::from(name)),
+                                )
+                            })?;
+                            Some(kind)
+                        };
+
+                        // Captures of the node itself: `(KIND @capture ...)`
+                        let captures = self.parse_captures()?;
+                        let (fields, children) = self.parse_children(kind, true)?;
+
+                        return Ok(Pattern {
+                            kind: PatternKind::Node {
+                                kind,
+                                fields,
+                                children,
+                            },
+                            captures,
+                        });
+                    }
+                    Some(c) => {
+                        return Err(self.error(self.offset, QueryErrorKind::UnexpectedChar(c)));
+                    }
+                    None => return Err(self.error(self.offset, QueryErrorKind::UnexpectedEnd)),
+                }
+            }
+            Some(c) if is_identifier_char(c) => {
+                let name = self.parse_identifier();
+
+                if name != "_" {
+                    return Err(self.error(start, QueryErrorKind::UnexpectedChar(c)));
+                }
+
+                PatternKind::Any
+            }
+            Some(c) => return Err(self.error(start, QueryErrorKind::UnexpectedChar(c))),
+        };
+
+        Ok(Pattern {
+            kind,
+            captures: Vec::new(),
+        })
+    }
+
+    /// Parses the fields, children, and predicates up to and including the closing `)`.
+    fn parse_children(
+        &mut self,
+        kind: Option<L::Kind>,
+        allow_fields: bool,
+    ) -> ParseResult<NodeItems<L::Kind>> {
+        let mut fields = Vec::new();
+        let mut children = Vec::new();
+
+        loop {
+            self.skip_trivia();
+            let start = self.offset;
+
+            match self.peek() {
+                None => return Err(self.error(start, QueryErrorKind::UnexpectedEnd)),
+                Some(')') => {
+                    self.bump();
+                    break;
+                }
+                Some('(') if self.rest()[1..].trim_start().starts_with('#') => {
+                    self.parse_predicate()?;
+                }
+                Some('!') => {
+                    self.bump();
+                    let name = self.parse_identifier();
+                    let slot = self.resolve_field(kind, allow_fields, name, start)?;
+
+                    fields.push(FieldItem {
+                        name: String::from(name),
+                        slot,
+                        pattern: None,
+                        optional: false,
+                    });
+                }
+                Some(c) if is_identifier_char(c) && self.at_field() => {
+                    let name = self.parse_identifier();
+                    let slot = self.resolve_field(kind, allow_fields, name, start)?;
+                    let name = String::from(name);
+
+                    self.skip_trivia();
+                    self.bump(); // :
+
+                    let (pattern, quantifier) = self.parse_pattern()?;
+                    let optional = match quantifier {
+                        Quantifier::One => false,
+                        Quantifier::ZeroOrOne => true,
+                        _ => return Err(self.error(start, QueryErrorKind::InvalidQuantifier)),
+                    };
+
+                    fields.push(FieldItem {
+                        name,
+                        slot,
+                        pattern: Some(pattern),
+                        optional,
+                    });
+                }
+                Some(_) => {
+                    let (pattern, quantifier) = self.parse_pattern()?;
+                    children.push(ChildItem {
+                        pattern,
+                        quantifier,
+                    });
+                }
+            }
+        }
+
+        Ok((fields, children))
+    }
+
+    /// Returns `true` if the parser is at an identifier followed by a `:`.
+    fn at_field(&self) -> bool {
+        let rest = self.rest();
+        let name_len = rest.find(|c| !is_identifier_char(c)).unwrap_or(rest.len());
+
+        rest[name_len..].trim_start().starts_with(':')
+    }
+
+    fn resolve_field(
+        &self,
+        kind: Option<L::Kind>,
+        allow_fields: bool,
+        name: &str,
+        start: usize,
+    ) -> ParseResult<Option<usize>> {
+        if !allow_fields {
+            return Err(self.error(start, QueryErrorKind::FieldOutsideNode(String::from(name))));
+        }
+
+        match kind {
+            // Resolved against the kind of the matched node
+            None => Ok(None),
+            Some(kind) => L::slot_by_name(kind, name)
+                .map(Some)
+                .ok_or_else(|| self.error(start, QueryErrorKind::UnknownField(String::from(name)))),
+        }
+    }
+
+    /// Parses `(#name? arguments...)`.
+    fn parse_predicate(&mut self) -> ParseResult<()> {
+        let start = self.offset;
+        self.bump(); // (
+        self.skip_trivia();
+        self.bump(); // #
+
+        let name = String::from(self.parse_identifier());
+        let name = match self.peek() {
+            Some(c @ ('?' | '!')) => {
+                self.bump();
+                format!("{name}{c}")
+            }
+            _ => name,
+        };
+
+        let mut arguments = Vec::new();
+        loop {
+            self.skip_trivia();
+            let argument_start = self.offset;
+
+            match self.peek() {
+                None => return Err(self.error(argument_start, QueryErrorKind::UnexpectedEnd)),
+                Some(')') => {
+                    self.bump();
+                    break;
+                }
+                Some('@') => {
+                    self.bump();
+                    let capture = self.parse_capture_name(argument_start)?;
+                    self.referenced_captures
+                        .push((capture, self.range(argument_start)));
+                    arguments.push(PredicateValue::Capture(capture));
+                }
+                Some('"') => arguments.push(PredicateValue::Text(self.parse_string()?)),
+                Some(c) => {
+                    return Err(self.error(argument_start, QueryErrorKind::UnexpectedChar(c)));
+                }
+            }
+        }
+
+        let invalid_arguments = |parser: &Self| {
+            parser.error(
+                start,
+                QueryErrorKind::InvalidPredicateArguments(name.clone()),
+            )
+        };
+
+        let predicate = match (name.as_str(), arguments.as_slice()) {
+            ("eq?" | "not-eq?", [PredicateValue::Capture(capture), _]) => {
+                let capture = *capture;
+                let value = arguments.pop().unwrap();
+                Predicate::Eq {
+                    capture,
+                    value,
+                    negated: name.starts_with("not-"),
+                }
+            }
+            #[cfg(feature = "regex")]
+            (
+                "match?" | "not-match?",
+                [
+                    PredicateValue::Capture(capture),
+                    PredicateValue::Text(pattern),
+                ],
+            ) => {
+                let regex = Regex::new(pattern).map_err(|error| {
+                    self.error(start, QueryErrorKind::InvalidRegex(error.to_string()))
+                })?;
+
+                Predicate::Match {
+                    capture: *capture,
+                    regex,
+                    negated: name.starts_with("not-"),
+                }
+            }
+            ("eq?" | "not-eq?", _) => return Err(invalid_arguments(self)),
+            #[cfg(feature = "regex")]
+            ("match?" | "not-match?", _) => return Err(invalid_arguments(self)),
+            _ => return Err(self.error(start, QueryErrorKind::UnknownPredicate(name))),
+        };
+
+        self.predicates.push(predicate);
+        Ok(())
+    }
+
+    fn parse_quantifier(&mut self) -> Quantifier {
+        self.skip_trivia();
+
+        let quantifier = match self.peek() {
+            Some('?') => Quantifier::ZeroOrOne,
+            Some('*') => Quantifier::ZeroOrMore,
+            Some('+') => Quantifier::OneOrMore,
+            _ => return Quantifier::One,
+        };
+
+        self.bump();
+        quantifier
+    }
+
+    fn parse_captures(&mut self) -> ParseResult<Vec<usize>> {
+        let mut captures = Vec::new();
+
+        loop {
+            self.skip_trivia();
+            let start = self.offset;
+
+            if !self.eat('@') {
+                break;
+            }
+
+            let capture = self.parse_capture_name(start)?;
+            self.pattern_captures.push(capture);
+            captures.push(capture);
+        }
+
+        Ok(captures)
+    }
+
+    /// Parses the name following an `@` and returns the index of the capture.
+    fn parse_capture_name(&mut self, start: usize) -> ParseResult<usize> {
+        let name = self.parse_identifier();
+
+        if name.is_empty() {
+            return Err(match self.peek() {
+                Some(c) => self.error(start, QueryErrorKind::UnexpectedChar(c)),
+                None => self.error(start, QueryErrorKind::UnexpectedEnd),
+            });
+        }
+
+        let index = match self
+            .capture_names
+            .iter()
+            .position(|capture| capture == name)
+        {
+            Some(index) => index,
+            None => {
+                self.capture_names.push(String::from(name));
+                self.capture_names.len() - 1
+            }
+        };
+
+        Ok(index)
+    }
+
+    fn parse_identifier(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !is_identifier_char(c)).unwrap_or(rest.len());
+        self.offset += len;
+        &rest[..len]
+    }
+
+    fn parse_string(&mut self) -> ParseResult<String> {
+        let start = self.offset;
+        self.bump(); // "
+
+        let mut text = String::new();
+        loop {
+            let Some(c) = self.bump() else {
+                return Err(self.error(start, QueryErrorKind::UnterminatedString));
+            };
+
+            match c {
+                '"' => break,
+                '\\' => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some(c) => text.push(c),
+                    None => return Err(self.error(start, QueryErrorKind::UnterminatedString)),
+                },
+                c => text.push(c),
+            }
+        }
+
+        Ok(text)
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.offset += rest.len() - trimmed.len();
+
+            if !trimmed.starts_with(';') {
+                break;
+            }
+
+            self.offset += trimmed.find('\n').unwrap_or(trimmed.len());
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let matches = self.peek() == Some(c);
+        if matches {
+            self.offset += c.len_utf8();
+        }
+        matches
+    }
+
+    fn range(&self, start: usize) -> TextRange {
+        TextRange::new(
+            TextSize::from(start as u32),
+            TextSize::from(self.offset.max(start) as u32),
+        )
+    }
+
+    fn error(&self, start: usize, kind: QueryErrorKind) -> QueryError {
+        let mut range = self.range(start);
+
+        // Include the unexpected character
+        if let QueryErrorKind::UnexpectedChar(c) = kind
+            && range.is_empty()
+        {
+            range = TextRange::at(range.start(), TextSize::of(c));
+        }
+
+        QueryError { range, kind }
+    }
+}
+
+/// Adds `captures` to `pattern`. Captures of an alternation apply to each alternative.
+fn add_captures<K>(pattern: &mut Pattern<K>, captures: &[usize]) -> Result<(), QueryErrorKind> {
+    match &mut pattern.kind {
+        PatternKind::Group(_) => Err(QueryErrorKind::CaptureOnGroup),
+        PatternKind::Alternation(alternatives) => alternatives
+            .iter_mut()
+            .try_for_each(|alternative| add_captures(alternative, captures)),
+        _ => {
+            pattern.captures.extend_from_slice(captures);
+            Ok(())
+        }
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::{RawLanguage, parse};
+
+    /// Returns the captures of every match as `name=text` strings.
+    fn matches(source: &str, text: &str) -> Vec<Vec<String>> {
+        let query = Query::<RawLanguage>::new(source).unwrap();
+
+        query
+            .matches(&parse(text))
+            .map(|query_match| {
+                query_match
+                    .captures()
+                    .iter()
+                    .map(|capture| {
+                        format!(
+                            "{}={}",
+                            query.capture_names()[capture.index()],
+                            element_text(capture.element())
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn error(source: &str) -> (QueryErrorKind, std::ops::Range<u32>) {
+        let error = Query::<RawLanguage>::new(source).unwrap_err();
+        (
+            error.kind,
+            error.range.start().into()..error.range.end().into(),
+        )
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(
+            matches(
+                "(BINARY_EXPRESSION left: (NAME_EXPRESSION) @left right: (_) @right)",
+                "a + 1; 2 * b;"
+            ),
+            vec![vec!["left=a", "right=1"]]
+        );
+
+        // Fields of `_` are resolved against the kind of the matched node
+        assert_eq!(
+            matches("(_ operator_token: \"-\") @node", "a - b; -c; a + b;"),
+            vec![vec!["node=a - b"], vec!["node=-c"]]
+        );
+    }
+
+    #[test]
+    fn empty_and_optional_fields() {
+        assert_eq!(
+            matches(
+                "(EXPRESSION_STATEMENT !semicolon_token) @statement",
+                "a;\nb"
+            ),
+            vec![vec!["statement=b"]]
+        );
+
+        assert_eq!(
+            matches(
+                "(EXPRESSION_STATEMENT semicolon_token: \";\"? @semicolon) @statement",
+                "a;\nb"
+            ),
+            vec![vec!["statement=a;", "semicolon=;"], vec!["statement=b"]]
+        );
+    }
+
+    #[test]
+    fn alternation() {
+        assert_eq!(
+            matches(
+                "[(NUMBER_TOKEN) (STRING_TOKEN)] @literal",
+                "f(1, \"s\", x);"
+            ),
+            vec![vec!["literal=1"], vec!["literal=\"s\""]]
+        );
+
+        assert_eq!(
+            matches(
+                "(BINARY_EXPRESSION operator_token: [\"*\" \"/\"]) @product",
+                "a + b; a * b; a / b;"
+            ),
+            vec![vec!["product=a * b"], vec!["product=a / b"]]
+        );
+    }
+
+    #[test]
+    fn children_match_in_order_with_gaps() {
+        assert_eq!(
+            matches(
+                "(ARGUMENT_LIST (NAME_EXPRESSION) @first (LITERAL_EXPRESSION) @second)",
+                "f(a, b, 1); g(1, a);"
+            ),
+            vec![vec!["first=a", "second=1"]]
+        );
+    }
+
+    #[test]
+    fn quantifiers() {
+        let query = "(ARGUMENT_LIST (NAME_EXPRESSION)* @names)";
+        assert_eq!(
+            matches(query, "f(a, 1, b); g();"),
+            vec![vec!["names=a", "names=b"], vec![]]
+        );
+
+        let query = "(ARGUMENT_LIST (NAME_EXPRESSION)+ @names)";
+        assert_eq!(
+            matches(query, "f(a, 1, b); g(1);"),
+            vec![vec!["names=a", "names=b"]]
+        );
+
+        let query = "(ARGUMENT_LIST (LITERAL_EXPRESSION)? @literal (NAME_EXPRESSION) @name)";
+        assert_eq!(
+            matches(query, "f(1, a); g(a);"),
+            vec![vec!["literal=1", "name=a"], vec!["name=a"]]
+        );
+    }
+
+    #[test]
+    fn quantifiers_backtrack() {
+        // `*` prefers more repetitions but leaves the last name to the following sibling
+        assert_eq!(
+            matches(
+                "(ARGUMENT_LIST (NAME_EXPRESSION)* @names (NAME_EXPRESSION) @last)",
+                "f(a, b, c);"
+            ),
+            vec![vec!["names=a", "names=b", "last=c"]]
+        );
+
+        // Backtracks until the predicate holds
+        assert_eq!(
+            matches(
+                "((ARGUMENT_LIST (NAME_EXPRESSION)* @names (NAME_EXPRESSION) @last) (#eq? @last \"b\"))",
+                "f(a, b, c);"
+            ),
+            vec![vec!["names=a", "last=b"]]
+        );
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(
+            matches(
+                "(ARGUMENT_LIST ((NAME_EXPRESSION) @name \",\")+)",
+                "f(a, b, c);"
+            ),
+            vec![vec!["name=a", "name=b"]]
+        );
+    }
+
+    #[test]
+    fn eq_predicates() {
+        assert_eq!(
+            matches("((NAME_EXPRESSION) @name (#eq? @name \"b\"))", "a; b;"),
+            vec![vec!["name=b"]]
+        );
+        assert_eq!(
+            matches("((NAME_EXPRESSION) @name (#not-eq? @name \"b\"))", "a; b;"),
+            vec![vec!["name=a"]]
+        );
+        assert_eq!(
+            matches(
+                "(BINARY_EXPRESSION left: (_) @left right: (_) @right (#eq? @left @right))",
+                "a + a; a + b;"
+            ),
+            vec![vec!["left=a", "right=a"]]
+        );
+        assert_eq!(
+            matches(
+                "(BINARY_EXPRESSION left: (_) @left right: (_) @right (#not-eq? @left @right))",
+                "a + a; a + b;"
+            ),
+            vec![vec!["left=a", "right=b"]]
+        );
+    }
+
+    #[test]
+    fn predicates_on_quantified_captures() {
+        // The predicate must hold for every captured element
+        assert_eq!(
+            matches(
+                "((ARGUMENT_LIST (NAME_EXPRESSION)+ @names) (#eq? @names \"a\"))",
+                "f(a, a); g(b, b);"
+            ),
+            vec![vec!["names=a", "names=a"]]
+        );
+        assert_eq!(
+            matches(
+                "((ARGUMENT_LIST (NAME_EXPRESSION)* @names) (#not-eq? @names \"b\"))",
+                "f(a, b, c);"
+            ),
+            vec![vec!["names=a", "names=c"]]
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn match_predicates() {
+        assert_eq!(
+            matches(
+                "((NAME_EXPRESSION) @name (#match? @name \"^[a-c]+$\"))",
+                "abc; abd;"
+            ),
+            vec![vec!["name=abc"]]
+        );
+        assert_eq!(
+            matches(
+                "((NAME_EXPRESSION) @name (#not-match? @name \"^[a-c]+$\"))",
+                "abc; abd;"
+            ),
+            vec![vec!["name=abd"]]
+        );
+        assert_eq!(
+            matches(
+                "((ARGUMENT_LIST (NAME_EXPRESSION)* @names) (#match? @names \"^a\"))",
+                "f(ab, b, ac);"
+            ),
+            vec![vec!["names=ab", "names=ac"]]
+        );
+    }
+
+    #[test]
+    fn capture_order() {
+        let query = Query::<RawLanguage>::new(
+            "(BINARY_EXPRESSION @binary right: (_) @right left: (_) @left)",
+        )
+        .unwrap();
+        assert_eq!(query.capture_names(), ["binary", "right", "left"]);
+        assert_eq!(query.capture_index("left"), Some(2));
+
+        // The captures are in the order of the pattern, fields first, then children
+        assert_eq!(
+            matches(
+                "(CALL_EXPRESSION (CALL_ARGUMENTS) @arguments callee: (_) @callee) @call",
+                "f(a);"
+            ),
+            vec![vec!["call=f(a)", "callee=f", "arguments=(a)"]]
+        );
+    }
+
+    #[test]
+    fn match_order() {
+        use crate::raw_language::RawLanguageKind::*;
+
+        let query = Query::<RawLanguage>::new("(NAME_EXPRESSION) @name\n(_) @node").unwrap();
+        let root = parse("a;");
+
+        let matches: Vec<_> = query
+            .matches(&root)
+            .map(|query_match| {
+                let element = query_match.captures()[0].element();
+                (query_match.pattern_index(), element.kind())
+            })
+            .collect();
+
+        assert_eq!(
+            matches,
+            vec![
+                (1, ROOT),
+                (1, STATEMENT_LIST),
+                (1, EXPRESSION_STATEMENT),
+                (0, NAME_EXPRESSION),
+                (1, NAME_EXPRESSION),
+            ]
+        );
+        assert_eq!(query.pattern_count(), 2);
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            matches("; names\n(NAME_EXPRESSION) @name ; a comment", "a;"),
+            vec![vec!["name=a"]]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use QueryErrorKind::*;
+
+        assert_eq!(error("(NAME_EXPRESSION"), (UnexpectedEnd, 16..16));
+        assert_eq!(error("(NAME_EXPRESSION }"), (UnexpectedChar('}'), 17..18));
+        assert_eq!(error("\"abc"), (UnterminatedString, 0..4));
+        assert_eq!(error("(FOO)"), (UnknownKind("FOO".into()), 1..4));
+        assert_eq!(
+            error("(BINARY_EXPRESSION foo: (_))"),
+            (UnknownField("foo".into()), 19..22)
+        );
+        assert_eq!(
+            error("((_) left: (_))"),
+            (FieldOutsideNode("left".into()), 5..9)
+        );
+        assert_eq!(
+            error("((_) @a (#foo? @a))"),
+            (UnknownPredicate("foo?".into()), 8..18)
+        );
+        assert_eq!(
+            error("((_) @a (#eq? @a))"),
+            (InvalidPredicateArguments("eq?".into()), 8..17)
+        );
+        assert_eq!(error("(#eq? @a \"b\")"), (MisplacedPredicate, 0..1));
+        assert_eq!(
+            error("((_) @a (#eq? @b \"x\"))"),
+            (UnknownCapture("b".into()), 14..16)
+        );
+        assert_eq!(error("(_)*"), (InvalidQuantifier, 0..4));
+        assert_eq!(
+            error("(BINARY_EXPRESSION left: (_)*)"),
+            (InvalidQuantifier, 19..29)
+        );
+        assert_eq!(
+            error("(NAME_EXPRESSION ((_) (_)) @group)"),
+            (CaptureOnGroup, 17..33)
+        );
+        assert_eq!(error("[]"), (Empty, 0..2));
+        assert_eq!(error("((_) (_))"), (SequenceAtTopLevel, 0..9));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn invalid_regex() {
+        let (kind, range) = error("((_) @a (#match? @a \"(\"))");
+
+        assert!(matches!(kind, QueryErrorKind::InvalidRegex(_)));
+        assert_eq!(range, 8..24);
+    }
+
+    #[cfg(not(feature = "regex"))]
+    #[test]
+    fn match_predicates_require_the_regex_feature() {
+        assert_eq!(
+            error("((_) @a (#match? @a \"a\"))"),
+            (QueryErrorKind::UnknownPredicate("match?".into()), 8..24)
+        );
+    }
+
+    #[test]
+    fn display_error() {
+        let error = Query::<RawLanguage>::new("(FOO)").unwrap_err();
+
+        assert_eq!(error.to_string(), "unknown kind `FOO` at 1..4");
+    }
+}
@@ -0,0 +1,937 @@
+//! A tiny arithmetic expression language to exercise the crate without a real grammar.
+//!
+//! The module provides everything a language built on top of this crate needs: a [SyntaxKind]
+//! ([RawLanguageKind]), a [Language] ([RawLanguage]), a [SyntaxFactory] ([RawSyntaxFactory]),
+//! a builder to assemble trees by hand ([RawSyntaxTreeBuilder]) and a resilient [parse] function.
+//!
+//! The grammar of the language is:
+//!
+//! ```text
+//! Root                = StatementList EOF
+//! StatementList       = (ExpressionStatement | BogusStatement)*
+//! ExpressionStatement = Expression ';'?
+//! Expression          = LiteralExpression | NameExpression | BinaryExpression | UnaryExpression
+//!                     | ParenExpression | CallExpression | BogusExpression
+//! LiteralExpression   = NUMBER | STRING
+//! NameExpression      = IDENT
+//! BinaryExpression    = Expression ('+' | '-' | '*' | '/') Expression
+//! UnaryExpression     = ('+' | '-') Expression
+//! ParenExpression     = '(' Expression ')'
+//! CallExpression      = Expression CallArguments
+//! CallArguments       = '(' ArgumentList ')'
+//! ArgumentList        = (Expression (',' Expression)* ','?)?
+//! ```
+//!
+//! Strings are enclosed in `"` and may span multiple lines. Whitespace, line breaks, `//` and
+//! `/* */` comments are attached as trivia to the tokens.
+//! Characters that don't start any token are kept as skipped trivia.
+
+use std::borrow::Cow;
+
+use text_size::{TextRange, TextSize};
+
+use crate::{
+    ast::{AstNode, SyntaxKindSet},
+    green::{RawSyntaxKind, element::GreenElement, token::GreenToken, trivia::GreenTrivia},
+    query::QueryLanguage,
+    syntax::{
+        Language, SyntaxKind,
+        node::SyntaxNode,
+        trivia::{TriviaPiece, TriviaPieceKind},
+    },
+    syntax_factory::{
+        RawNodeSlots, SyntaxFactory,
+        parsed_children::ParsedChildren,
+        raw_syntax::RawSyntaxNode,
+        schema::{ErasedSlotSchema, SchemaSlot, SlotSchema},
+    },
+    syntax_kind_set,
+    utility_types::NodeOrToken,
+};
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum RawLanguageKind {
+    TOMBSTONE = 0,
+    EOF,
+
+    // Tokens
+    NUMBER_TOKEN,
+    STRING_TOKEN,
+    IDENT_TOKEN,
+    PLUS,
+    MINUS,
+    STAR,
+    SLASH,
+    L_PAREN,
+    R_PAREN,
+    COMMA,
+    SEMICOLON,
+
+    // Nodes
+    ROOT,
+    STATEMENT_LIST,
+    EXPRESSION_STATEMENT,
+    LITERAL_EXPRESSION,
+    NAME_EXPRESSION,
+    BINARY_EXPRESSION,
+    UNARY_EXPRESSION,
+    PAREN_EXPRESSION,
+    CALL_EXPRESSION,
+    CALL_ARGUMENTS,
+    ARGUMENT_LIST,
+    BOGUS,
+    BOGUS_EXPRESSION,
+    BOGUS_STATEMENT,
+
+    #[doc(hidden)]
+    __LAST,
+}
+
+impl RawLanguageKind {
+    /// Returns `true` for all kinds that are valid in an expression position.
+    pub const fn is_expression(self) -> bool {
+        matches!(
+            self,
+            Self::LITERAL_EXPRESSION
+                | Self::NAME_EXPRESSION
+                | Self::BINARY_EXPRESSION
+                | Self::UNARY_EXPRESSION
+                | Self::PAREN_EXPRESSION
+                | Self::CALL_EXPRESSION
+                | Self::BOGUS_EXPRESSION
+        )
+    }
+
+    /// Returns `true` for all kinds that are valid in a statement position.
+    pub const fn is_statement(self) -> bool {
+        matches!(self, Self::EXPRESSION_STATEMENT | Self::BOGUS_STATEMENT)
+    }
+
+    /// Returns `true` for the binary operator tokens.
+    pub const fn is_binary_operator(self) -> bool {
+        matches!(self, Self::PLUS | Self::MINUS | Self::STAR | Self::SLASH)
+    }
+
+    /// Returns `true` for the unary operator tokens.
+    pub const fn is_unary_operator(self) -> bool {
+        matches!(self, Self::PLUS | Self::MINUS)
+    }
+
+    /// Returns `true` for token kinds.
+    pub const fn is_token(self) -> bool {
+        (self as u16) >= (Self::EOF as u16) && (self as u16) < (Self::ROOT as u16)
+    }
+}
+
+impl SyntaxKind for RawLanguageKind {
+    const TOMBSTONE: Self = RawLanguageKind::TOMBSTONE;
+    const EOF: Self = RawLanguageKind::EOF;
+    const KIND_COUNT: u16 = RawLanguageKind::__LAST as u16;
+
+    fn is_bogus(&self) -> bool {
+        matches!(
+            self,
+            RawLanguageKind::BOGUS
+                | RawLanguageKind::BOGUS_EXPRESSION
+                | RawLanguageKind::BOGUS_STATEMENT
+        )
+    }
+
+    fn to_bogus(&self) -> Self {
+        if self.is_expression() {
+            RawLanguageKind::BOGUS_EXPRESSION
+        } else if self.is_statement() {
+            RawLanguageKind::BOGUS_STATEMENT
+        } else {
+            RawLanguageKind::BOGUS
+        }
+    }
+
+    fn to_raw(&self) -> RawSyntaxKind {
+        RawSyntaxKind(*self as u16)
+    }
+
+    fn from_raw(raw: RawSyntaxKind) -> Self {
+        assert!(raw.0 < RawLanguageKind::__LAST as u16);
+
+        unsafe { std::mem::transmute::<u16, RawLanguageKind>(raw.0) }
+    }
+
+    fn is_root(&self) -> bool {
+        self == &RawLanguageKind::ROOT
+    }
+
+    fn is_list(&self) -> bool {
+        matches!(
+            self,
+            RawLanguageKind::STATEMENT_LIST | RawLanguageKind::ARGUMENT_LIST
+        )
+    }
+
+    fn list_separator(&self) -> Option<Self> {
+        match self {
+            RawLanguageKind::ARGUMENT_LIST => Some(RawLanguageKind::COMMA),
+            _ => None,
+        }
+    }
+
+    fn is_trivia(self) -> bool {
+        false
+    }
+
+    fn is_string_literal(&self) -> bool {
+        self == &RawLanguageKind::STRING_TOKEN
+    }
+
+    fn to_string(&self) -> Option<&'static str> {
+        let str = match self {
+            RawLanguageKind::PLUS => "+",
+            RawLanguageKind::MINUS => "-",
+            RawLanguageKind::STAR => "*",
+            RawLanguageKind::SLASH => "/",
+            RawLanguageKind::L_PAREN => "(",
+            RawLanguageKind::R_PAREN => ")",
+            RawLanguageKind::COMMA => ",",
+            RawLanguageKind::SEMICOLON => ";",
+            RawLanguageKind::EOF => "EOF",
+            _ => return None,
+        };
+        Some(str)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawLanguage;
+
+impl Language for RawLanguage {
+    type Kind = RawLanguageKind;
+    type Root = RawLanguageRoot;
+
+    fn slot_schema() -> Option<ErasedSlotSchema<Self>> {
+        Some(ErasedSlotSchema::new())
+    }
+}
+
+impl QueryLanguage for RawLanguage {
+    fn kind_by_name(name: &str) -> Option<RawLanguageKind> {
+        (0..RawLanguageKind::__LAST as u16)
+            .map(|raw| RawLanguageKind::from_raw(RawSyntaxKind(raw)))
+            .find(|kind| format!("{kind:?}") == name)
+    }
+}
+
+impl SlotSchema for RawLanguage {
+    fn slots(kind: RawLanguageKind) -> Option<&'static [SchemaSlot<Self>]> {
+        use RawLanguageKind::*;
+
+        const EXPRESSIONS: SyntaxKindSet<RawLanguage> = syntax_kind_set![
+            LITERAL_EXPRESSION,
+            NAME_EXPRESSION,
+            BINARY_EXPRESSION,
+            UNARY_EXPRESSION,
+            PAREN_EXPRESSION,
+            CALL_EXPRESSION,
+            BOGUS_EXPRESSION,
+        ];
+
+        const fn slot(
+            name: &'static str,
+            kinds: SyntaxKindSet<RawLanguage>,
+            optional: bool,
+        ) -> SchemaSlot<RawLanguage> {
+            SchemaSlot {
+                name: Cow::Borrowed(name),
+                kinds,
+                optional,
+            }
+        }
+
+        const ROOT_SLOTS: &[SchemaSlot<RawLanguage>] = &[
+            slot("statements", syntax_kind_set![STATEMENT_LIST], false),
+            slot("eof_token", syntax_kind_set![EOF], false),
+        ];
+        const EXPRESSION_STATEMENT_SLOTS: &[SchemaSlot<RawLanguage>] = &[
+            slot("expression", EXPRESSIONS, false),
+            slot("semicolon_token", syntax_kind_set![SEMICOLON], true),
+        ];
+        const LITERAL_EXPRESSION_SLOTS: &[SchemaSlot<RawLanguage>] = &[slot(
+            "value_token",
+            syntax_kind_set![NUMBER_TOKEN, STRING_TOKEN],
+            false,
+        )];
+        const NAME_EXPRESSION_SLOTS: &[SchemaSlot<RawLanguage>] =
+            &[slot("name_token", syntax_kind_set![IDENT_TOKEN], false)];
+        const BINARY_EXPRESSION_SLOTS: &[SchemaSlot<RawLanguage>] = &[
+            slot("left", EXPRESSIONS, false),
+            slot(
+                "operator_token",
+                syntax_kind_set![PLUS, MINUS, STAR, SLASH],
+                false,
+            ),
+            slot("right", EXPRESSIONS, false),
+        ];
+        const UNARY_EXPRESSION_SLOTS: &[SchemaSlot<RawLanguage>] = &[
+            slot("operator_token", syntax_kind_set![PLUS, MINUS], false),
+            slot("argument", EXPRESSIONS, false),
+        ];
+        const PAREN_EXPRESSION_SLOTS: &[SchemaSlot<RawLanguage>] = &[
+            slot("l_paren_token", syntax_kind_set![L_PAREN], false),
+            slot("expression", EXPRESSIONS, false),
+            slot("r_paren_token", syntax_kind_set![R_PAREN], false),
+        ];
+        const CALL_EXPRESSION_SLOTS: &[SchemaSlot<RawLanguage>] = &[
+            slot("callee", EXPRESSIONS, false),
+            slot("arguments", syntax_kind_set![CALL_ARGUMENTS], false),
+        ];
+        const CALL_ARGUMENTS_SLOTS: &[SchemaSlot<RawLanguage>] = &[
+            slot("l_paren_token", syntax_kind_set![L_PAREN], false),
+            slot("args", syntax_kind_set![ARGUMENT_LIST], false),
+            slot("r_paren_token", syntax_kind_set![R_PAREN], false),
+        ];
+
+        let slots = match kind {
+            ROOT => ROOT_SLOTS,
+            EXPRESSION_STATEMENT => EXPRESSION_STATEMENT_SLOTS,
+            LITERAL_EXPRESSION => LITERAL_EXPRESSION_SLOTS,
+            NAME_EXPRESSION => NAME_EXPRESSION_SLOTS,
+            BINARY_EXPRESSION => BINARY_EXPRESSION_SLOTS,
+            UNARY_EXPRESSION => UNARY_EXPRESSION_SLOTS,
+            PAREN_EXPRESSION => PAREN_EXPRESSION_SLOTS,
+            CALL_EXPRESSION => CALL_EXPRESSION_SLOTS,
+            CALL_ARGUMENTS => CALL_ARGUMENTS_SLOTS,
+            _ => return None,
+        };
+
+        Some(slots)
+    }
+}
+
+/// The root node of a [RawLanguage] tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawLanguageRoot {
+    node: SyntaxNode<RawLanguage>,
+}
+
+impl RawLanguageRoot {
+    /// Wraps `node` if it's a [RawLanguageKind::ROOT] node.
+    pub fn cast(node: SyntaxNode<RawLanguage>) -> Option<Self> {
+        Self::can_cast(node.kind()).then_some(Self { node })
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode<RawLanguage> {
+        &self.node
+    }
+
+    pub fn into_syntax(self) -> SyntaxNode<RawLanguage> {
+        self.node
+    }
+}
+
+impl AstNode for RawLanguageRoot {
+    type Language = RawLanguage;
+
+    const KIND_SET: SyntaxKindSet<RawLanguage> =
+        SyntaxKindSet::from_raw(RawSyntaxKind(RawLanguageKind::ROOT as u16));
+
+    fn can_cast(kind: RawLanguageKind) -> bool {
+        kind == RawLanguageKind::ROOT
+    }
+}
+
+/// [SyntaxFactory] for the [RawLanguage] that fills in empty slots for missing children.
+#[derive(Debug)]
+pub struct RawSyntaxFactory;
+
+impl SyntaxFactory for RawSyntaxFactory {
+    type Kind = RawLanguageKind;
+
+    fn make_syntax(
+        kind: Self::Kind,
+        children: ParsedChildren<Self::Kind>,
+    ) -> RawSyntaxNode<Self::Kind> {
+        use RawLanguageKind::*;
+
+        match kind {
+            ROOT => make_node(
+                kind,
+                children,
+                [|kind| kind == STATEMENT_LIST, |kind| kind == EOF],
+            ),
+            STATEMENT_LIST => {
+                Self::make_node_list_syntax(kind, children, RawLanguageKind::is_statement)
+            }
+            EXPRESSION_STATEMENT => make_node(
+                kind,
+                children,
+                [RawLanguageKind::is_expression, |kind| kind == SEMICOLON],
+            ),
+            LITERAL_EXPRESSION => make_node(
+                kind,
+                children,
+                [|kind| matches!(kind, NUMBER_TOKEN | STRING_TOKEN)],
+            ),
+            NAME_EXPRESSION => make_node(kind, children, [|kind| kind == IDENT_TOKEN]),
+            BINARY_EXPRESSION => make_node(
+                kind,
+                children,
+                [
+                    RawLanguageKind::is_expression,
+                    RawLanguageKind::is_binary_operator,
+                    RawLanguageKind::is_expression,
+                ],
+            ),
+            UNARY_EXPRESSION => make_node(
+                kind,
+                children,
+                [
+                    RawLanguageKind::is_unary_operator,
+                    RawLanguageKind::is_expression,
+                ],
+            ),
+            PAREN_EXPRESSION => make_node(
+                kind,
+                children,
+                [
+                    |kind| kind == L_PAREN,
+                    RawLanguageKind::is_expression,
+                    |kind| kind == R_PAREN,
+                ],
+            ),
+            CALL_EXPRESSION => make_node(
+                kind,
+                children,
+                [RawLanguageKind::is_expression, |kind| {
+                    kind == CALL_ARGUMENTS
+                }],
+            ),
+            CALL_ARGUMENTS => make_node(
+                kind,
+                children,
+                [
+                    |kind| kind == L_PAREN,
+                    |kind| kind == ARGUMENT_LIST,
+                    |kind| kind == R_PAREN,
+                ],
+            ),
+            ARGUMENT_LIST => Self::make_separated_list_syntax(
+                kind,
+                children,
+                RawLanguageKind::is_expression,
+                COMMA,
+                true,
+            ),
+            _ => RawSyntaxNode::new(kind, children.into_iter().map(Some)),
+        }
+    }
+}
+
+/// Creates a node with `COUNT` slots where each child is matched against the predicate of the
+/// next slot. Slots for which no matching child exists are left empty.
+///
+/// The node is converted to its bogus kind if not all children fit into a slot.
+fn make_node<const COUNT: usize>(
+    kind: RawLanguageKind,
+    children: ParsedChildren<RawLanguageKind>,
+    slot_predicates: [fn(RawLanguageKind) -> bool; COUNT],
+) -> RawSyntaxNode<RawLanguageKind> {
+    let mut elements = (&children).into_iter();
+    let mut slots: RawNodeSlots<COUNT> = RawNodeSlots::default();
+    let mut current_element = elements.next();
+
+    for predicate in slot_predicates {
+        if let Some(element) = &current_element
+            && predicate(element.kind())
+        {
+            slots.mark_present();
+            current_element = elements.next();
+        }
+
+        slots.next_slot();
+    }
+
+    if current_element.is_some() {
+        return RawSyntaxNode::new(kind.to_bogus(), children.into_iter().map(Some));
+    }
+
+    slots.into_node(kind, children)
+}
+
+/// A position in the children of the [RawSyntaxTreeBuilder] that allows wrapping
+/// all elements added after the checkpoint in a new node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// Builds a [RawLanguage] tree by starting and finishing nodes and adding tokens in source order.
+///
+/// The nodes are created using the [RawSyntaxFactory] which fills in empty slots for missing children.
+///
+/// ```ignore
+/// let mut builder = RawSyntaxTreeBuilder::new();
+/// builder.start_node(RawLanguageKind::LITERAL_EXPRESSION);
+/// builder.token(RawLanguageKind::NUMBER_TOKEN, "42");
+/// builder.finish_node();
+///
+/// let node = builder.finish();
+/// assert_eq!(node.text_with_trivia().to_string(), "42");
+/// ```
+#[derive(Debug, Default)]
+pub struct RawSyntaxTreeBuilder {
+    /// The kinds of the started nodes and the index of their first child in `children`
+    parents: Vec<(RawLanguageKind, usize)>,
+    children: Vec<(u64, GreenElement)>,
+}
+
+impl RawSyntaxTreeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new node of `kind`. All following tokens and nodes become its children
+    /// until the matching [finish_node](RawSyntaxTreeBuilder::finish_node) call.
+    pub fn start_node(&mut self, kind: RawLanguageKind) {
+        self.parents.push((kind, self.children.len()));
+    }
+
+    /// Returns a checkpoint that can be passed to [start_node_at](RawSyntaxTreeBuilder::start_node_at)
+    /// to wrap the elements added after this call.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.children.len())
+    }
+
+    /// Starts a new node of `kind` that wraps all elements added since `checkpoint` was taken.
+    pub fn start_node_at(&mut self, checkpoint: Checkpoint, kind: RawLanguageKind) {
+        let Checkpoint(first_child) = checkpoint;
+
+        assert!(
+            first_child <= self.children.len(),
+            "checkpoint no longer valid, was finish_node called early?"
+        );
+
+        if let Some(&(_, parent_first_child)) = self.parents.last() {
+            assert!(
+                first_child >= parent_first_child,
+                "checkpoint no longer valid, was an unmatched start_node called?"
+            );
+        }
+
+        self.parents.push((kind, first_child));
+    }
+
+    /// Finishes the most recently started node.
+    pub fn finish_node(&mut self) {
+        let (kind, first_child) = self
+            .parents
+            .pop()
+            .expect("finish_node called without a matching start_node");
+
+        let children = ParsedChildren::new(&mut self.children, first_child);
+        let node = RawSyntaxFactory::make_syntax(kind, children).into_green();
+
+        self.children.push((0, NodeOrToken::Node(node)));
+    }
+
+    /// Adds a token without trivia.
+    pub fn token(&mut self, kind: RawLanguageKind, text: &str) {
+        self.token_with_trivia(kind, text, &[], &[]);
+    }
+
+    /// Adds a token with the given leading and trailing trivia.
+    ///
+    /// `text` is the token's full text, including the text of its leading and trailing trivia.
+    pub fn token_with_trivia(
+        &mut self,
+        kind: RawLanguageKind,
+        text: &str,
+        leading: &[TriviaPiece],
+        trailing: &[TriviaPiece],
+    ) {
+        let token = GreenToken::with_trivia(
+            kind.to_raw(),
+            text,
+            GreenTrivia::new(leading.iter().copied()),
+            GreenTrivia::new(trailing.iter().copied()),
+        );
+
+        self.children.push((0, NodeOrToken::Token(token)));
+    }
+
+    /// Completes the tree and returns its root node.
+    ///
+    /// ## Panics
+    /// If there are unfinished nodes or if the builder doesn't contain exactly one root node.
+    pub fn finish(mut self) -> SyntaxNode<RawLanguage> {
+        assert!(self.parents.is_empty(), "some nodes haven't been finished");
+        assert_eq!(self.children.len(), 1, "expected a single root node");
+
+        match self.children.pop() {
+            Some((_, NodeOrToken::Node(node))) => SyntaxNode::new_root(node),
+            _ => panic!("expected the root element to be a node"),
+        }
+    }
+}
+
+/// A token produced by the [lex] function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawLexedToken {
+    pub kind: RawLanguageKind,
+    /// The range of the token including its leading and trailing trivia.
+    pub range: TextRange,
+    pub leading: Vec<TriviaPiece>,
+    pub trailing: Vec<TriviaPiece>,
+}
+
+/// Splits `text` into tokens and attaches whitespace, comments and skipped characters
+/// as trivia to the tokens. The last token is always an [RawLanguageKind::EOF] token.
+///
+/// A token's trailing trivia contains all whitespace and comments up to, but not including,
+/// the next line break. Anything else becomes part of the next token's leading trivia.
+pub fn lex(text: &str) -> Vec<RawLexedToken> {
+    let mut tokens = Vec::new();
+    let mut leading = Vec::new();
+    let mut token_start = TextSize::from(0);
+    let mut offset = 0usize;
+
+    // The token that is still collecting its trailing trivia
+    let mut pending: Option<(RawLanguageKind, Vec<TriviaPiece>)> = None;
+
+    while offset < text.len() {
+        let rest = &text[offset..];
+        let (item, len) = lex_item(rest);
+        let len_size = TextSize::try_from(len).unwrap();
+
+        match item {
+            NodeOrToken::Token(kind) => {
+                if let Some((pending_kind, trailing)) = pending.take() {
+                    push_token(
+                        &mut tokens,
+                        pending_kind,
+                        token_start,
+                        offset,
+                        std::mem::take(&mut leading),
+                        trailing,
+                    );
+                    token_start = TextSize::try_from(offset).unwrap();
+                }
+
+                pending = Some((kind, Vec::new()));
+            }
+            NodeOrToken::Node(trivia_kind) => {
+                let piece = TriviaPiece::new(trivia_kind, len_size);
+
+                match &mut pending {
+                    Some((_, trailing))
+                        if !matches!(
+                            trivia_kind,
+                            TriviaPieceKind::Newline | TriviaPieceKind::Skipped
+                        ) =>
+                    {
+                        trailing.push(piece);
+                    }
+                    _ => {
+                        if let Some((pending_kind, trailing)) = pending.take() {
+                            push_token(
+                                &mut tokens,
+                                pending_kind,
+                                token_start,
+                                offset,
+                                std::mem::take(&mut leading),
+                                trailing,
+                            );
+                            token_start = TextSize::try_from(offset).unwrap();
+                        }
+
+                        leading.push(piece);
+                    }
+                }
+            }
+        }
+
+        offset += len;
+    }
+
+    if let Some((pending_kind, trailing)) = pending.take() {
+        push_token(
+            &mut tokens,
+            pending_kind,
+            token_start,
+            offset,
+            std::mem::take(&mut leading),
+            trailing,
+        );
+        token_start = TextSize::try_from(offset).unwrap();
+    }
+
+    tokens.push(RawLexedToken {
+        kind: RawLanguageKind::EOF,
+        range: TextRange::new(token_start, TextSize::try_from(text.len()).unwrap()),
+        leading,
+        trailing: Vec::new(),
+    });
+
+    tokens
+}
+
+fn push_token(
+    tokens: &mut Vec<RawLexedToken>,
+    kind: RawLanguageKind,
+    start: TextSize,
+    end: usize,
+    leading: Vec<TriviaPiece>,
+    trailing: Vec<TriviaPiece>,
+) {
+    tokens.push(RawLexedToken {
+        kind,
+        range: TextRange::new(start, TextSize::try_from(end).unwrap()),
+        leading,
+        trailing,
+    });
+}
+
+/// Lexes the next token or trivia piece at the start of `text` and returns its length in bytes.
+fn lex_item(text: &str) -> (NodeOrToken<TriviaPieceKind, RawLanguageKind>, usize) {
+    let bytes = text.as_bytes();
+    let first = text.chars().next().unwrap();
+
+    let token = |kind| (NodeOrToken::Token(kind), 1);
+    let trivia = |kind, len| (NodeOrToken::Node(kind), len);
+
+    match first {
+        '\r' if bytes.get(1) == Some(&b'\n') => trivia(TriviaPieceKind::Newline, 2),
+        '\n' | '\r' => trivia(TriviaPieceKind::Newline, 1),
+        ' ' | '\t' => {
+            let len = text
+                .find(|c: char| c != ' ' && c != '\t')
+                .unwrap_or(text.len());
+            trivia(TriviaPieceKind::Whitespace, len)
+        }
+        '/' if bytes.get(1) == Some(&b'/') => {
+            let len = text.find(['\n', '\r']).unwrap_or(text.len());
+            trivia(TriviaPieceKind::SingleLineComment, len)
+        }
+        '/' if bytes.get(1) == Some(&b'*') => {
+            let len = text[2..].find("*/").map_or(text.len(), |end| end + 4);
+            let kind = if text[..len].contains(['\n', '\r']) {
+                TriviaPieceKind::MultiLineComment
+            } else {
+                TriviaPieceKind::SingleLineComment
+            };
+            trivia(kind, len)
+        }
+        '0'..='9' => {
+            let len = text
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(text.len());
+            (NodeOrToken::Token(RawLanguageKind::NUMBER_TOKEN), len)
+        }
+        '"' => {
+            let len = text[1..].find('"').map_or(text.len(), |end| end + 2);
+            (NodeOrToken::Token(RawLanguageKind::STRING_TOKEN), len)
+        }
+        c if c.is_alphabetic() || c == '_' => {
+            let len = text
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(text.len());
+            (NodeOrToken::Token(RawLanguageKind::IDENT_TOKEN), len)
+        }
+        '+' => token(RawLanguageKind::PLUS),
+        '-' => token(RawLanguageKind::MINUS),
+        '*' => token(RawLanguageKind::STAR),
+        '/' => token(RawLanguageKind::SLASH),
+        '(' => token(RawLanguageKind::L_PAREN),
+        ')' => token(RawLanguageKind::R_PAREN),
+        ',' => token(RawLanguageKind::COMMA),
+        ';' => token(RawLanguageKind::SEMICOLON),
+        c => trivia(TriviaPieceKind::Skipped, c.len_utf8()),
+    }
+}
+
+/// Parses `text` into a [RawLanguage] tree.
+///
+/// The parser never fails. Missing children result in empty slots and unexpected tokens are
+/// wrapped in [RawLanguageKind::BOGUS_STATEMENT] nodes. The text of the returned tree is
+/// always identical to `text`.
+pub fn parse(text: &str) -> SyntaxNode<RawLanguage> {
+    let mut parser = Parser {
+        text,
+        tokens: lex(text),
+        position: 0,
+        builder: RawSyntaxTreeBuilder::new(),
+    };
+
+    parser.parse_root();
+    parser.builder.finish()
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<RawLexedToken>,
+    position: usize,
+    builder: RawSyntaxTreeBuilder,
+}
+
+impl Parser<'_> {
+    const UNARY_BINDING_POWER: u8 = 5;
+
+    fn current(&self) -> RawLanguageKind {
+        self.tokens[self.position].kind
+    }
+
+    fn at(&self, kind: RawLanguageKind) -> bool {
+        self.current() == kind
+    }
+
+    fn at_expression_start(&self) -> bool {
+        matches!(
+            self.current(),
+            RawLanguageKind::NUMBER_TOKEN
+                | RawLanguageKind::STRING_TOKEN
+                | RawLanguageKind::IDENT_TOKEN
+                | RawLanguageKind::PLUS
+                | RawLanguageKind::MINUS
+                | RawLanguageKind::L_PAREN
+        )
+    }
+
+    fn bump(&mut self) {
+        let token = &self.tokens[self.position];
+
+        self.builder.token_with_trivia(
+            token.kind,
+            &self.text[token.range],
+            &token.leading,
+            &token.trailing,
+        );
+
+        if token.kind != RawLanguageKind::EOF {
+            self.position += 1;
+        }
+    }
+
+    fn eat(&mut self, kind: RawLanguageKind) -> bool {
+        let at = self.at(kind);
+        if at {
+            self.bump();
+        }
+        at
+    }
+
+    fn parse_root(&mut self) {
+        self.builder.start_node(RawLanguageKind::ROOT);
+        self.builder.start_node(RawLanguageKind::STATEMENT_LIST);
+
+        while !self.at(RawLanguageKind::EOF) {
+            self.parse_statement();
+        }
+
+        self.builder.finish_node();
+        self.bump();
+        self.builder.finish_node();
+    }
+
+    fn parse_statement(&mut self) {
+        if self.at_expression_start() {
+            self.builder
+                .start_node(RawLanguageKind::EXPRESSION_STATEMENT);
+            self.parse_expression(0);
+            self.eat(RawLanguageKind::SEMICOLON);
+            self.builder.finish_node();
+        } else {
+            self.builder.start_node(RawLanguageKind::BOGUS_STATEMENT);
+
+            loop {
+                let current = self.current();
+                self.bump();
+
+                if current == RawLanguageKind::SEMICOLON
+                    || self.at(RawLanguageKind::EOF)
+                    || self.at_expression_start()
+                {
+                    break;
+                }
+            }
+
+            self.builder.finish_node();
+        }
+    }
+
+    /// Parses an expression whose operators bind at least as tight as `min_binding_power`.
+    /// Doesn't add any element if the current token can't start an expression.
+    fn parse_expression(&mut self, min_binding_power: u8) {
+        let checkpoint = self.builder.checkpoint();
+
+        match self.current() {
+            RawLanguageKind::NUMBER_TOKEN | RawLanguageKind::STRING_TOKEN => {
+                self.builder.start_node(RawLanguageKind::LITERAL_EXPRESSION);
+                self.bump();
+                self.builder.finish_node();
+            }
+            RawLanguageKind::IDENT_TOKEN => {
+                self.builder.start_node(RawLanguageKind::NAME_EXPRESSION);
+                self.bump();
+                self.builder.finish_node();
+            }
+            RawLanguageKind::PLUS | RawLanguageKind::MINUS => {
+                self.builder.start_node(RawLanguageKind::UNARY_EXPRESSION);
+                self.bump();
+                self.parse_expression(Self::UNARY_BINDING_POWER);
+                self.builder.finish_node();
+            }
+            RawLanguageKind::L_PAREN => {
+                self.builder.start_node(RawLanguageKind::PAREN_EXPRESSION);
+                self.bump();
+                self.parse_expression(0);
+                self.eat(RawLanguageKind::R_PAREN);
+                self.builder.finish_node();
+            }
+            _ => return,
+        }
+
+        loop {
+            let current = self.current();
+
+            if current == RawLanguageKind::L_PAREN {
+                self.builder
+                    .start_node_at(checkpoint, RawLanguageKind::CALL_EXPRESSION);
+                self.parse_call_arguments();
+                self.builder.finish_node();
+                continue;
+            }
+
+            let (left_binding_power, right_binding_power) = match current {
+                RawLanguageKind::PLUS | RawLanguageKind::MINUS => (1, 2),
+                RawLanguageKind::STAR | RawLanguageKind::SLASH => (3, 4),
+                _ => break,
+            };
+
+            if left_binding_power < min_binding_power {
+                break;
+            }
+
+            self.builder
+                .start_node_at(checkpoint, RawLanguageKind::BINARY_EXPRESSION);
+            self.bump();
+            self.parse_expression(right_binding_power);
+            self.builder.finish_node();
+        }
+    }
+
+    fn parse_call_arguments(&mut self) {
+        self.builder.start_node(RawLanguageKind::CALL_ARGUMENTS);
+        self.bump();
+        self.builder.start_node(RawLanguageKind::ARGUMENT_LIST);
+
+        loop {
+            if self.at_expression_start() {
+                self.parse_expression(0);
+            } else if !self.eat(RawLanguageKind::COMMA) {
+                break;
+            }
+        }
+
+        self.builder.finish_node();
+        self.eat(RawLanguageKind::R_PAREN);
+        self.builder.finish_node();
+    }
+}
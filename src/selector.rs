@@ -0,0 +1,574 @@
+//! Path selectors for locating nodes by the kinds of their ancestors.
+//!
+//! A [Selector] is a list of steps separated by combinators:
+//!
+//! ```text
+//! MODULE > STRUCT_DECL > FIELD_LIST > FIELD[2]
+//! ```
+//!
+//! * `KIND` matches a node of the kind named `KIND` and `*` matches any node.
+//! * `[n]` after a step requires that the node is in the slot `n` of its parent.
+//! * `:nth(n)` after a step selects the `n`-th (zero based) node that matches the step, counted per parent for `>`
+//!   and per ancestor for `>>`.
+//! * `a > b` matches the children `b` of the nodes matched by `a`, `a >> b` all descendants.
+//!
+//! The first step matches the node the selector is resolved against, or, if the selector starts with `>>`,
+//! that node and any of its descendants.
+//!
+//! [Selector::from_node] creates the canonical selector of a node, which uses the kind and slot of every
+//! ancestor and uniquely identifies the node in its tree. The names of kinds are provided by the
+//! language's [QueryLanguage] implementation.
+
+use std::fmt;
+
+use rustc_hash::FxHashSet;
+use text_size::{TextRange, TextSize};
+
+use crate::{query::QueryLanguage, syntax::node::SyntaxNode};
+
+/// An error found while parsing a [Selector].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    /// The range in the selector source.
+    pub range: TextRange,
+    pub kind: SelectorErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorErrorKind {
+    /// The selector ends where a step or a number is expected.
+    UnexpectedEnd,
+    /// A character that can't start or continue a step.
+    UnexpectedChar(char),
+    /// The language has no kind with this name.
+    UnknownKind(String),
+    /// A pseudo-class other than `:nth`.
+    UnknownPseudoClass(String),
+    /// A slot or `:nth` index that isn't a valid number.
+    InvalidIndex,
+    /// A selector without steps.
+    Empty,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            SelectorErrorKind::UnexpectedEnd => write!(f, "unexpected end of the selector"),
+            SelectorErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {c:?}"),
+            SelectorErrorKind::UnknownKind(name) => write!(f, "unknown kind `{name}`"),
+            SelectorErrorKind::UnknownPseudoClass(name) => {
+                write!(f, "unknown pseudo-class `:{name}`")
+            }
+            SelectorErrorKind::InvalidIndex => write!(f, "invalid index"),
+            SelectorErrorKind::Empty => write!(f, "empty selector"),
+        }
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    /// The node itself, used by the first step.
+    Itself,
+    Child,
+    Descendant,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step<K> {
+    combinator: Combinator,
+    /// `None` for `*`.
+    kind: Option<K>,
+    slot: Option<usize>,
+    nth: Option<usize>,
+}
+
+/// A compiled path selector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector<L: QueryLanguage> {
+    steps: Vec<Step<L::Kind>>,
+}
+
+impl<L: QueryLanguage> Selector<L> {
+    /// Parses `source`.
+    pub fn parse(source: &str) -> Result<Self, SelectorError> {
+        Parser {
+            source,
+            position: 0,
+        }
+        .parse()
+    }
+
+    /// Returns the canonical selector of `node`: the kind of its root followed by the kind and slot
+    /// of each ancestor, ending with `node`.
+    ///
+    /// Resolving the selector against the root of `node` returns `node` only.
+    pub fn from_node(node: &SyntaxNode<L>) -> Self {
+        let mut steps: Vec<_> = node
+            .ancestors()
+            .map(|ancestor| Step {
+                combinator: Combinator::Child,
+                kind: Some(ancestor.kind()),
+                slot: ancestor.parent().map(|_| ancestor.index()),
+                nth: None,
+            })
+            .collect();
+
+        steps.reverse();
+        steps[0].combinator = Combinator::Itself;
+
+        Self { steps }
+    }
+
+    /// Returns the nodes in the tree rooted at `root` that match the selector, in preorder.
+    pub fn select(&self, root: &SyntaxNode<L>) -> Vec<SyntaxNode<L>> {
+        let mut current = vec![root.clone()];
+
+        for (index, step) in self.steps.iter().enumerate() {
+            let mut seen = FxHashSet::default();
+            let mut next = Vec::new();
+
+            for context in &current {
+                let candidates: Box<dyn Iterator<Item = SyntaxNode<L>>> = match step.combinator {
+                    Combinator::Itself => Box::new(std::iter::once(context.clone())),
+                    Combinator::Child => Box::new(context.children()),
+                    Combinator::Descendant if index == 0 => Box::new(context.descendants()),
+                    Combinator::Descendant => Box::new(context.descendants().skip(1)),
+                };
+
+                let mut matching = candidates.filter(|node| step.matches(node));
+                let selected: Box<dyn Iterator<Item = SyntaxNode<L>>> = match step.nth {
+                    Some(nth) => Box::new(matching.nth(nth).into_iter()),
+                    None => Box::new(matching),
+                };
+
+                for node in selected {
+                    if seen.insert(node.clone()) {
+                        next.push(node);
+                    }
+                }
+            }
+
+            // The nodes selected for different contexts interleave if one context contains another
+            if current.len() > 1 {
+                next.sort_by_cached_key(slot_path);
+            }
+
+            if next.is_empty() {
+                return next;
+            }
+
+            current = next;
+        }
+
+        current
+    }
+
+    /// Returns the first node in the tree rooted at `root` that matches the selector.
+    pub fn select_first(&self, root: &SyntaxNode<L>) -> Option<SyntaxNode<L>> {
+        self.select(root).into_iter().next()
+    }
+}
+
+impl<L: QueryLanguage> fmt::Display for Selector<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, step) in self.steps.iter().enumerate() {
+            match step.combinator {
+                Combinator::Itself => {}
+                Combinator::Child => f.write_str(" > ")?,
+                Combinator::Descendant if index == 0 => f.write_str(">> ")?,
+                Combinator::Descendant => f.write_str(" >> ")?,
+            }
+
+            match step.kind {
+                Some(kind) => f.write_str(&L::kind_name(kind))?,
+                None => f.write_str("*")?,
+            }
+
+            if let Some(slot) = step.slot {
+                write!(f, "[{slot}]")?;
+            }
+
+            if let Some(nth) = step.nth {
+                write!(f, ":nth({nth})")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<K: Copy + PartialEq> Step<K> {
+    fn matches<L: QueryLanguage<Kind = K>>(&self, node: &SyntaxNode<L>) -> bool {
+        if self.kind.is_some_and(|kind| kind != node.kind()) {
+            return false;
+        }
+
+        match self.slot {
+            Some(slot) => node.parent().is_some() && node.index() == slot,
+            None => true,
+        }
+    }
+}
+
+/// Returns the slot indices from the root to `node`, which orders nodes in preorder.
+fn slot_path<L: QueryLanguage>(node: &SyntaxNode<L>) -> Vec<usize> {
+    let mut path: Vec<_> = node
+        .ancestors()
+        .take_while(|ancestor| ancestor.parent().is_some())
+        .map(|ancestor| ancestor.index())
+        .collect();
+    path.reverse();
+    path
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse<L: QueryLanguage>(mut self) -> Result<Selector<L>, SelectorError> {
+        let mut steps = Vec::new();
+
+        self.skip_whitespace();
+        let mut combinator = if self.eat(">>") {
+            Combinator::Descendant
+        } else {
+            Combinator::Itself
+        };
+
+        loop {
+            self.skip_whitespace();
+
+            if self.peek().is_none() {
+                if steps.is_empty() && combinator == Combinator::Itself {
+                    return Err(
+                        self.error(TextRange::empty(self.offset()), SelectorErrorKind::Empty)
+                    );
+                }
+                return Err(self.error(
+                    TextRange::empty(self.offset()),
+                    SelectorErrorKind::UnexpectedEnd,
+                ));
+            }
+
+            steps.push(self.step::<L>(combinator)?);
+            self.skip_whitespace();
+
+            combinator = if self.eat(">>") {
+                Combinator::Descendant
+            } else if self.eat(">") {
+                Combinator::Child
+            } else {
+                match self.peek() {
+                    None => break,
+                    Some(c) => return Err(self.unexpected_char(c)),
+                }
+            };
+        }
+
+        Ok(Selector { steps })
+    }
+
+    fn step<L: QueryLanguage>(
+        &mut self,
+        combinator: Combinator,
+    ) -> Result<Step<L::Kind>, SelectorError> {
+        let kind = if self.eat("*") {
+            None
+        } else {
+            let start = self.offset();
+            let name = self.identifier()?;
+            let kind = L::kind_by_name(name).ok_or_else(|| {
+                self.error(
+                    TextRange::new(start, self.offset()),
+                    SelectorErrorKind::UnknownKind(name.to_string()),
+                )
+            })?;
+            Some(kind)
+        };
+
+        let slot = if self.eat("[") {
+            let slot = self.number()?;
+            self.expect(']')?;
+            Some(slot)
+        } else {
+            None
+        };
+
+        let nth = if self.eat(":") {
+            let start = self.offset();
+            let name = self.identifier()?;
+
+            if name != "nth" {
+                return Err(self.error(
+                    TextRange::new(start, self.offset()),
+                    SelectorErrorKind::UnknownPseudoClass(name.to_string()),
+                ));
+            }
+
+            self.expect('(')?;
+            self.skip_whitespace();
+            let nth = self.number()?;
+            self.skip_whitespace();
+            self.expect(')')?;
+            Some(nth)
+        } else {
+            None
+        };
+
+        Ok(Step {
+            combinator,
+            kind,
+            slot,
+            nth,
+        })
+    }
+
+    fn identifier(&mut self) -> Result<&'a str, SelectorError> {
+        let rest = &self.source[self.position..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(match self.peek() {
+                Some(c) => self.unexpected_char(c),
+                None => self.error(
+                    TextRange::empty(self.offset()),
+                    SelectorErrorKind::UnexpectedEnd,
+                ),
+            });
+        }
+
+        self.position += len;
+        Ok(&rest[..len])
+    }
+
+    fn number(&mut self) -> Result<usize, SelectorError> {
+        let rest = &self.source[self.position..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let start = self.offset();
+
+        if len == 0 {
+            return Err(match self.peek() {
+                Some(c) => self.unexpected_char(c),
+                None => self.error(TextRange::empty(start), SelectorErrorKind::UnexpectedEnd),
+            });
+        }
+
+        self.position += len;
+        rest[..len].parse().map_err(|_| {
+            self.error(
+                TextRange::new(start, self.offset()),
+                SelectorErrorKind::InvalidIndex,
+            )
+        })
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SelectorError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += c.len_utf8();
+                Ok(())
+            }
+            Some(c) => Err(self.unexpected_char(c)),
+            None => Err(self.error(
+                TextRange::empty(self.offset()),
+                SelectorErrorKind::UnexpectedEnd,
+            )),
+        }
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.source[self.position..].starts_with(text) {
+            self.position += text.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn offset(&self) -> TextSize {
+        TextSize::try_from(self.position).expect("selector to be smaller than 4GB")
+    }
+
+    fn unexpected_char(&self, c: char) -> SelectorError {
+        self.error(
+            TextRange::at(self.offset(), TextSize::of(c)),
+            SelectorErrorKind::UnexpectedChar(c),
+        )
+    }
+
+    fn error(&self, range: TextRange, kind: SelectorErrorKind) -> SelectorError {
+        SelectorError { range, kind }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::{RawLanguage, parse};
+
+    fn select(selector: &str, text: &str) -> Vec<String> {
+        let selector = Selector::<RawLanguage>::parse(selector).unwrap();
+
+        selector
+            .select(&parse(text))
+            .into_iter()
+            .map(|node| node.text_trimmed().to_string())
+            .collect()
+    }
+
+    fn error(source: &str) -> (SelectorErrorKind, std::ops::Range<u32>) {
+        let error = Selector::<RawLanguage>::parse(source).unwrap_err();
+        (
+            error.kind,
+            error.range.start().into()..error.range.end().into(),
+        )
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for source in [
+            "ROOT",
+            "ROOT > STATEMENT_LIST[0] > EXPRESSION_STATEMENT:nth(1)",
+            ">> *",
+            ">> CALL_EXPRESSION >> NAME_EXPRESSION[0]:nth(2)",
+        ] {
+            let selector = Selector::<RawLanguage>::parse(source).unwrap();
+            assert_eq!(selector.to_string(), source);
+        }
+
+        let selector =
+            Selector::<RawLanguage>::parse("  ROOT>*[1] >>NAME_EXPRESSION:nth( 0 ) ").unwrap();
+        assert_eq!(
+            selector.to_string(),
+            "ROOT > *[1] >> NAME_EXPRESSION:nth(0)"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use SelectorErrorKind::*;
+
+        assert_eq!(error(""), (Empty, 0..0));
+        assert_eq!(error("  "), (Empty, 2..2));
+        assert_eq!(error(">>"), (UnexpectedEnd, 2..2));
+        assert_eq!(error("ROOT >"), (UnexpectedEnd, 6..6));
+        assert_eq!(error("ROOT ROOT"), (UnexpectedChar('R'), 5..6));
+        assert_eq!(error("ROOT > ?"), (UnexpectedChar('?'), 7..8));
+        assert_eq!(error("FOO > ROOT"), (UnknownKind("FOO".into()), 0..3));
+        assert_eq!(error("ROOT[a]"), (UnexpectedChar('a'), 5..6));
+        assert_eq!(error("ROOT[1"), (UnexpectedEnd, 6..6));
+        assert_eq!(
+            error("ROOT[99999999999999999999999]"),
+            (InvalidIndex, 5..28)
+        );
+        assert_eq!(
+            error("ROOT:first"),
+            (UnknownPseudoClass("first".into()), 5..10)
+        );
+        assert_eq!(error("ROOT:nth(1"), (UnexpectedEnd, 10..10));
+    }
+
+    #[test]
+    fn select_children_and_descendants() {
+        let text = "a + b;\nf(c);";
+
+        assert_eq!(select("ROOT", text), ["a + b;\nf(c);"]);
+        assert_eq!(select("EXPRESSION_STATEMENT", text), Vec::<String>::new());
+        assert_eq!(
+            select("ROOT > STATEMENT_LIST > * > *", text),
+            ["a + b", "f(c)"]
+        );
+        assert_eq!(select(">> NAME_EXPRESSION", text), ["a", "b", "f", "c"]);
+        assert_eq!(
+            select("ROOT >> CALL_EXPRESSION >> NAME_EXPRESSION", text),
+            ["f", "c"]
+        );
+        assert_eq!(select(">> ROOT", text), ["a + b;\nf(c);"]);
+    }
+
+    #[test]
+    fn select_slot() {
+        let text = "a + b;\nc * d;\n-e;";
+
+        assert_eq!(select(">> BINARY_EXPRESSION > *[2]", text), ["b", "d"]);
+        assert_eq!(select(">> UNARY_EXPRESSION > *[1]", text), ["e"]);
+        assert_eq!(select(">> STATEMENT_LIST > *[1]", text), ["c * d;"]);
+        // The root isn't in a slot
+        assert_eq!(select("ROOT[0]", text), Vec::<String>::new());
+    }
+
+    #[test]
+    fn select_nth() {
+        let text = "f(a, b); g(c, d, e);";
+
+        // Counted per parent for `>`
+        assert_eq!(select(">> ARGUMENT_LIST > *:nth(1)", text), ["b", "d"]);
+        assert_eq!(select(">> ARGUMENT_LIST > *:nth(2)", text), ["e"]);
+        // Counted per ancestor for `>>`
+        assert_eq!(
+            select(">> EXPRESSION_STATEMENT >> NAME_EXPRESSION:nth(0)", text),
+            ["f", "g"]
+        );
+        assert_eq!(select(">> NAME_EXPRESSION:nth(3)", text), ["g"]);
+    }
+
+    #[test]
+    fn select_with_nested_contexts_in_preorder() {
+        // The outer binary expression's children are selected before the inner one's,
+        // but `c` follows the inner expression's operands
+        assert_eq!(
+            select(">> BINARY_EXPRESSION > *", "(a + b) * c;"),
+            ["(a + b)", "a", "b", "c"]
+        );
+
+        assert_eq!(
+            select(">> PAREN_EXPRESSION > *", "((a) + (b));"),
+            ["(a) + (b)", "a", "b"]
+        );
+
+        // Nodes selected by multiple contexts are only returned once
+        assert_eq!(
+            select(">> PAREN_EXPRESSION >> NAME_EXPRESSION", "((a) + b);"),
+            ["a", "b"]
+        );
+    }
+
+    #[test]
+    fn from_node_round_trip() {
+        let root = parse("a + b;\nf(c, (d));\n-e;");
+
+        for node in root.descendants() {
+            let selector = Selector::from_node(&node);
+
+            assert_eq!(selector.select(&root), vec![node.clone()]);
+            assert_eq!(selector.select_first(&root), Some(node.clone()));
+            assert_eq!(
+                Selector::<RawLanguage>::parse(&selector.to_string()),
+                Ok(selector)
+            );
+        }
+
+        let binary = root.descendants().nth(3).unwrap();
+        assert_eq!(
+            Selector::from_node(&binary).to_string(),
+            "ROOT > STATEMENT_LIST[0] > EXPRESSION_STATEMENT[0] > BINARY_EXPRESSION[0]"
+        );
+    }
+}
@@ -0,0 +1,703 @@
+//! Structural search and replace.
+//!
+//! An [SsrPattern] is a snippet of code in the target language where `$name` placeholders stand
+//! for any node or token, for example, `foo($a, $b)`. The snippet is parsed by a parse function
+//! of the language and matches all subtrees with the same structure and token texts,
+//! ignoring their trivia. An [SsrRule] additionally has a replacement template, for example,
+//! `bar($b, $a)`, that's instantiated with the elements matched by the placeholders.
+//!
+//! Placeholders are replaced by the identifier `__ssr_<name>` before parsing, the language must
+//! accept an identifier wherever a placeholder is used. A placeholder matches the outermost
+//! node that only consists of the placeholder token, unless that node is a list.
+//!
+//! The replacement must have the same root kind as the pattern so that it fits wherever the pattern matched,
+//! unless the replacement only consists of a placeholder.
+//!
+//! [SsrRule::apply] keeps the trivia surrounding each match and the comments of the elements
+//! matched by placeholders that aren't part of the surrounding trivia. The trivia of the pattern's other tokens is replaced by the trivia of
+//! the replacement template.
+
+use std::fmt;
+
+use rustc_hash::FxHashMap;
+use text_size::TextSize;
+
+use crate::{
+    syntax::{
+        Language, SyntaxKind,
+        element::SyntaxElement,
+        node::SyntaxNode,
+        rewriter::{SyntaxRewriter, VisitNodeSignal},
+        structural::TriviaMode,
+    },
+    utility_types::{NodeOrToken, WalkEvent},
+};
+
+const PLACEHOLDER_PREFIX: &str = "__ssr_";
+
+/// An error found while creating an [SsrPattern] or [SsrRule].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SsrError {
+    /// A `$` that isn't followed by a placeholder name.
+    InvalidPlaceholder { offset: TextSize },
+    /// The pattern has syntax errors: it contains bogus nodes or skipped trivia.
+    PatternParseError,
+    /// The replacement has syntax errors: it contains bogus nodes or skipped trivia.
+    ReplacementParseError,
+    /// The pattern only consists of a placeholder and would match every node.
+    PlaceholderOnlyPattern,
+    /// The replacement uses a placeholder that isn't defined by the pattern.
+    UnknownPlaceholder(String),
+    /// The replacement's root kind differs from the pattern's root kind. Contains the `Debug`
+    /// representation of both kinds.
+    ReplacementKindMismatch {
+        pattern: String,
+        replacement: String,
+    },
+}
+
+impl fmt::Display for SsrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SsrError::InvalidPlaceholder { offset } => {
+                write!(f, "expected a placeholder name after `$` at {offset:?}")
+            }
+            SsrError::PatternParseError => write!(f, "the pattern contains syntax errors"),
+            SsrError::ReplacementParseError => write!(f, "the replacement contains syntax errors"),
+            SsrError::PlaceholderOnlyPattern => {
+                write!(f, "the pattern must contain more than a placeholder")
+            }
+            SsrError::UnknownPlaceholder(name) => {
+                write!(f, "the placeholder `${name}` isn't defined by the pattern")
+            }
+            SsrError::ReplacementKindMismatch {
+                pattern,
+                replacement,
+            } => write!(
+                f,
+                "the replacement is a `{replacement}` but the pattern matches `{pattern}`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SsrError {}
+
+/// A search pattern with placeholders. See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct SsrPattern<L: Language> {
+    template: SyntaxNode<L>,
+    placeholders: Vec<String>,
+}
+
+impl<L: Language> SsrPattern<L> {
+    /// Parses `pattern` with `parse`.
+    pub fn new<F>(pattern: &str, mut parse: F) -> Result<Self, SsrError>
+    where
+        F: FnMut(&str) -> SyntaxNode<L>,
+    {
+        let (template, placeholders) =
+            parse_template(pattern, &mut parse, SsrError::PatternParseError)?;
+
+        let pattern = Self {
+            template,
+            placeholders,
+        };
+
+        if pattern
+            .placeholder_name(&pattern.template.clone().into())
+            .is_some()
+        {
+            return Err(SsrError::PlaceholderOnlyPattern);
+        }
+
+        Ok(pattern)
+    }
+
+    /// Returns the names of the pattern's placeholders, without the `$`.
+    pub fn placeholders(&self) -> &[String] {
+        &self.placeholders
+    }
+
+    /// Returns all matches in the subtree rooted at `root` in source order.
+    ///
+    /// Matches don't overlap, the subtree of a matched node isn't searched for further matches.
+    pub fn find(&self, root: &SyntaxNode<L>) -> Vec<SsrMatch<L>> {
+        let mut matches = Vec::new();
+        let mut preorder = root.preorder();
+
+        while let Some(event) = preorder.next() {
+            if let WalkEvent::Enter(node) = event
+                && let Some(ssr_match) = self.match_node(&node)
+            {
+                matches.push(ssr_match);
+                preorder.skip_subtree();
+            }
+        }
+
+        matches
+    }
+
+    /// Returns the match if `node` matches the pattern.
+    pub fn match_node(&self, node: &SyntaxNode<L>) -> Option<SsrMatch<L>> {
+        let mut placeholders = FxHashMap::default();
+
+        self.match_element(
+            &self.template.clone().into(),
+            &node.clone().into(),
+            &mut placeholders,
+        )
+        .then(|| SsrMatch {
+            node: node.clone(),
+            placeholders,
+        })
+    }
+
+    fn match_element(
+        &self,
+        pattern: &SyntaxElement<L>,
+        code: &SyntaxElement<L>,
+        placeholders: &mut FxHashMap<String, SyntaxElement<L>>,
+    ) -> bool {
+        if let Some(name) = self.placeholder_name(pattern) {
+            return match placeholders.get(name) {
+                // A placeholder used multiple times must match the same code every time
+                Some(bound) => elements_eq(bound, code),
+                None => {
+                    placeholders.insert(String::from(name), code.clone());
+                    true
+                }
+            };
+        }
+
+        match (pattern, code) {
+            (NodeOrToken::Token(pattern), NodeOrToken::Token(code)) => {
+                pattern.kind() == code.kind() && pattern.text_trimmed() == code.text_trimmed()
+            }
+            (NodeOrToken::Node(pattern), NodeOrToken::Node(code)) => {
+                pattern.kind() == code.kind()
+                    && pattern.slots().len() == code.slots().len()
+                    && pattern.slots().zip(code.slots()).all(|(pattern, code)| {
+                        match (pattern.into_syntax_element(), code.into_syntax_element()) {
+                            (None, None) => true,
+                            (Some(pattern), Some(code)) => {
+                                self.match_element(&pattern, &code, placeholders)
+                            }
+                            _ => false,
+                        }
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the name of the placeholder if `element` is a placeholder token or the outermost
+    /// non-list node that only consists of a placeholder token.
+    fn placeholder_name(&self, element: &SyntaxElement<L>) -> Option<&str> {
+        let token = match element {
+            NodeOrToken::Token(token) => token.clone(),
+            NodeOrToken::Node(node) => {
+                if node.kind().is_list() {
+                    return None;
+                }
+
+                let token = node.first_token()?;
+                if token.text_trimmed_range() != node.text_trimmed_range() {
+                    return None;
+                }
+                token
+            }
+        };
+
+        let name = token.text_trimmed().strip_prefix(PLACEHOLDER_PREFIX)?;
+
+        self.placeholders
+            .iter()
+            .find(|placeholder| *placeholder == name)
+            .map(String::as_str)
+    }
+}
+
+/// A match of an [SsrPattern].
+#[derive(Debug, Clone)]
+pub struct SsrMatch<L: Language> {
+    node: SyntaxNode<L>,
+    placeholders: FxHashMap<String, SyntaxElement<L>>,
+}
+
+impl<L: Language> SsrMatch<L> {
+    /// Returns the matched node.
+    pub fn node(&self) -> &SyntaxNode<L> {
+        &self.node
+    }
+
+    /// Returns the element matched by the placeholder `name` (without the `$`).
+    pub fn placeholder(&self, name: &str) -> Option<&SyntaxElement<L>> {
+        self.placeholders.get(name)
+    }
+
+    /// Returns the names of the placeholders and the elements they matched.
+    pub fn placeholders(&self) -> impl Iterator<Item = (&str, &SyntaxElement<L>)> {
+        self.placeholders
+            .iter()
+            .map(|(name, element)| (name.as_str(), element))
+    }
+}
+
+/// A search pattern with a replacement template. See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct SsrRule<L: Language> {
+    pattern: SsrPattern<L>,
+    replacement: SyntaxNode<L>,
+}
+
+impl<L: Language> SsrRule<L> {
+    /// Parses `pattern` and `replacement` with `parse`.
+    ///
+    /// Returns [SsrError::ReplacementKindMismatch] if `replacement` has a different root kind than `pattern`
+    /// and isn't a single placeholder.
+    pub fn new<F>(pattern: &str, replacement: &str, mut parse: F) -> Result<Self, SsrError>
+    where
+        F: FnMut(&str) -> SyntaxNode<L>,
+    {
+        let pattern = SsrPattern::new(pattern, &mut parse)?;
+        let (replacement, placeholders) =
+            parse_template(replacement, &mut parse, SsrError::ReplacementParseError)?;
+
+        if let Some(unknown) = placeholders
+            .into_iter()
+            .find(|name| !pattern.placeholders.contains(name))
+        {
+            return Err(SsrError::UnknownPlaceholder(unknown));
+        }
+
+        if pattern
+            .placeholder_name(&replacement.clone().into())
+            .is_none()
+            && replacement.kind() != pattern.template.kind()
+        {
+            return Err(SsrError::ReplacementKindMismatch {
+                pattern: format!("{:?}", pattern.template.kind()),
+                replacement: format!("{:?}", replacement.kind()),
+            });
+        }
+
+        Ok(Self {
+            pattern,
+            replacement,
+        })
+    }
+
+    pub fn pattern(&self) -> &SsrPattern<L> {
+        &self.pattern
+    }
+
+    /// Returns the replacement for `ssr_match`, keeping the trivia of the matched node.
+    ///
+    /// The placeholders of the replacement template are replaced by the elements they matched.
+    /// Each inserted element keeps its inner trivia and takes the leading and trailing trivia of the
+    /// placeholder in the template.
+    pub fn instantiate(&self, ssr_match: &SsrMatch<L>) -> SyntaxNode<L> {
+        let replacement =
+            match self.instantiate_element(&self.replacement.clone().into(), ssr_match) {
+                NodeOrToken::Node(node) => node,
+                // A placeholder that matched a token
+                NodeOrToken::Token(_) => self.instantiate_node(&self.replacement, ssr_match),
+            };
+
+        with_trivia_of(
+            replacement.into(),
+            &ssr_match.node.clone().into(),
+            OwnComments {
+                leading: true,
+                trailing: true,
+            },
+        )
+        .into_node()
+        .expect("replacing trivia keeps the element a node")
+    }
+
+    /// Replaces all matches of the pattern in `root` using the rewriter.
+    ///
+    /// Matches don't overlap, placeholders in a match aren't searched for further matches.
+    /// Returns `root` if the pattern doesn't match.
+    pub fn apply(&self, root: SyntaxNode<L>) -> SyntaxNode<L> {
+        SsrRewriter { rule: self }.transform(root)
+    }
+
+    fn instantiate_element(
+        &self,
+        template: &SyntaxElement<L>,
+        ssr_match: &SsrMatch<L>,
+    ) -> SyntaxElement<L> {
+        if let Some(name) = self.pattern.placeholder_name(template)
+            && let Some(element) = ssr_match.placeholder(name)
+        {
+            // The trivia at the start and end of the matched node is kept by `instantiate`
+            let node_range = ssr_match.node.text_range_with_trivia();
+            let element_range = element.text_range();
+            let own_comments = OwnComments {
+                leading: element_range.start() != node_range.start(),
+                trailing: element_range.end() != node_range.end(),
+            };
+
+            return with_trivia_of(element.clone(), template, own_comments);
+        }
+
+        match template {
+            NodeOrToken::Node(node) => self.instantiate_node(node, ssr_match).into(),
+            NodeOrToken::Token(token) => token.clone().into(),
+        }
+    }
+
+    fn instantiate_node(&self, template: &SyntaxNode<L>, ssr_match: &SsrMatch<L>) -> SyntaxNode<L> {
+        let slots: Vec<_> = template
+            .slots()
+            .map(|slot| {
+                slot.into_syntax_element()
+                    .map(|element| self.instantiate_element(&element, ssr_match))
+            })
+            .collect();
+
+        SyntaxNode::new_detached(template.kind(), slots)
+    }
+}
+
+struct SsrRewriter<'a, L: Language> {
+    rule: &'a SsrRule<L>,
+}
+
+impl<L: Language> SyntaxRewriter for SsrRewriter<'_, L> {
+    type Language = L;
+
+    fn visit_node(&mut self, node: SyntaxNode<L>) -> VisitNodeSignal<L> {
+        match self.rule.pattern.match_node(&node) {
+            Some(ssr_match) => VisitNodeSignal::Replace(self.rule.instantiate(&ssr_match)),
+            None => VisitNodeSignal::Traverse(node),
+        }
+    }
+}
+
+/// Replaces the placeholders in `source`, parses it, and returns the node covering its text
+/// with the names of the placeholders.
+///
+/// Returns `parse_error` if the parsed tree has syntax errors.
+fn parse_template<L, F>(
+    source: &str,
+    parse: &mut F,
+    parse_error: SsrError,
+) -> Result<(SyntaxNode<L>, Vec<String>), SsrError>
+where
+    L: Language,
+    F: FnMut(&str) -> SyntaxNode<L>,
+{
+    let mut text = String::new();
+    let mut placeholders = Vec::new();
+    let mut rest = source;
+
+    while let Some(dollar) = rest.find('$') {
+        text.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+
+        let name_len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+
+        if name_len == 0 {
+            let offset = source.len() - rest.len() - 1;
+            return Err(SsrError::InvalidPlaceholder {
+                offset: TextSize::from(offset as u32),
+            });
+        }
+
+        let name = &rest[..name_len];
+        text.push_str(PLACEHOLDER_PREFIX);
+        text.push_str(name);
+
+        if !placeholders.iter().any(|placeholder| placeholder == name) {
+            placeholders.push(String::from(name));
+        }
+
+        rest = &rest[name_len..];
+    }
+    text.push_str(rest);
+
+    let root = parse(&text);
+
+    let has_errors = root.descendants().any(|node| node.kind().is_bogus())
+        || root.skipped_trivia().next().is_some();
+
+    if has_errors {
+        return Err(parse_error);
+    }
+
+    // The innermost node covering the template's text
+    let range = root.text_trimmed_range();
+    let mut node = root;
+    while let Some(child) = node
+        .children()
+        .find(|child| child.text_trimmed_range() == range)
+    {
+        node = child;
+    }
+
+    Ok((node, placeholders))
+}
+
+/// Returns `true` if `left` and `right` have the same structure and token texts.
+fn elements_eq<L: Language>(left: &SyntaxElement<L>, right: &SyntaxElement<L>) -> bool {
+    match (left, right) {
+        (NodeOrToken::Node(left), NodeOrToken::Node(right)) => {
+            left.structurally_eq(right, TriviaMode::Ignore)
+        }
+        (NodeOrToken::Token(left), NodeOrToken::Token(right)) => {
+            left.kind() == right.kind() && left.text_trimmed() == right.text_trimmed()
+        }
+        _ => false,
+    }
+}
+
+/// Which comments of an element's own leading and trailing trivia [with_trivia_of] keeps.
+#[derive(Debug, Clone, Copy)]
+struct OwnComments {
+    leading: bool,
+    trailing: bool,
+}
+
+/// Returns `element` with the leading and trailing trivia of `trivia_source`.
+///
+/// The comments in `element`'s own leading and trailing trivia selected by `own_comments` are kept:
+/// they follow the leading trivia and precede the trailing trivia of `trivia_source`.
+fn with_trivia_of<L: Language>(
+    element: SyntaxElement<L>,
+    trivia_source: &SyntaxElement<L>,
+    own_comments: OwnComments,
+) -> SyntaxElement<L> {
+    let (Some(leading), Some(trailing)) = (
+        trivia_source.leading_trivia(),
+        trivia_source.trailing_trivia(),
+    ) else {
+        return element;
+    };
+    let (Some(own_leading), Some(own_trailing)) =
+        (element.leading_trivia(), element.trailing_trivia())
+    else {
+        return element;
+    };
+
+    let leading: Vec<_> = leading
+        .pieces()
+        .chain(
+            own_leading
+                .pieces()
+                .skip_while(|piece| !piece.is_comments())
+                .filter(|_| own_comments.leading),
+        )
+        .collect();
+
+    let own_trailing: Vec<_> = own_trailing.pieces().collect();
+    let own_comments_end = own_trailing
+        .iter()
+        .rposition(|piece| piece.is_comments())
+        .filter(|_| own_comments.trailing)
+        .map_or(0, |last| last + 1);
+    let trailing: Vec<_> = own_trailing
+        .into_iter()
+        .take(own_comments_end)
+        .chain(trailing.pieces())
+        .collect();
+
+    match element {
+        NodeOrToken::Token(token) => token
+            .with_leading_trivia_pieces(leading)
+            .with_trailing_trivia_pieces(trailing)
+            .into(),
+        NodeOrToken::Node(node) => node
+            .clone()
+            .with_leading_trivia_pieces(leading)
+            .and_then(|node| node.with_trailing_trivia_pieces(trailing))
+            .unwrap_or(node)
+            .into(),
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::{RawLanguage, parse};
+
+    fn pattern(pattern: &str) -> SsrPattern<RawLanguage> {
+        SsrPattern::new(pattern, parse).unwrap()
+    }
+
+    fn rule(pattern: &str, replacement: &str) -> SsrRule<RawLanguage> {
+        SsrRule::new(pattern, replacement, parse).unwrap()
+    }
+
+    fn apply(pattern: &str, replacement: &str, text: &str) -> String {
+        let root = rule(pattern, replacement).apply(parse(text));
+
+        assert_eq!(root.validate(), vec![]);
+        root.text_with_trivia().to_string()
+    }
+
+    fn placeholder_text(ssr_match: &SsrMatch<RawLanguage>, name: &str) -> String {
+        match ssr_match.placeholder(name).unwrap() {
+            NodeOrToken::Node(node) => node.text_trimmed().to_string(),
+            NodeOrToken::Token(token) => token.text_trimmed().to_string(),
+        }
+    }
+
+    #[test]
+    fn placeholders_match_any_node() {
+        let pattern = pattern("$left + $right");
+        assert_eq!(pattern.placeholders(), ["left", "right"]);
+
+        let matches = pattern.find(&parse("x + y;\n(a * b)  +  f(c);\nx - y;"));
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(placeholder_text(&matches[0], "left"), "x");
+        assert_eq!(placeholder_text(&matches[0], "right"), "y");
+        assert_eq!(placeholder_text(&matches[1], "left"), "(a * b)");
+        assert_eq!(placeholder_text(&matches[1], "right"), "f(c)");
+        assert_eq!(matches[1].node().text_trimmed(), "(a * b)  +  f(c)");
+    }
+
+    #[test]
+    fn placeholders_match_outermost_node() {
+        let matches = pattern("f($a)").find(&parse("f(1 + 2);"));
+
+        let NodeOrToken::Node(argument) = matches[0].placeholder("a").unwrap() else {
+            panic!("expected the placeholder to match a node");
+        };
+        assert_eq!(
+            argument.kind(),
+            crate::raw_language::RawLanguageKind::BINARY_EXPRESSION
+        );
+    }
+
+    #[test]
+    fn tokens_must_match() {
+        let pattern = pattern("f($a, 1)");
+
+        assert_eq!(pattern.find(&parse("f(x, 1); f(x, 2); g(x, 1);")).len(), 1);
+    }
+
+    #[test]
+    fn repeated_placeholders_match_equal_code() {
+        let matches = pattern("$a + $a").find(&parse("x + x;\nx + y;\n(a+b) + ( a + b );"));
+
+        let texts: Vec<_> = matches
+            .iter()
+            .map(|ssr_match| ssr_match.node().text_trimmed().to_string())
+            .collect();
+        assert_eq!(texts, ["x + x", "(a+b) + ( a + b )"]);
+    }
+
+    #[test]
+    fn matches_do_not_overlap() {
+        let matches = pattern("$a + $b").find(&parse("a + b + c;"));
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(placeholder_text(&matches[0], "a"), "a + b");
+    }
+
+    #[test]
+    fn apply_replaces_matches() {
+        assert_eq!(apply("$a + $b", "$b + $a", "x + y;\nz;"), "y + x;\nz;");
+        assert_eq!(apply("f($a, $b)", "g($b)", "f(1, 2);"), "g(2);");
+        assert_eq!(apply("f($a)", "f($a)", "g(1);"), "g(1);");
+    }
+
+    #[test]
+    fn apply_to_nested_matches() {
+        // The placeholders of the outer match aren't searched for further matches
+        assert_eq!(apply("$a + $b", "$b + $a", "a + b + c;"), "c + a + b;");
+    }
+
+    #[test]
+    fn apply_keeps_trivia() {
+        // The trivia surrounding the match
+        assert_eq!(
+            apply("$a + $b", "$b + $a", "// leading\nx  +  y; // trailing\nz;"),
+            "// leading\ny + x; // trailing\nz;"
+        );
+
+        // The comments of the elements matched by placeholders
+        assert_eq!(
+            apply("f($a, $b)", "f($b, $a)", "f(\n/* a */ a, b /* b */);"),
+            "f(b /* b */, /* a */ a);"
+        );
+    }
+
+    #[test]
+    fn replacement_with_only_a_placeholder() {
+        assert_eq!(apply("$a + 0", "$a", "x + 0;\ny + 1;"), "x;\ny + 1;");
+    }
+
+    #[test]
+    fn invalid_placeholder() {
+        assert_eq!(
+            SsrPattern::new("$ + a", parse).unwrap_err(),
+            SsrError::InvalidPlaceholder { offset: 0.into() }
+        );
+        assert_eq!(
+            SsrRule::new("$a + b", "$a + $", parse).unwrap_err(),
+            SsrError::InvalidPlaceholder { offset: 5.into() }
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            SsrPattern::new("$a + )", parse).unwrap_err(),
+            SsrError::PatternParseError
+        );
+        assert_eq!(
+            SsrPattern::new("$a @ $b", parse).unwrap_err(),
+            SsrError::PatternParseError
+        );
+        assert_eq!(
+            SsrRule::new("$a + $b", "$a + )", parse).unwrap_err(),
+            SsrError::ReplacementParseError
+        );
+    }
+
+    #[test]
+    fn placeholder_only_pattern() {
+        assert_eq!(
+            SsrPattern::new("$a", parse).unwrap_err(),
+            SsrError::PlaceholderOnlyPattern
+        );
+        assert_eq!(
+            SsrPattern::new(" ( $a ) ", parse).map(|_| ()),
+            Ok(()),
+            "a parenthesized placeholder only matches parenthesized expressions"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholder() {
+        assert_eq!(
+            SsrRule::new("$a + $b", "$a + $c", parse).unwrap_err(),
+            SsrError::UnknownPlaceholder(String::from("c"))
+        );
+    }
+
+    #[test]
+    fn replacement_kind_mismatch() {
+        let error = SsrRule::new("$a + $b", "$a;", parse).unwrap_err();
+
+        assert_eq!(
+            error,
+            SsrError::ReplacementKindMismatch {
+                pattern: String::from("BINARY_EXPRESSION"),
+                replacement: String::from("EXPRESSION_STATEMENT"),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "the replacement is a `EXPRESSION_STATEMENT` but the pattern matches `BINARY_EXPRESSION`"
+        );
+    }
+}
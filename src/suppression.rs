@@ -0,0 +1,673 @@
+//! Parsing of suppression comments and mapping them to the ranges they suppress.
+//!
+//! A suppression comment disables one or more rules (for example, lint rules) for a part of
+//! the source. With the default [SuppressionSyntax], suppressions look like:
+//!
+//! ```text
+//! // ignore-file(rule): reason     suppresses `rule` in the whole file
+//! // ignore-line(rule): reason     suppresses `rule` on the next line, or on the same line for end of line comments
+//! // ignore(rule, other): reason   suppresses `rule` and `other` in the node following the comment
+//! ```
+//!
+//! File suppressions must come before any token, except tokens for which
+//! [SyntaxKind::is_allowed_before_suppressions] returns `true`.
+//!
+//! A rule matches a suppressed rule if they're equal or if the suppressed rule is a group
+//! of the rule: `ignore(lint/style)` suppresses `lint/style/noVar`.
+
+use std::cell::Cell;
+
+use text_size::{TextRange, TextSize};
+
+use crate::{
+    syntax::{Language, SyntaxKind, node::SyntaxNode, token::SyntaxToken},
+    utility_types::Direction,
+};
+
+/// The syntax of suppression comments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuppressionSyntax {
+    /// The marker that starts a suppression, `ignore` by default.
+    pub marker: String,
+    /// Suffix appended to the marker for line suppressions, `-line` by default.
+    pub line_suffix: String,
+    /// Suffix appended to the marker for file suppressions, `-file` by default.
+    pub file_suffix: String,
+    /// Whether a suppression must explain why the rule is suppressed (`: reason`).
+    pub require_reason: bool,
+}
+
+impl SuppressionSyntax {
+    pub fn new(marker: impl Into<String>) -> Self {
+        Self {
+            marker: marker.into(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SuppressionSyntax {
+    fn default() -> Self {
+        Self {
+            marker: String::from("ignore"),
+            line_suffix: String::from("-line"),
+            file_suffix: String::from("-file"),
+            require_reason: true,
+        }
+    }
+}
+
+/// The part of the source suppressed by a suppression comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionScope {
+    File,
+    Line,
+    Node,
+}
+
+/// A rule listed in a suppression comment.
+#[derive(Debug, Clone)]
+pub struct SuppressedRule {
+    name: String,
+    range: TextRange,
+    used: Cell<bool>,
+}
+
+impl SuppressedRule {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The range of the rule's name inside of the comment.
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    /// Returns `true` if this rule suppressed at least one [Suppressions::is_suppressed] query.
+    pub fn is_used(&self) -> bool {
+        self.used.get()
+    }
+
+    fn matches(&self, rule: &str) -> bool {
+        rule.strip_prefix(self.name.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+}
+
+/// A well-formed suppression comment.
+#[derive(Debug, Clone)]
+pub struct Suppression {
+    scope: SuppressionScope,
+    rules: Vec<SuppressedRule>,
+    reason: Option<String>,
+    comment_range: TextRange,
+    suppressed_range: TextRange,
+}
+
+impl Suppression {
+    pub fn scope(&self) -> SuppressionScope {
+        self.scope
+    }
+
+    pub fn rules(&self) -> &[SuppressedRule] {
+        &self.rules
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    /// The range of the suppression comment.
+    pub fn comment_range(&self) -> TextRange {
+        self.comment_range
+    }
+
+    /// The range in which the suppression's rules are suppressed.
+    pub fn suppressed_range(&self) -> TextRange {
+        self.suppressed_range
+    }
+
+    /// Returns `true` if a diagnostic starting at `offset` is inside the suppressed range.
+    fn suppresses(&self, offset: TextSize) -> bool {
+        if self.scope == SuppressionScope::File {
+            // Diagnostics at the end of the file, e.g. for a missing token, are inside of the file
+            self.suppressed_range.contains_inclusive(offset)
+        } else if self.suppressed_range.is_empty() {
+            // A node suppression at the end of the file that isn't followed by a token
+            self.suppressed_range.start() == offset
+        } else {
+            self.suppressed_range.contains(offset)
+        }
+    }
+}
+
+/// A comment that starts with the suppression marker but isn't a valid suppression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedSuppression {
+    /// The range of the comment.
+    pub range: TextRange,
+    pub kind: MalformedSuppressionKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MalformedSuppressionKind {
+    /// The marker is followed by an unknown suffix, e.g. `ignore-block(rule)`.
+    UnknownScope,
+    /// The marker isn't followed by a parenthesized list of rules.
+    MissingRules,
+    /// The list of rules isn't closed by a `)`.
+    UnclosedRules,
+    /// The list of rules contains an empty rule name, e.g. `ignore(a, )`.
+    EmptyRuleName,
+    /// The suppression doesn't have a `: reason` but [SuppressionSyntax::require_reason] is set.
+    MissingReason,
+    /// A file suppression that's preceded by a token that isn't allowed before suppressions.
+    MisplacedFileSuppression,
+}
+
+/// The suppressions of a tree.
+///
+/// Use [is_suppressed](Suppressions::is_suppressed) to test if a rule is suppressed for a range.
+/// The queries mark the matching suppressions as used, allowing to report unused suppressions
+/// after all rules ran.
+#[derive(Debug, Clone, Default)]
+pub struct Suppressions {
+    suppressions: Vec<Suppression>,
+    malformed: Vec<MalformedSuppression>,
+}
+
+impl Suppressions {
+    /// Collects the suppressions in all comments of the tree rooted at `root`.
+    pub fn from_node<L: Language>(root: &SyntaxNode<L>, syntax: &SuppressionSyntax) -> Self {
+        let mut collector = SuppressionCollector {
+            root,
+            text: root.text_with_trivia().to_string(),
+            syntax,
+            file_suppressions_allowed: true,
+            suppressions: Suppressions::default(),
+        };
+
+        for token in root.descendants_tokens(Direction::Next) {
+            for piece in token.leading_trivia().pieces() {
+                if let Some(comment) = piece.as_comments() {
+                    collector.add_comment(&token, false, comment.text(), comment.text_range());
+                }
+            }
+
+            if !token.text_trimmed().is_empty() && !token.kind().is_allowed_before_suppressions() {
+                collector.file_suppressions_allowed = false;
+            }
+
+            for piece in token.trailing_trivia().pieces() {
+                if let Some(comment) = piece.as_comments() {
+                    collector.add_comment(&token, true, comment.text(), comment.text_range());
+                }
+            }
+        }
+
+        collector.suppressions
+    }
+
+    /// Returns `true` if `rule` is suppressed at the start of `range` and marks the matching
+    /// suppressions as used.
+    pub fn is_suppressed(&self, rule: &str, range: TextRange) -> bool {
+        let mut suppressed = false;
+
+        for suppression in &self.suppressions {
+            if !suppression.suppresses(range.start()) {
+                continue;
+            }
+
+            for suppressed_rule in &suppression.rules {
+                if suppressed_rule.matches(rule) {
+                    suppressed_rule.used.set(true);
+                    suppressed = true;
+                }
+            }
+        }
+
+        suppressed
+    }
+
+    /// Returns all well-formed suppressions in source order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Suppression> {
+        self.suppressions.iter()
+    }
+
+    /// Returns the rules of all suppressions that didn't suppress any [is_suppressed](Suppressions::is_suppressed) query.
+    pub fn unused(&self) -> impl Iterator<Item = (&Suppression, &SuppressedRule)> {
+        self.suppressions.iter().flat_map(|suppression| {
+            suppression
+                .rules
+                .iter()
+                .filter(|rule| !rule.is_used())
+                .map(move |rule| (suppression, rule))
+        })
+    }
+
+    /// Returns the comments that start with the suppression marker but aren't valid suppressions.
+    pub fn malformed(&self) -> &[MalformedSuppression] {
+        &self.malformed
+    }
+}
+
+struct SuppressionCollector<'a, L: Language> {
+    root: &'a SyntaxNode<L>,
+    text: String,
+    syntax: &'a SuppressionSyntax,
+    /// Whether all tokens so far are allowed before file suppressions.
+    file_suppressions_allowed: bool,
+    suppressions: Suppressions,
+}
+
+impl<L: Language> SuppressionCollector<'_, L> {
+    /// Adds the comment `comment` that's part of the leading or trailing trivia of `token`.
+    fn add_comment(
+        &mut self,
+        token: &SyntaxToken<L>,
+        is_trailing: bool,
+        comment: &str,
+        comment_range: TextRange,
+    ) {
+        let parsed = match parse_suppression(comment, comment_range.start(), self.syntax) {
+            None => return,
+            Some(Err(kind)) => {
+                self.suppressions.malformed.push(MalformedSuppression {
+                    range: comment_range,
+                    kind,
+                });
+                return;
+            }
+            Some(Ok(parsed)) => parsed,
+        };
+
+        let suppressed_range = match parsed.scope {
+            SuppressionScope::File => {
+                if !self.file_suppressions_allowed {
+                    self.suppressions.malformed.push(MalformedSuppression {
+                        range: comment_range,
+                        kind: MalformedSuppressionKind::MisplacedFileSuppression,
+                    });
+                    return;
+                }
+
+                self.root.text_range_with_trivia()
+            }
+            SuppressionScope::Line => {
+                let base = self.root.text_range_with_trivia().start();
+                let comment_start = comment_range.start() - base;
+                let line_start = line_start(&self.text, comment_start);
+                let is_own_line = self.text[line_start..usize::from(comment_start)]
+                    .trim()
+                    .is_empty();
+
+                let offset = if is_own_line {
+                    following_token(token, is_trailing).map_or(comment_range.end(), |token| {
+                        token.text_trimmed_range().start()
+                    })
+                } else {
+                    comment_range.start()
+                };
+
+                line_range(&self.text, offset - base) + base
+            }
+            SuppressionScope::Node => match following_token(token, is_trailing) {
+                Some(token) => suppressed_node_range(&token),
+                None => TextRange::empty(comment_range.end()),
+            },
+        };
+
+        self.suppressions.suppressions.push(Suppression {
+            scope: parsed.scope,
+            rules: parsed.rules,
+            reason: parsed.reason,
+            comment_range,
+            suppressed_range,
+        });
+    }
+}
+
+fn following_token<L: Language>(
+    token: &SyntaxToken<L>,
+    is_trailing: bool,
+) -> Option<SyntaxToken<L>> {
+    if is_trailing {
+        token.next_token()
+    } else {
+        Some(token.clone())
+    }
+}
+
+/// Returns the range of the outermost node starting with `token` that isn't a list or the root.
+fn suppressed_node_range<L: Language>(token: &SyntaxToken<L>) -> TextRange {
+    let Some(mut node) = token.parent() else {
+        return token.text_trimmed_range();
+    };
+
+    while let Some(parent) = node.parent() {
+        if parent.kind().is_list()
+            || parent.kind().is_root()
+            || parent.first_token().as_ref() != Some(token)
+        {
+            break;
+        }
+
+        node = parent;
+    }
+
+    if node.kind().is_root() {
+        token.text_trimmed_range()
+    } else {
+        node.text_trimmed_range()
+    }
+}
+
+/// Returns the byte offset of the start of the line containing `offset`.
+fn line_start(text: &str, offset: TextSize) -> usize {
+    text[..usize::from(offset)]
+        .rfind(['\n', '\r'])
+        .map_or(0, |index| index + 1)
+}
+
+/// Returns the range of the line containing `offset`, excluding the line break.
+fn line_range(text: &str, offset: TextSize) -> TextRange {
+    let start = line_start(text, offset);
+    let end = text[usize::from(offset)..]
+        .find(['\n', '\r'])
+        .map_or(text.len(), |index| usize::from(offset) + index);
+
+    TextRange::new(
+        TextSize::try_from(start).unwrap(),
+        TextSize::try_from(end).unwrap(),
+    )
+}
+
+struct ParsedSuppression {
+    scope: SuppressionScope,
+    rules: Vec<SuppressedRule>,
+    reason: Option<String>,
+}
+
+/// Parses the text of a comment starting at `offset`.
+///
+/// Returns `None` if the comment isn't a suppression comment.
+fn parse_suppression(
+    comment: &str,
+    offset: TextSize,
+    syntax: &SuppressionSyntax,
+) -> Option<Result<ParsedSuppression, MalformedSuppressionKind>> {
+    // Strip the comment delimiters, e.g. `//`, `/*`, `*/`, or `#`
+    let body = comment
+        .trim_start_matches(['/', '*', '#', '!'])
+        .trim_start();
+    let body_offset = comment.len() - body.len();
+    let body = body.strip_suffix("*/").unwrap_or(body);
+
+    let rest = body.strip_prefix(syntax.marker.as_str())?;
+
+    if !rest.starts_with(['(', '-', ':']) {
+        return None;
+    }
+
+    let (scope, rest) = if let Some(rest) = rest.strip_prefix(syntax.file_suffix.as_str()) {
+        (SuppressionScope::File, rest)
+    } else if let Some(rest) = rest.strip_prefix(syntax.line_suffix.as_str()) {
+        (SuppressionScope::Line, rest)
+    } else if rest.starts_with('-') {
+        return Some(Err(MalformedSuppressionKind::UnknownScope));
+    } else {
+        (SuppressionScope::Node, rest)
+    };
+
+    let Some(rules_text) = rest.strip_prefix('(') else {
+        return Some(Err(MalformedSuppressionKind::MissingRules));
+    };
+
+    let Some(rules_end) = rules_text.find(')') else {
+        return Some(Err(MalformedSuppressionKind::UnclosedRules));
+    };
+
+    // Offset of `rules_text` relative to the start of the comment
+    let rules_offset = body_offset + (body.len() - rules_text.len());
+    let mut rules = Vec::new();
+    let mut rule_start = 0;
+
+    for name in rules_text[..rules_end].split(',') {
+        let trimmed = name.trim();
+
+        if trimmed.is_empty() {
+            return Some(Err(MalformedSuppressionKind::EmptyRuleName));
+        }
+
+        let start = rules_offset + rule_start + (name.len() - name.trim_start().len());
+        let start = offset + TextSize::try_from(start).unwrap();
+
+        rules.push(SuppressedRule {
+            name: trimmed.to_string(),
+            range: TextRange::at(start, TextSize::of(trimmed)),
+            used: Cell::new(false),
+        });
+
+        rule_start += name.len() + 1;
+    }
+
+    let reason = rules_text[rules_end + 1..]
+        .trim()
+        .strip_prefix(':')
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty())
+        .map(String::from);
+
+    if reason.is_none() && syntax.require_reason {
+        return Some(Err(MalformedSuppressionKind::MissingReason));
+    }
+
+    Some(Ok(ParsedSuppression {
+        scope,
+        rules,
+        reason,
+    }))
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::parse;
+
+    fn suppressions(text: &str) -> Suppressions {
+        Suppressions::from_node(&parse(text), &SuppressionSyntax::default())
+    }
+
+    /// Returns the range of the first occurrence of `needle` in `text`.
+    fn range_of(text: &str, needle: &str) -> TextRange {
+        let start = text.find(needle).unwrap();
+        TextRange::at(TextSize::try_from(start).unwrap(), TextSize::of(needle))
+    }
+
+    fn malformed(text: &str) -> Vec<MalformedSuppressionKind> {
+        suppressions(text)
+            .malformed()
+            .iter()
+            .map(|malformed| malformed.kind)
+            .collect()
+    }
+
+    #[test]
+    fn node_suppression_covers_the_following_node() {
+        let text = "// ignore(lint): why\nfoo;bar;";
+        let suppressions = suppressions(text);
+
+        let suppression = suppressions.iter().next().unwrap();
+        assert_eq!(suppression.scope(), SuppressionScope::Node);
+        assert_eq!(suppression.reason(), Some("why"));
+        assert_eq!(
+            suppression.comment_range(),
+            range_of(text, "// ignore(lint): why")
+        );
+        assert_eq!(suppression.suppressed_range(), range_of(text, "foo;"));
+        assert_eq!(suppression.rules()[0].name(), "lint");
+        assert_eq!(suppression.rules()[0].range(), range_of(text, "lint"));
+
+        assert!(suppressions.is_suppressed("lint", range_of(text, "foo")));
+        assert!(suppressions.is_suppressed("lint", range_of(text, "foo;")));
+        // `bar` starts at the end of the suppressed range
+        assert!(!suppressions.is_suppressed("lint", range_of(text, "bar")));
+        assert!(!suppressions.is_suppressed("other", range_of(text, "foo")));
+    }
+
+    #[test]
+    fn node_suppression_at_end_of_file() {
+        let text = "a;\n// ignore(lint): why";
+        let suppressions = suppressions(text);
+        let end = TextSize::of(text);
+
+        assert_eq!(
+            suppressions.iter().next().unwrap().suppressed_range(),
+            TextRange::empty(end)
+        );
+        assert!(suppressions.is_suppressed("lint", TextRange::empty(end)));
+        assert!(!suppressions.is_suppressed("lint", range_of(text, "a;")));
+    }
+
+    #[test]
+    fn line_suppression_covers_the_next_line() {
+        let text = "a; b;\n// ignore-line(lint): why\nc; d;\ne;";
+        let suppressions = suppressions(text);
+
+        assert_eq!(
+            suppressions.iter().next().unwrap().suppressed_range(),
+            range_of(text, "c; d;")
+        );
+        assert!(suppressions.is_suppressed("lint", range_of(text, "c")));
+        assert!(suppressions.is_suppressed("lint", range_of(text, "d")));
+        assert!(!suppressions.is_suppressed("lint", range_of(text, "a")));
+        assert!(!suppressions.is_suppressed("lint", range_of(text, "e")));
+    }
+
+    #[test]
+    fn end_of_line_suppression_covers_its_own_line() {
+        let text = "a; // ignore-line(lint): why\nb;";
+        let suppressions = suppressions(text);
+
+        assert!(suppressions.is_suppressed("lint", range_of(text, "a")));
+        assert!(!suppressions.is_suppressed("lint", range_of(text, "b")));
+    }
+
+    #[test]
+    fn file_suppression_covers_the_whole_file() {
+        let text = "// ignore-file(lint): why\na;\nb;";
+        let suppressions = suppressions(text);
+
+        assert_eq!(
+            suppressions.iter().next().unwrap().scope(),
+            SuppressionScope::File
+        );
+        assert!(suppressions.is_suppressed("lint", range_of(text, "a")));
+        assert!(suppressions.is_suppressed("lint", range_of(text, "b")));
+        assert!(suppressions.is_suppressed("lint", TextRange::empty(TextSize::of(text))));
+    }
+
+    #[test]
+    fn group_suppresses_its_rules() {
+        let text = "// ignore(lint/style): why\na;";
+        let suppressions = suppressions(text);
+        let a = range_of(text, "a");
+
+        assert!(suppressions.is_suppressed("lint/style", a));
+        assert!(suppressions.is_suppressed("lint/style/noVar", a));
+        assert!(!suppressions.is_suppressed("lint/styles", a));
+        assert!(!suppressions.is_suppressed("lint", a));
+        assert!(!suppressions.is_suppressed("lint/correctness/noVar", a));
+    }
+
+    #[test]
+    fn rule_suppresses_only_itself() {
+        let text = "// ignore(lint/style/noVar): why\na;";
+        let suppressions = suppressions(text);
+        let a = range_of(text, "a;");
+
+        assert!(suppressions.is_suppressed("lint/style/noVar", a));
+        assert!(!suppressions.is_suppressed("lint/style", a));
+    }
+
+    #[test]
+    fn unused_returns_rules_that_suppressed_nothing() {
+        let text = "// ignore(first, second): why\na;\n// ignore(third): why\nb;";
+        let suppressions = suppressions(text);
+
+        assert!(suppressions.is_suppressed("first", range_of(text, "a")));
+        // `third` doesn't suppress `a`
+        assert!(!suppressions.is_suppressed("third", range_of(text, "a")));
+
+        let unused: Vec<_> = suppressions.unused().map(|(_, rule)| rule.name()).collect();
+        assert_eq!(unused, vec!["second", "third"]);
+        assert!(suppressions.iter().next().unwrap().rules()[0].is_used());
+    }
+
+    #[test]
+    fn malformed_suppressions() {
+        assert_eq!(
+            malformed("// ignore-block(lint): why\na;"),
+            vec![MalformedSuppressionKind::UnknownScope]
+        );
+        assert_eq!(
+            malformed("// ignore: why\na;"),
+            vec![MalformedSuppressionKind::MissingRules]
+        );
+        assert_eq!(
+            malformed("// ignore(lint: why\na;"),
+            vec![MalformedSuppressionKind::UnclosedRules]
+        );
+        assert_eq!(
+            malformed("// ignore(lint, ): why\na;"),
+            vec![MalformedSuppressionKind::EmptyRuleName]
+        );
+        assert_eq!(
+            malformed("// ignore(lint)\na;"),
+            vec![MalformedSuppressionKind::MissingReason]
+        );
+        assert_eq!(
+            malformed("a;\n// ignore-file(lint): why\n"),
+            vec![MalformedSuppressionKind::MisplacedFileSuppression]
+        );
+    }
+
+    #[test]
+    fn malformed_suppression_range_is_the_comment() {
+        let text = "a;\n/* ignore(lint) */\nb;";
+        let suppressions = suppressions(text);
+
+        assert_eq!(
+            suppressions.malformed(),
+            &[MalformedSuppression {
+                range: range_of(text, "/* ignore(lint) */"),
+                kind: MalformedSuppressionKind::MissingReason,
+            }]
+        );
+        assert_eq!(suppressions.iter().count(), 0);
+    }
+
+    #[test]
+    fn comments_without_marker_are_ignored() {
+        let suppressions = suppressions("// ignored\n// ignore\n// ignores(lint): why\na;");
+
+        assert_eq!(suppressions.iter().count(), 0);
+        assert!(suppressions.malformed().is_empty());
+    }
+
+    #[test]
+    fn custom_syntax() {
+        let text = "// lint-ignore-next(rule)\na;";
+        let syntax = SuppressionSyntax {
+            line_suffix: String::from("-next"),
+            require_reason: false,
+            ..SuppressionSyntax::new("lint-ignore")
+        };
+        let suppressions = Suppressions::from_node(&parse(text), &syntax);
+
+        let suppression = suppressions.iter().next().unwrap();
+        assert_eq!(suppression.scope(), SuppressionScope::Line);
+        assert_eq!(suppression.reason(), None);
+        assert!(suppressions.is_suppressed("rule", range_of(text, "a")));
+    }
+}
@@ -4,19 +4,37 @@ use crate::{
     ast::AstNode,
     green::RawSyntaxKind,
     syntax::node::{SyntaxNode, SyntaxSlot, SyntaxSlots},
+    syntax_factory::schema::ErasedSlotSchema,
 };
 
 pub mod element;
 pub mod node;
+pub mod normalize;
+pub mod reindent;
 pub mod rewriter;
+pub mod skipped;
+pub mod source_style;
+pub mod structural;
 pub mod token;
 pub mod trivia;
+pub mod validate;
+
+#[cfg(feature = "macros")]
+pub use mini_rowan_macros::SyntaxKind;
 
 /// Type tag for each node or token of a language
 pub trait SyntaxKind: fmt::Debug + PartialEq + Copy {
     const TOMBSTONE: Self;
     const EOF: Self;
 
+    /// The number of kinds of the language. The raw values of all kinds are less than this count.
+    ///
+    /// Bounds [SyntaxKindSet::all](crate::ast::SyntaxKindSet::all) and
+    /// [SyntaxKindSet::complement](crate::ast::SyntaxKindSet::complement). Defaults to 640, the number of
+    /// kinds a [SyntaxKindSet](crate::ast::SyntaxKindSet) can hold, in which case these sets contain raw values
+    /// that aren't kinds of the language. Languages should override it if they iterate over such sets.
+    const KIND_COUNT: u16 = 640;
+
     /// Returns `true` if this is a kind of a bogus node.
     fn is_bogus(&self) -> bool;
 
@@ -35,6 +53,13 @@ pub trait SyntaxKind: fmt::Debug + PartialEq + Copy {
     /// Returns `true` if this kind is a list node.
     fn is_list(&self) -> bool;
 
+    /// Returns the kind of the separator tokens if this kind is a separated list, or `None` otherwise.
+    ///
+    /// Separated lists may contain empty slots for missing elements or separators.
+    fn list_separator(&self) -> Option<Self> {
+        None
+    }
+
     /// Returns `true` if this kind is a trivia.
     fn is_trivia(self) -> bool;
 
@@ -46,11 +71,27 @@ pub trait SyntaxKind: fmt::Debug + PartialEq + Copy {
     fn is_allowed_before_suppressions(&self) -> bool {
         false
     }
+
+    /// Returns `true` if this kind is a string literal token whose text must be preserved as is,
+    /// for example when re-indenting a node.
+    fn is_string_literal(&self) -> bool {
+        false
+    }
 }
 
 pub trait Language: Sized + Clone + Copy + fmt::Debug + Eq + Ord + std::hash::Hash {
     type Kind: SyntaxKind;
     type Root: AstNode<Language = Self> + Clone + Eq + fmt::Debug;
+
+    /// Returns the [SlotSchema](crate::syntax_factory::schema::SlotSchema) of the language, or
+    /// `None` if it doesn't describe its slots.
+    ///
+    /// Code that works with any language reads the slot names through it, for example to label
+    /// the slots in the alternate `Debug` output of nodes. Languages implementing `SlotSchema`
+    /// return `Some(ErasedSlotSchema::new())`.
+    fn slot_schema() -> Option<ErasedSlotSchema<Self>> {
+        None
+    }
 }
 
 /// A list of `SyntaxNode's` and/or `SyntaxToken`s
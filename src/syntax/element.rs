@@ -1,4 +1,4 @@
-use std::{iter, ptr::NonNull};
+use std::{cmp::Ordering, iter, ptr::NonNull};
 
 use text_size::{TextRange, TextSize};
 
@@ -76,6 +76,14 @@ impl<L: Language> SyntaxElement<L> {
         iter::successors(first, SyntaxNode::parent)
     }
 
+    /// Compares the positions of the elements in a preorder traversal of their tree: an element comes
+    /// before its descendants and its following siblings. Returns `None` if the elements belong to
+    /// different trees.
+    pub fn cmp_position(&self, other: &Self) -> Option<Ordering> {
+        cursor::element::SyntaxElement::from(self.clone())
+            .cmp_position(&cursor::element::SyntaxElement::from(other.clone()))
+    }
+
     pub fn next_sibling_or_token(&self) -> Option<Self> {
         match self {
             Self::Node(it) => it.next_sibling_or_token(),
@@ -8,15 +8,16 @@ use std::{
 use text_size::{TextRange, TextSize};
 
 use crate::{
-    ast::AstNode,
+    ast::{AstNode, SyntaxKindSet},
     cursor,
     green::{element::GreenElement, node::GreenNode},
     syntax::{
         Language, SyntaxKind, SyntaxList,
         element::{SyntaxElement, SyntaxElementKey},
         token::SyntaxToken,
-        trivia::{SyntaxTrivia, SyntaxTriviaPiece},
+        trivia::{SyntaxTrivia, SyntaxTriviaPiece, TriviaPieceKind},
     },
+    syntax_factory::schema::{SchemaSlot, SlotSchema},
     syntax_token_text::SyntaxNodeText,
     utility_types::{Direction, NodeOrToken, TokenAtOffset, WalkEvent},
 };
@@ -105,7 +106,7 @@ impl<L: Language> SyntaxNode<L> {
 
     /// Returns the trailing trivia of the [last_token](SyntaxNode::last_token), or [None] if the node does not have any descendant tokens.
     pub fn last_trailing_trivia(&self) -> Option<SyntaxTrivia<L>> {
-        self.raw.parent().map(SyntaxTrivia::from)
+        self.raw.last_trailing_trivia().map(SyntaxTrivia::new)
     }
 
     pub fn parent(&self) -> Option<Self> {
@@ -127,6 +128,17 @@ impl<L: Language> SyntaxNode<L> {
         self.raw.ancestors().map(Self::from)
     }
 
+    /// Returns the deepest node that is an ancestor of both this node and `other`, or `None` if they
+    /// belong to different trees. Like [ancestors](SyntaxNode::ancestors), a node counts as its own ancestor.
+    pub fn common_ancestor(&self, other: &Self) -> Option<Self> {
+        self.raw.common_ancestor(&other.raw).map(Self::from)
+    }
+
+    /// Returns `true` if this node is `other` or one of its ancestors.
+    pub fn is_ancestor_of(&self, other: &Self) -> bool {
+        self.raw.is_ancestor_of(&other.raw)
+    }
+
     pub fn children(&self) -> SyntaxNodeChildren<L> {
         SyntaxNodeChildren {
             raw: self.raw.children(),
@@ -212,6 +224,33 @@ impl<L: Language> SyntaxNode<L> {
         self.raw.descendants().map(Self::from)
     }
 
+    /// Returns the ancestors of this node, starting with the node itself, whose kind is in `kinds`.
+    pub fn ancestors_matching(
+        &self,
+        kinds: SyntaxKindSet<L>,
+    ) -> impl Iterator<Item = Self> + use<L> {
+        self.ancestors()
+            .filter(move |node| kinds.matches(node.kind()))
+    }
+
+    /// Returns the child nodes whose kind is in `kinds`.
+    pub fn children_matching(
+        &self,
+        kinds: SyntaxKindSet<L>,
+    ) -> impl Iterator<Item = Self> + use<L> {
+        self.children()
+            .filter(move |node| kinds.matches(node.kind()))
+    }
+
+    /// Returns the descendants of this node, including the node itself, whose kind is in `kinds`.
+    pub fn descendants_matching(
+        &self,
+        kinds: SyntaxKindSet<L>,
+    ) -> impl Iterator<Item = Self> + use<L> {
+        self.descendants()
+            .filter(move |node| kinds.matches(node.kind()))
+    }
+
     pub fn descendants_tokens(
         &self,
         direction: Direction,
@@ -275,6 +314,33 @@ impl<L: Language> SyntaxNode<L> {
         NodeOrToken::from(self.raw.convering_element(range))
     }
 
+    /// Returns the deepest node or token that contains `range` and all of its ancestors with the same
+    /// range, starting with the outermost.
+    ///
+    /// Unlike [covering_element](SyntaxNode::covering_element), the range may be empty: it's then
+    /// covered by the element starting at its offset or, if there's none, the element ending there.
+    /// Returns an empty list if `range` isn't contained within the current node.
+    pub fn covering_elements(&self, range: TextRange) -> Vec<SyntaxElement<L>> {
+        self.raw
+            .covering_elements(range)
+            .into_iter()
+            .map(SyntaxElement::from)
+            .collect()
+    }
+
+    /// Returns the deepest node that contains `range`, accepting empty ranges like
+    /// [covering_elements](SyntaxNode::covering_elements).
+    ///
+    /// Returns the parent of the covering token if `range` is inside a single token.
+    pub fn covering_node(&self, range: TextRange) -> Option<Self> {
+        let node = match self.raw.covering_elements(range).pop()? {
+            NodeOrToken::Node(node) => node,
+            NodeOrToken::Token(token) => token.parent()?,
+        };
+
+        Some(Self::from(node))
+    }
+
     /// Finds a [`SyntaxElement`] which intersects with a given `range`. If
     /// there are several intersecting elements, any one can be returned.
     ///
@@ -336,6 +402,253 @@ impl<L: Language> SyntaxNode<L> {
         })
     }
 
+    /// Return a new version of this node with the descendant `prev_node` replaced with `next_node`,
+    /// keeping the trivia surrounding `prev_node`.
+    ///
+    /// The leading trivia of `prev_node`'s first token replaces the leading trivia of `next_node`'s
+    /// first token and the trailing trivia of its last token replaces the trailing trivia of
+    /// `next_node`'s last token. If `next_node` has no tokens, the comments are moved to the
+    /// neighboring tokens as in [remove_node_preserving_comments](SyntaxNode::remove_node_preserving_comments).
+    ///
+    /// Returns `None` if `prev_node` is not a descendant of this node.
+    #[must_use = "syntax elements are immutable, the result of update methods must be propagated to have any effect"]
+    pub fn replace_node_preserving_trivia(
+        self,
+        prev_node: SyntaxNode<L>,
+        next_node: SyntaxNode<L>,
+    ) -> Option<Self> {
+        let (Some(leading), Some(trailing)) = (
+            prev_node.first_leading_trivia(),
+            prev_node.last_trailing_trivia(),
+        ) else {
+            return self.replace_child(prev_node.into(), next_node.into());
+        };
+
+        if next_node.first_token().is_none() {
+            let parent = prev_node.parent()?;
+            let index = prev_node.index();
+
+            return self.splice_preserving_comments(
+                &prev_node,
+                &parent,
+                index..=index,
+                Some(next_node.into()),
+            );
+        }
+
+        let next_node = next_node
+            .with_leading_trivia_pieces(leading.pieces())?
+            .with_trailing_trivia_pieces(trailing.pieces())?;
+
+        self.replace_child(prev_node.into(), next_node.into())
+    }
+
+    /// Return a new version of this node with the descendant `node` removed.
+    ///
+    /// The comments in the leading trivia of `node` are prepended to the leading trivia of the
+    /// token following `node`, and the comments in its trailing trivia are appended to the
+    /// trailing trivia of the token preceding `node`. If this node has no token on that side of
+    /// `node`, the comments move to the token on the other side instead. Removing an element
+    /// from a list removes its slot, together with the element's separator if the list is a
+    /// separated list. Removing any other node leaves its slot empty.
+    ///
+    /// Returns `None` if `node` is not a descendant of this node, or if `node` has comments but
+    /// this node has no token before or after `node` to move them to.
+    #[must_use = "syntax elements are immutable, the result of update methods must be propagated to have any effect"]
+    pub fn remove_node_preserving_comments(self, node: SyntaxNode<L>) -> Option<Self> {
+        let parent = node.parent()?;
+        let index = node.index();
+
+        if !parent.kind().is_list() {
+            return self.splice_preserving_comments(&node, &parent, index..=index, None);
+        }
+
+        let slot_count = parent.slots().len();
+
+        // Remove the separator following the element, or the one preceding it for the last element.
+        // The separator slots of a separated list are the odd slots, even if they're empty.
+        let slots = if parent.kind().list_separator().is_none() {
+            index..=index
+        } else if index + 1 < slot_count {
+            index..=index + 1
+        } else if index > 0 {
+            index - 1..=index
+        } else {
+            index..=index
+        };
+
+        self.splice_preserving_comments(&node, &parent, slots, None)
+    }
+
+    /// Replaces the `slots` of `parent` with `replacement` (or removes them if `parent` is a list)
+    /// and moves the comments surrounding the replaced slots to the neighboring tokens.
+    fn splice_preserving_comments(
+        self,
+        node: &SyntaxNode<L>,
+        parent: &SyntaxNode<L>,
+        slots: ops::RangeInclusive<usize>,
+        replacement: Option<SyntaxElement<L>>,
+    ) -> Option<Self> {
+        if !node.ancestors().skip(1).any(|ancestor| ancestor == self) {
+            return None;
+        }
+
+        let replaced_tokens = || {
+            slots
+                .clone()
+                .filter_map(|slot| parent.element_in_slot(slot as u32))
+                .flat_map(|element| match element {
+                    NodeOrToken::Node(node) => {
+                        node.descendants_tokens(Direction::Next).collect::<Vec<_>>()
+                    }
+                    NodeOrToken::Token(token) => vec![token],
+                })
+        };
+
+        // `replace_child` returns a new root, use slot paths to find the elements in the updated tree
+        let parent_path = self.path_to(&parent.clone().into());
+        let mut root = self.clone();
+
+        if let (Some(first), Some(last)) = (replaced_tokens().next(), replaced_tokens().last()) {
+            let is_in_self =
+                |token: &SyntaxToken<L>| token.ancestors().any(|ancestor| ancestor == self);
+            let next = last.next_token().filter(is_in_self);
+            let prev = first.prev_token().filter(is_in_self);
+            let prev_path = prev.map(|prev| self.path_to(&prev.into()));
+
+            let leading = first.leading_trivia();
+            let trailing = last.trailing_trivia();
+            // Trivia prepended to the leading trivia of the next token
+            let mut moved_leading: Vec<(TriviaPieceKind, String)> = Vec::new();
+            // Trivia appended to the trailing trivia of the previous token
+            let mut moved_trailing: Vec<SyntaxTriviaPiece<L>> = Vec::new();
+            let has_trailing_comments = trailing.pieces().any(|piece| piece.is_comments());
+
+            if leading.pieces().any(|piece| piece.is_comments()) {
+                if next.is_some() {
+                    moved_leading.extend(
+                        leading
+                            .pieces()
+                            .map(|piece| (piece.kind(), String::from(piece.text()))),
+                    );
+                } else if prev_path.is_some() {
+                    // Keep the comments at the end of the previous token, without the
+                    // indentation of the removed node. Keep the line break if the trailing
+                    // comments follow so that they don't end up in a single line comment.
+                    moved_trailing.extend(leading.pieces());
+
+                    while moved_trailing.last().is_some_and(|piece| {
+                        piece.is_whitespace() || (piece.is_newline() && !has_trailing_comments)
+                    }) {
+                        moved_trailing.pop();
+                    }
+                } else {
+                    return None;
+                }
+            }
+
+            if has_trailing_comments {
+                if prev_path.is_some() {
+                    let starts_line = moved_trailing
+                        .last()
+                        .is_some_and(|piece| piece.is_newline());
+                    moved_trailing.extend(
+                        trailing
+                            .pieces()
+                            .skip_while(|piece| starts_line && piece.is_whitespace()),
+                    );
+                } else if next.is_some() {
+                    // Put the comments on their own line before the next token
+                    moved_leading.extend(
+                        trailing
+                            .pieces()
+                            .skip_while(|piece| piece.is_whitespace())
+                            .map(|piece| (piece.kind(), String::from(piece.text()))),
+                    );
+                    moved_leading.push((TriviaPieceKind::Newline, String::from("\n")));
+                } else {
+                    return None;
+                }
+            }
+
+            if let (false, Some(next)) = (moved_leading.is_empty(), next) {
+                // The moved trivia ends with the indentation of the removed node. Skip the
+                // newlines and whitespace preceding the next token's own trivia.
+                moved_leading.extend(
+                    next.leading_trivia()
+                        .pieces()
+                        .skip_while(|piece| piece.is_whitespace() || piece.is_newline())
+                        .map(|piece| (piece.kind(), String::from(piece.text()))),
+                );
+
+                let new_next = next.with_leading_trivia(
+                    moved_leading
+                        .iter()
+                        .map(|(kind, text)| (*kind, text.as_str())),
+                );
+
+                root = root.replace_child(next.into(), new_next.into())?;
+            }
+
+            if let (false, Some(prev_path)) = (moved_trailing.is_empty(), prev_path) {
+                let prev = root.element_at_path(&prev_path)?.into_token()?;
+                let new_prev = prev.with_trailing_trivia_pieces(
+                    prev.trailing_trivia()
+                        .pieces()
+                        .chain(moved_trailing)
+                        .collect::<Vec<_>>(),
+                );
+
+                root = root.replace_child(prev.into(), new_prev.into())?;
+            }
+        }
+
+        let replace_with: Vec<_> = if parent.kind().is_list() && replacement.is_none() {
+            Vec::new()
+        } else {
+            std::iter::once(replacement)
+                .chain(std::iter::repeat_n(None, slots.end() - slots.start()))
+                .collect()
+        };
+
+        if parent_path.is_empty() {
+            return Some(root.splice_slots(slots, replace_with));
+        }
+
+        let parent = root.element_at_path(&parent_path)?.into_node()?;
+        let updated_parent = parent.clone().splice_slots(slots, replace_with);
+        root.replace_child(parent.into(), updated_parent.into())
+    }
+
+    /// Returns the slot indices leading from this node to its descendant `element`.
+    fn path_to(&self, element: &SyntaxElement<L>) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = element.clone();
+
+        while current.as_node() != Some(self) {
+            path.push(current.index());
+
+            match current.parent() {
+                Some(parent) => current = NodeOrToken::Node(parent),
+                None => break,
+            }
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// Returns the element reached by following the slot indices in `path`.
+    fn element_at_path(&self, path: &[usize]) -> Option<SyntaxElement<L>> {
+        let mut element = NodeOrToken::Node(self.clone());
+
+        for index in path {
+            element = element.into_node()?.element_in_slot(*index as u32)?;
+        }
+
+        Some(element)
+    }
+
     /// Return a new version of this node with the leading trivia of its first token replaced with `trivia`.
     #[must_use = "syntax elements are immutable, the result of update methods must be propagated to have any effect"]
     pub fn with_leading_trivia_pieces<I>(self, trivia: I) -> Option<Self>
@@ -478,9 +791,31 @@ where
     }
 }
 
+impl<L: SlotSchema> SyntaxNode<L> {
+    /// Returns the slots of this node's kind as described by the language's [SlotSchema], or `None`
+    /// if the kind has no fixed slots, like lists and bogus nodes.
+    pub fn slot_schema(&self) -> Option<&'static [SchemaSlot<L>]> {
+        L::slots(self.kind())
+    }
+
+    /// Returns the slot called `name`, or `None` if this node's kind has no such slot.
+    ///
+    /// The returned slot is [SyntaxSlot::Empty] if the child is missing.
+    pub fn slot_by_name(&self, name: &str) -> Option<SyntaxSlot<L>> {
+        let index = L::slot_index(self.kind(), name)?;
+        self.slots().nth(index)
+    }
+
+    /// Returns the name of the slot at `index`.
+    pub fn slot_name(&self, index: usize) -> Option<&'static str> {
+        L::slot_name(self.kind(), index)
+    }
+}
+
 impl<L: Language> fmt::Debug for SyntaxNode<L> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
+            let schema = L::slot_schema();
             let mut level = 0;
             for event in self.raw.preorder_slots() {
                 match event {
@@ -488,19 +823,33 @@ impl<L: Language> fmt::Debug for SyntaxNode<L> {
                         for _ in 0..level {
                             write!(f, " ")?
                         }
+
+                        let (parent, index) = match &element {
+                            cursor::node::SyntaxSlot::Node(node) => (node.parent(), node.index()),
+                            cursor::node::SyntaxSlot::Token(token) => {
+                                (token.parent(), token.index())
+                            }
+                            cursor::node::SyntaxSlot::Empty { parent, index } => {
+                                (Some(parent.clone()), *index as usize)
+                            }
+                        };
+
+                        write!(f, "{index}: ")?;
+
+                        if let Some(name) = parent.zip(schema).and_then(|(parent, schema)| {
+                            schema.slot_name(L::Kind::from_raw(parent.kind()), index)
+                        }) {
+                            write!(f, "{name}: ")?;
+                        }
+
                         match element {
                             cursor::node::SyntaxSlot::Node(node) => {
-                                writeln!(f, "{}: {:?}", node.index(), Self::from(node))?
+                                writeln!(f, "{:?}", Self::from(node))?
                             }
-                            cursor::node::SyntaxSlot::Token(token) => writeln!(
-                                f,
-                                "{}: {:?}",
-                                token.index(),
-                                SyntaxToken::<L>::from(token)
-                            )?,
-                            cursor::node::SyntaxSlot::Empty { parent: _, index } => {
-                                write!(f, "{index}: (empty)")?
+                            cursor::node::SyntaxSlot::Token(token) => {
+                                writeln!(f, "{:?}", SyntaxToken::<L>::from(token))?
                             }
+                            cursor::node::SyntaxSlot::Empty { .. } => writeln!(f, "(empty)")?,
                         }
                         level += 1;
                     }
@@ -769,6 +1118,22 @@ impl<L: Language> SyntaxSlot<L> {
     }
 }
 
+impl<L: SlotSchema> SyntaxSlot<L> {
+    /// Returns the name of this slot in its parent node.
+    ///
+    /// Returns `None` for [SyntaxSlot::Empty] slots because they don't reference their parent, use
+    /// [SyntaxNode::slot_name] on the parent instead.
+    pub fn name(&self) -> Option<&'static str> {
+        let (parent, index) = match self {
+            Self::Node(node) => (node.parent()?, node.index()),
+            Self::Token(token) => (token.parent()?, token.index()),
+            Self::Empty { .. } => return None,
+        };
+
+        parent.slot_name(index)
+    }
+}
+
 impl<L: Language> From<cursor::node::SyntaxSlot> for SyntaxSlot<L> {
     fn from(raw: cursor::node::SyntaxSlot) -> Self {
         match raw {
@@ -950,3 +1315,214 @@ impl<L: Language + 'static> From<SyntaxNode<L>> for AnySyntaxNode {
         }
     }
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use text_size::{TextRange, TextSize};
+
+    use super::{SyntaxNode, SyntaxToken};
+    use crate::raw_language::{RawLanguage, RawLanguageKind, parse};
+
+    /// Returns the first node of `kind` whose trimmed text is `text`.
+    fn find(
+        root: &SyntaxNode<RawLanguage>,
+        kind: RawLanguageKind,
+        text: &str,
+    ) -> SyntaxNode<RawLanguage> {
+        root.descendants()
+            .find(|node| node.kind() == kind && node.text_trimmed() == text)
+            .unwrap_or_else(|| panic!("no {kind:?} node with the text {text:?}"))
+    }
+
+    fn statement(text: &str) -> SyntaxNode<RawLanguage> {
+        parse(text)
+            .descendants()
+            .find(|node| node.kind() == RawLanguageKind::EXPRESSION_STATEMENT)
+            .unwrap()
+            .detach()
+    }
+
+    #[track_caller]
+    fn assert_text(root: &SyntaxNode<RawLanguage>, expected: &str) {
+        assert_eq!(root.text_with_trivia().to_string(), expected);
+        assert_eq!(root.validate(), vec![]);
+    }
+
+    #[test]
+    fn last_trailing_trivia() {
+        let root = parse("a; /* b */\n");
+        let statement = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "a;");
+
+        assert_eq!(statement.last_trailing_trivia().unwrap().text(), " /* b */");
+        assert_eq!(statement.first_leading_trivia().unwrap().text(), "");
+    }
+
+    #[test]
+    fn replace_node_preserving_trivia_keeps_comments() {
+        let root = parse("a;\n// leading\nb + c; // trailing\nd;");
+        let prev = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "b + c;");
+
+        let root = root
+            .clone()
+            .replace_node_preserving_trivia(prev, statement("/* x */ x;  "))
+            .unwrap();
+
+        assert_text(&root, "a;\n// leading\nx; // trailing\nd;");
+    }
+
+    #[test]
+    fn replace_node_preserving_trivia_with_tokenless_node() {
+        let root = parse("a;\n// leading\nb; // trailing\nc;");
+        let prev = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "b;");
+        let empty = SyntaxNode::new_detached(RawLanguageKind::BOGUS_STATEMENT, []);
+
+        let root = root.replace_node_preserving_trivia(prev, empty).unwrap();
+
+        assert_text(&root, "a; // trailing\n// leading\nc;");
+        let list = root.first_child().unwrap();
+        assert_eq!(list.slots().len(), 3);
+        assert_eq!(
+            list.element_in_slot(1).map(|element| element.kind()),
+            Some(RawLanguageKind::BOGUS_STATEMENT)
+        );
+    }
+
+    #[test]
+    fn remove_node_from_list() {
+        let root = parse("a;\nb;\nc;");
+        let b = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "b;");
+
+        let root = root.remove_node_preserving_comments(b).unwrap();
+
+        assert_text(&root, "a;\nc;");
+        assert_eq!(root.first_child().unwrap().slots().len(), 2);
+    }
+
+    #[test]
+    fn remove_node_from_list_keeps_comments() {
+        let root = parse("a;\n// leading\nb; // trailing\nc;");
+        let b = find(&root, RawLanguageKind::EXPRESSION_STATEMENT, "b;");
+
+        let root = root.remove_node_preserving_comments(b).unwrap();
+
+        assert_text(&root, "a; // trailing\n// leading\nc;");
+    }
+
+    #[test]
+    fn remove_node_from_separated_list() {
+        let remove = |text: &str, name: &str| {
+            let root = parse(text);
+            let node = find(&root, RawLanguageKind::NAME_EXPRESSION, name);
+            root.remove_node_preserving_comments(node).unwrap()
+        };
+
+        // Removes the element and the following separator
+        assert_text(&remove("f(a, b, c);", "a"), "f(b, c);");
+        assert_text(&remove("f(a, b, c);", "b"), "f(a, c);");
+        assert_text(&remove("f(a, b, c,);", "c"), "f(a, b, );");
+        // Removes the preceding separator of the last element
+        assert_text(&remove("f(a, b, c);", "c"), "f(a, b);");
+        assert_text(&remove("f(a);", "a"), "f();");
+    }
+
+    #[test]
+    fn remove_node_keeps_empty_separator_slot_pairs() {
+        use RawLanguageKind::*;
+
+        let name = |text: &str| find(&parse(text), NAME_EXPRESSION, text).detach();
+        let comma = || SyntaxToken::new_detached(COMMA, ",", [], []).into();
+
+        // `a b,` with a missing separator between `a` and `b`
+        let list = SyntaxNode::new_detached(
+            ARGUMENT_LIST,
+            [
+                Some(name("a").into()),
+                None,
+                Some(name("b").into()),
+                Some(comma()),
+            ],
+        );
+        let a = list.first_child().unwrap();
+
+        let list = list.remove_node_preserving_comments(a).unwrap();
+
+        assert_eq!(list.slots().len(), 2);
+        assert_eq!(list.text_with_trivia().to_string(), "b,");
+    }
+
+    #[test]
+    fn remove_node_without_neighbouring_token() {
+        let statement = statement("/* comment */ a");
+        let name = statement.first_child().unwrap();
+
+        assert_eq!(
+            statement.clone().remove_node_preserving_comments(name),
+            None
+        );
+
+        // Without comments, there's nothing to move
+        let statement = self::statement("a");
+        let name = statement.first_child().unwrap();
+        let removed = statement.remove_node_preserving_comments(name).unwrap();
+        assert_eq!(removed.text_with_trivia().to_string(), "");
+    }
+
+    #[test]
+    fn remove_node_that_is_not_a_descendant() {
+        let root = parse("a;");
+        let other = statement("b;");
+        let other_name = other.first_child().unwrap();
+
+        assert_eq!(root.remove_node_preserving_comments(other_name), None);
+    }
+
+    #[test]
+    fn covering_node_of_range_inside_token() {
+        let root = parse("call(argument)");
+        let range = TextRange::new(TextSize::from(6), TextSize::from(9));
+
+        let node = root.covering_node(range).unwrap();
+
+        assert_eq!(node.kind(), RawLanguageKind::NAME_EXPRESSION);
+        assert_eq!(node.text_trimmed(), "argument");
+    }
+
+    #[test]
+    fn covering_node_of_empty_range_inside_token() {
+        let root = parse("call(argument)");
+        let range = TextRange::empty(TextSize::from(2));
+
+        let node = root.covering_node(range).unwrap();
+
+        assert_eq!(node.kind(), RawLanguageKind::NAME_EXPRESSION);
+        assert_eq!(node.text_trimmed(), "call");
+    }
+
+    #[test]
+    fn alternate_debug_labels_slots() {
+        let root = parse("-;");
+
+        assert_eq!(
+            format!("{root:#?}"),
+            r#"0: ROOT@0..2
+ 0: statements: STATEMENT_LIST@0..2
+  0: EXPRESSION_STATEMENT@0..2
+   0: expression: UNARY_EXPRESSION@0..1
+    0: operator_token: MINUS&0..1 "-"[] []
+    1: argument: (empty)
+   1: semicolon_token: SEMICOLON&1..2 ";"[] []
+ 1: eof_token: EOF&2..2 ""[] []
+"#
+        );
+    }
+
+    #[test]
+    fn covering_node_of_range_spanning_tokens() {
+        let root = parse("a + b;");
+        let range = TextRange::new(TextSize::from(0), TextSize::from(3));
+
+        let node = root.covering_node(range).unwrap();
+
+        assert_eq!(node.kind(), RawLanguageKind::BINARY_EXPRESSION);
+    }
+}
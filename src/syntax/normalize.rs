@@ -0,0 +1,519 @@
+//! Normalization of the whitespace and newlines in a tree's trivia.
+//!
+//! [SyntaxNode::normalize_trivia] applies all enabled [NormalizeOptions] in a single pass over
+//! the tokens of a tree. Only tokens whose trivia changes are re-created, all other tokens and
+//! the nodes that don't contain a changed token are reused from the original tree.
+
+use text_size::{TextRange, TextSize};
+
+use crate::{
+    syntax::{
+        Language,
+        node::SyntaxNode,
+        rewriter::ReplaceTokens,
+        token::SyntaxToken,
+        trivia::{SyntaxTriviaPiece, TriviaPiece, TriviaPieceKind},
+    },
+    utility_types::Direction,
+};
+
+/// The normalizations applied by [SyntaxNode::normalize_trivia]. All normalizations are disabled by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    /// Converts `\r\n` newlines to `\n`.
+    pub lf_newlines: bool,
+    /// Removes whitespace that directly precedes a newline.
+    pub trim_trailing_whitespace: bool,
+    /// Collapses runs of more than `max_blank_lines` empty lines (lines containing only whitespace).
+    pub max_blank_lines: Option<usize>,
+    /// Converts tabs in the indentation of a line to spaces, aligning to multiples of the given tab width.
+    pub tabs_to_spaces: Option<usize>,
+}
+
+impl NormalizeOptions {
+    pub fn with_lf_newlines(mut self, yes: bool) -> Self {
+        self.lf_newlines = yes;
+        self
+    }
+
+    pub fn with_trim_trailing_whitespace(mut self, yes: bool) -> Self {
+        self.trim_trailing_whitespace = yes;
+        self
+    }
+
+    pub fn with_max_blank_lines(mut self, max: usize) -> Self {
+        self.max_blank_lines = Some(max);
+        self
+    }
+
+    pub fn with_tabs_to_spaces(mut self, tab_width: usize) -> Self {
+        self.tabs_to_spaces = Some(tab_width);
+        self
+    }
+}
+
+/// Replacement of the text in `range` with `new_text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The replaced range in the original text.
+    pub range: TextRange,
+    pub new_text: String,
+}
+
+/// The result of [SyntaxNode::normalize_trivia].
+#[derive(Debug, Clone)]
+pub struct NormalizedTrivia<L: Language> {
+    /// The normalized node. The same node as the input if nothing changed.
+    pub node: SyntaxNode<L>,
+    /// The edits applied to the text, sorted by offset and not overlapping.
+    pub edits: Vec<TextEdit>,
+}
+
+impl<L: Language> NormalizedTrivia<L> {
+    /// Returns `true` if the normalization changed any trivia.
+    pub fn has_changes(&self) -> bool {
+        !self.edits.is_empty()
+    }
+}
+
+impl<L: Language> SyntaxNode<L> {
+    /// Normalizes the whitespace and newline trivia of all tokens in this node.
+    ///
+    /// Comments, skipped trivia and the tokens' own text are never changed.
+    pub fn normalize_trivia(&self, options: &NormalizeOptions) -> NormalizedTrivia<L> {
+        let mut pieces = Vec::new();
+
+        for (token_index, token) in self.descendants_tokens(Direction::Next).enumerate() {
+            for piece in token.leading_trivia().pieces() {
+                pieces.push(Piece::trivia(token_index, Position::Leading, &piece));
+            }
+
+            pieces.push(Piece {
+                token: token_index,
+                position: Position::Token,
+                kind: None,
+                range: token.text_trimmed_range(),
+                new_text: None,
+            });
+
+            for piece in token.trailing_trivia().pieces() {
+                pieces.push(Piece::trivia(token_index, Position::Trailing, &piece));
+            }
+        }
+
+        let text = self.text_with_trivia().to_string();
+        let base = self.text_range_with_trivia().start();
+        Normalizer {
+            options,
+            text: &text,
+            base,
+        }
+        .normalize(&mut pieces);
+
+        let edits = collect_edits(&pieces, &text, base);
+        if edits.is_empty() {
+            return NormalizedTrivia {
+                node: self.clone(),
+                edits,
+            };
+        }
+
+        let node = rebuild_tokens(self, &pieces, &text, base).apply(self.clone());
+
+        NormalizedTrivia { node, edits }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Leading,
+    Token,
+    Trailing,
+}
+
+/// A trivia piece or the text of a token in the flattened token stream.
+#[derive(Debug)]
+struct Piece {
+    /// Index of the token the piece belongs to.
+    token: usize,
+    position: Position,
+    /// The trivia kind or `None` for the token's text.
+    kind: Option<TriviaPieceKind>,
+    range: TextRange,
+    /// The new text of the piece if it changed. An empty string removes the piece.
+    new_text: Option<String>,
+}
+
+impl Piece {
+    fn trivia<L: Language>(token: usize, position: Position, piece: &SyntaxTriviaPiece<L>) -> Self {
+        Self {
+            token,
+            position,
+            kind: Some(piece.kind()),
+            range: piece.text_range(),
+            new_text: None,
+        }
+    }
+
+    fn is(&self, kind: TriviaPieceKind) -> bool {
+        self.kind == Some(kind)
+    }
+
+    fn is_removed(&self) -> bool {
+        self.new_text.as_deref() == Some("")
+    }
+}
+
+struct Normalizer<'a> {
+    options: &'a NormalizeOptions,
+    text: &'a str,
+    base: TextSize,
+}
+
+impl Normalizer<'_> {
+    fn text_of(&self, piece: &Piece) -> &str {
+        &self.text[std::ops::Range::<usize>::from(piece.range - self.base)]
+    }
+
+    fn normalize(&self, pieces: &mut [Piece]) {
+        // Number of newlines since the last piece that isn't a newline or whitespace
+        let mut newlines_in_run = 0usize;
+        // The column of the current indentation or `None` if not inside a line's indentation
+        let mut indent_column = Some(0usize);
+
+        for index in 0..pieces.len() {
+            let piece = &pieces[index];
+
+            match piece.kind {
+                Some(TriviaPieceKind::Newline) => {
+                    newlines_in_run += 1;
+                    indent_column = Some(0);
+
+                    let collapse = self
+                        .options
+                        .max_blank_lines
+                        .is_some_and(|max| newlines_in_run > max + 1);
+
+                    if collapse {
+                        pieces[index].new_text = Some(String::new());
+
+                        // Remove the whitespace of the blank line too
+                        for previous in pieces[..index].iter_mut().rev() {
+                            if !previous.is(TriviaPieceKind::Whitespace) {
+                                break;
+                            }
+                            previous.new_text = Some(String::new());
+                        }
+                    } else if self.options.lf_newlines && self.text_of(&pieces[index]) == "\r\n" {
+                        pieces[index].new_text = Some(String::from("\n"));
+                    }
+                }
+                Some(TriviaPieceKind::Whitespace) => {
+                    let followed_by_newline = pieces[index + 1..]
+                        .iter()
+                        .find(|next| !next.is(TriviaPieceKind::Whitespace))
+                        .is_some_and(|next| next.is(TriviaPieceKind::Newline));
+
+                    if self.options.trim_trailing_whitespace && followed_by_newline {
+                        pieces[index].new_text = Some(String::new());
+                        continue;
+                    }
+
+                    if let (Some(tab_width), Some(column)) =
+                        (self.options.tabs_to_spaces, indent_column)
+                    {
+                        let text = self.text_of(&pieces[index]);
+                        let (expanded, end_column) = expand_tabs(text, column, tab_width);
+
+                        if expanded != text {
+                            pieces[index].new_text = Some(expanded);
+                        }
+
+                        indent_column = Some(end_column);
+                    }
+                }
+                // Empty tokens, like `EOF`, don't end a run of blank lines
+                None if piece.range.is_empty() => {}
+                _ => {
+                    newlines_in_run = 0;
+                    indent_column = None;
+                }
+            }
+        }
+    }
+}
+
+/// Replaces the tabs in `text` starting at `column` with spaces up to the next tab stop.
+/// Returns the expanded text and the column at its end.
+fn expand_tabs(text: &str, mut column: usize, tab_width: usize) -> (String, usize) {
+    let tab_width = tab_width.max(1);
+    let mut expanded = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if c == '\t' {
+            let spaces = tab_width - column % tab_width;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+
+    (expanded, column)
+}
+
+/// Merges the changes of adjacent pieces into edits on the original text.
+fn collect_edits(pieces: &[Piece], text: &str, base: TextSize) -> Vec<TextEdit> {
+    let mut edits: Vec<TextEdit> = Vec::new();
+
+    for piece in pieces {
+        let Some(new_text) = &piece.new_text else {
+            continue;
+        };
+
+        match edits.last_mut() {
+            Some(last) if last.range.end() == piece.range.start() => {
+                last.range = last.range.cover(piece.range);
+                last.new_text.push_str(new_text);
+            }
+            _ => edits.push(TextEdit {
+                range: piece.range,
+                new_text: new_text.clone(),
+            }),
+        }
+    }
+
+    // Drop edits that don't change the text
+    edits.retain(|edit| text[std::ops::Range::<usize>::from(edit.range - base)] != edit.new_text);
+
+    edits
+}
+
+/// Creates the new tokens for all tokens with changed trivia.
+fn rebuild_tokens<L: Language>(
+    root: &SyntaxNode<L>,
+    pieces: &[Piece],
+    text: &str,
+    base: TextSize,
+) -> ReplaceTokens<L> {
+    let mut replacements = ReplaceTokens::new();
+    let mut pieces = pieces.iter().peekable();
+
+    for (token_index, token) in root.descendants_tokens(Direction::Next).enumerate() {
+        let mut token_pieces = Vec::new();
+        while let Some(piece) = pieces.next_if(|piece| piece.token == token_index) {
+            token_pieces.push(piece);
+        }
+
+        if token_pieces.iter().all(|piece| piece.new_text.is_none()) {
+            continue;
+        }
+
+        let mut full_text = String::new();
+        let mut leading = Vec::new();
+        let mut trailing = Vec::new();
+
+        for piece in token_pieces {
+            let piece_text = piece
+                .new_text
+                .as_deref()
+                .unwrap_or_else(|| &text[std::ops::Range::<usize>::from(piece.range - base)]);
+
+            full_text.push_str(piece_text);
+
+            let Some(kind) = piece.kind else {
+                continue;
+            };
+
+            if piece.is_removed() {
+                continue;
+            }
+
+            let trivia = TriviaPiece::new(kind, TextSize::of(piece_text));
+            match piece.position {
+                Position::Leading => leading.push(trivia),
+                Position::Trailing => trailing.push(trivia),
+                Position::Token => {}
+            }
+        }
+
+        replacements.insert(
+            &token,
+            SyntaxToken::new_detached(token.kind(), &full_text, leading, trailing),
+        );
+    }
+
+    replacements
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::{RawLanguage, RawLanguageKind, RawSyntaxTreeBuilder, parse};
+
+    /// Applies `edits` to `text` from back to front so that the ranges stay valid.
+    fn apply_edits(text: &str, edits: &[TextEdit]) -> String {
+        let mut text = text.to_string();
+
+        for edit in edits.iter().rev() {
+            text.replace_range(std::ops::Range::<usize>::from(edit.range), &edit.new_text);
+        }
+
+        text
+    }
+
+    #[track_caller]
+    fn assert_normalized(
+        root: &SyntaxNode<RawLanguage>,
+        options: NormalizeOptions,
+        expected: &str,
+    ) {
+        let original = root.text_with_trivia().to_string();
+        let normalized = root.normalize_trivia(&options);
+
+        assert_eq!(normalized.node.text_with_trivia().to_string(), expected);
+        assert_eq!(apply_edits(&original, &normalized.edits), expected);
+        assert_eq!(normalized.has_changes(), original != expected);
+        assert_eq!(normalized.node.validate(), vec![]);
+
+        for pair in normalized.edits.windows(2) {
+            assert!(
+                pair[0].range.end() < pair[1].range.start(),
+                "{:?}",
+                normalized.edits
+            );
+        }
+    }
+
+    #[test]
+    fn max_blank_lines() {
+        let options = NormalizeOptions::default().with_max_blank_lines(1);
+
+        assert_normalized(&parse("a;\n\n\n\nb;"), options, "a;\n\nb;");
+        assert_normalized(&parse("a;\n\nb;"), options, "a;\n\nb;");
+        // Comments end a run of blank lines
+        assert_normalized(&parse("a;\n\n\n// c\n\n\nb;"), options, "a;\n\n// c\n\nb;");
+        assert_normalized(&parse("a;\n\n\n\n"), options, "a;\n\n");
+    }
+
+    #[test]
+    fn max_blank_lines_removes_whitespace_of_blank_lines() {
+        let options = NormalizeOptions::default().with_max_blank_lines(0);
+
+        assert_normalized(&parse("a;\n  \n\t\n\n  b;"), options, "a;\n  b;");
+    }
+
+    #[test]
+    fn max_blank_lines_across_token_boundaries() {
+        use crate::syntax::trivia::TriviaPiece;
+        use RawLanguageKind::*;
+
+        // `a;\n\n` with the newlines in the trailing trivia of `;` followed by `\n\nb;`
+        let mut builder = RawSyntaxTreeBuilder::new();
+        builder.start_node(ROOT);
+        builder.start_node(STATEMENT_LIST);
+        builder.start_node(EXPRESSION_STATEMENT);
+        builder.start_node(NAME_EXPRESSION);
+        builder.token(IDENT_TOKEN, "a");
+        builder.finish_node();
+        builder.token_with_trivia(
+            SEMICOLON,
+            ";\n\n",
+            &[],
+            &[TriviaPiece::newline(1), TriviaPiece::newline(1)],
+        );
+        builder.finish_node();
+        builder.start_node(EXPRESSION_STATEMENT);
+        builder.start_node(NAME_EXPRESSION);
+        builder.token_with_trivia(
+            IDENT_TOKEN,
+            "\n\nb",
+            &[TriviaPiece::newline(1), TriviaPiece::newline(1)],
+            &[],
+        );
+        builder.finish_node();
+        builder.token(SEMICOLON, ";");
+        builder.finish_node();
+        builder.finish_node();
+        builder.token(EOF, "");
+        builder.finish_node();
+        let root = builder.finish();
+
+        assert_normalized(
+            &root,
+            NormalizeOptions::default().with_max_blank_lines(1),
+            "a;\n\nb;",
+        );
+        assert_normalized(
+            &root,
+            NormalizeOptions::default().with_max_blank_lines(0),
+            "a;\nb;",
+        );
+    }
+
+    #[test]
+    fn tabs_to_spaces() {
+        let root = parse("\ta;\n  \tb;\n\t  \tc\t;");
+
+        assert_normalized(
+            &root,
+            NormalizeOptions::default().with_tabs_to_spaces(4),
+            "    a;\n    b;\n        c\t;",
+        );
+        assert_normalized(
+            &root,
+            NormalizeOptions::default().with_tabs_to_spaces(2),
+            "  a;\n    b;\n      c\t;",
+        );
+    }
+
+    #[test]
+    fn trim_trailing_whitespace() {
+        let root = parse("a;  \nb; // c  \n\t\n  d;\t");
+
+        assert_normalized(
+            &root,
+            NormalizeOptions::default().with_trim_trailing_whitespace(true),
+            "a;\nb; // c  \n\n  d;\t",
+        );
+    }
+
+    #[test]
+    fn lf_newlines() {
+        assert_normalized(
+            &parse("a;\r\nb;\r\n\r\n"),
+            NormalizeOptions::default().with_lf_newlines(true),
+            "a;\nb;\n\n",
+        );
+    }
+
+    #[test]
+    fn all_options() {
+        let options = NormalizeOptions::default()
+            .with_lf_newlines(true)
+            .with_trim_trailing_whitespace(true)
+            .with_max_blank_lines(1)
+            .with_tabs_to_spaces(4);
+
+        assert_normalized(
+            &parse("a; \r\n\r\n\t\r\n\r\n\tf(1,\t \r\n\t\t2);  "),
+            options,
+            "a;\n\n    f(1,\n        2);  ",
+        );
+    }
+
+    #[test]
+    fn unchanged_tree_is_reused() {
+        let root = parse("a;\n\n  b; // c\n");
+        let options = NormalizeOptions::default()
+            .with_trim_trailing_whitespace(true)
+            .with_max_blank_lines(1)
+            .with_tabs_to_spaces(4);
+
+        let normalized = root.normalize_trivia(&options);
+
+        assert!(!normalized.has_changes());
+        assert_eq!(normalized.node, root);
+    }
+}
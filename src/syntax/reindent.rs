@@ -0,0 +1,300 @@
+//! Re-indentation of a subtree that moves to a different nesting level.
+
+use text_size::TextSize;
+
+use crate::{
+    syntax::{
+        Language, SyntaxKind,
+        node::SyntaxNode,
+        rewriter::ReplaceTokens,
+        token::SyntaxToken,
+        trivia::{TriviaPiece, TriviaPieceKind},
+    },
+    utility_types::Direction,
+};
+
+/// The characters used for one level of indentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    /// One tab per level.
+    Tab,
+    /// The given number of spaces per level.
+    Space(u8),
+}
+
+impl IndentStyle {
+    /// Returns the text of a single indentation level.
+    pub fn unit(&self) -> String {
+        match self {
+            IndentStyle::Tab => String::from("\t"),
+            IndentStyle::Space(width) => " ".repeat(usize::from(*width)),
+        }
+    }
+
+    /// Re-indents the indentation `indent` of a line from `from_level` to `to_level`.
+    ///
+    /// Removes up to `from_level` leading levels (written in this style or as tabs) and prepends
+    /// `to_level` levels. Any remaining whitespace, for example for alignment, is kept.
+    pub fn reindent(&self, indent: &str, from_level: usize, to_level: usize) -> String {
+        let unit = self.unit();
+        let mut rest = indent;
+
+        for _ in 0..from_level {
+            match rest
+                .strip_prefix(unit.as_str())
+                .or_else(|| rest.strip_prefix('\t'))
+            {
+                Some(stripped) => rest = stripped,
+                None => break,
+            }
+        }
+
+        let mut reindented = unit.repeat(to_level);
+        reindented.push_str(rest);
+        reindented
+    }
+}
+
+impl<L: Language> SyntaxNode<L> {
+    /// Returns a copy of this node with all lines moved from indentation level `from_level`
+    /// to `to_level`.
+    ///
+    /// Re-indents the whitespace following every newline in the leading trivia of the node's
+    /// tokens, the continuation lines of multiline comments, and the continuation lines of
+    /// multiline tokens except for string literals (see [SyntaxKind::is_string_literal]).
+    /// The indentation of the node's first line is only changed if the node's leading trivia contains a newline.
+    /// Blank lines are left untouched.
+    #[must_use = "syntax elements are immutable, the result of update methods must be propagated to have any effect"]
+    pub fn reindent(&self, from_level: usize, to_level: usize, style: IndentStyle) -> Self {
+        if from_level == to_level {
+            return self.clone();
+        }
+
+        let mut replacements = ReplaceTokens::new();
+
+        for token in self.descendants_tokens(Direction::Next) {
+            let mut changed = false;
+            let mut full_text = String::new();
+            let mut leading = Vec::new();
+            let mut pieces = token.leading_trivia().pieces().peekable();
+
+            while let Some(piece) = pieces.next() {
+                let text = match piece.kind() {
+                    TriviaPieceKind::MultiLineComment => {
+                        reindent_continuation_lines(piece.text(), from_level, to_level, style)
+                    }
+                    _ => String::from(piece.text()),
+                };
+                changed |= text != piece.text();
+                full_text.push_str(&text);
+                leading.push(TriviaPiece::new(piece.kind(), TextSize::of(text.as_str())));
+
+                if !piece.is_newline() {
+                    continue;
+                }
+
+                // The indentation of the following line: the whitespace piece after the newline, if any
+                let indent = pieces.next_if(|next| next.is_whitespace());
+                let indent_text = indent.as_ref().map_or("", |indent| indent.text());
+
+                // Keep blank lines as is
+                let is_blank = pieces.peek().is_some_and(|next| next.is_newline())
+                    || (pieces.peek().is_none() && token.text_trimmed().is_empty());
+                let reindented = if is_blank {
+                    String::from(indent_text)
+                } else {
+                    style.reindent(indent_text, from_level, to_level)
+                };
+
+                changed |= reindented != indent_text;
+
+                if !reindented.is_empty() {
+                    full_text.push_str(&reindented);
+                    leading.push(TriviaPiece::whitespace(TextSize::of(reindented.as_str())));
+                }
+            }
+
+            let token_text = if token.kind().is_string_literal() {
+                String::from(token.text_trimmed())
+            } else {
+                reindent_continuation_lines(token.text_trimmed(), from_level, to_level, style)
+            };
+            changed |= token_text != token.text_trimmed();
+            full_text.push_str(&token_text);
+
+            if !changed {
+                continue;
+            }
+
+            let trailing: Vec<_> = token
+                .trailing_trivia()
+                .pieces()
+                .map(|piece| {
+                    full_text.push_str(piece.text());
+                    TriviaPiece::new(piece.kind(), piece.text_len())
+                })
+                .collect();
+
+            replacements.insert(
+                &token,
+                SyntaxToken::new_detached(token.kind(), &full_text, leading, trailing),
+            );
+        }
+
+        replacements.apply(self.clone())
+    }
+}
+
+/// Re-indents all lines of `text` except the first.
+fn reindent_continuation_lines(
+    text: &str,
+    from_level: usize,
+    to_level: usize,
+    style: IndentStyle,
+) -> String {
+    let mut lines = text.split('\n');
+    let mut reindented = String::from(lines.next().unwrap_or_default());
+
+    for line in lines {
+        reindented.push('\n');
+
+        let content = line.trim_start_matches([' ', '\t']);
+
+        if content.is_empty() || content == "\r" {
+            reindented.push_str(line);
+        } else {
+            let indent = &line[..line.len() - content.len()];
+            reindented.push_str(&style.reindent(indent, from_level, to_level));
+            reindented.push_str(content);
+        }
+    }
+
+    reindented
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::{RawLanguage, RawLanguageKind, parse};
+
+    #[track_caller]
+    fn assert_reindented(
+        text: &str,
+        from_level: usize,
+        to_level: usize,
+        style: IndentStyle,
+        expected: &str,
+    ) {
+        let root = parse(text).reindent(from_level, to_level, style);
+
+        assert_eq!(root.text_with_trivia().to_string(), expected);
+        assert_eq!(root.validate(), vec![]);
+    }
+
+    fn statement(root: &SyntaxNode<RawLanguage>, index: usize) -> SyntaxNode<RawLanguage> {
+        root.descendants()
+            .filter(|node| node.kind() == RawLanguageKind::EXPRESSION_STATEMENT)
+            .nth(index)
+            .unwrap()
+    }
+
+    #[test]
+    fn indent_style_reindent() {
+        let spaces = IndentStyle::Space(2);
+
+        assert_eq!(spaces.reindent("    ", 2, 1), "  ");
+        assert_eq!(spaces.reindent("  \t", 2, 0), "");
+        assert_eq!(spaces.reindent("  ", 0, 2), "      ");
+        // Alignment that isn't a full level is kept
+        assert_eq!(spaces.reindent("     ", 2, 0), " ");
+        assert_eq!(spaces.reindent(" ", 1, 0), " ");
+        assert_eq!(IndentStyle::Tab.reindent("\t\t", 2, 1), "\t");
+        assert_eq!(IndentStyle::Tab.reindent("\t  ", 1, 2), "\t\t  ");
+    }
+
+    #[test]
+    fn indent() {
+        assert_reindented(
+            "a;\nf(1,\n  2);",
+            0,
+            1,
+            IndentStyle::Space(2),
+            "a;\n  f(1,\n    2);",
+        );
+        assert_reindented("a;\n\tb;", 1, 2, IndentStyle::Tab, "a;\n\t\tb;");
+    }
+
+    #[test]
+    fn dedent() {
+        assert_reindented(
+            "  a;\n    b;\n  c;",
+            1,
+            0,
+            IndentStyle::Space(2),
+            "  a;\n  b;\nc;",
+        );
+        // Tabs are stripped as levels but the new levels use the style
+        assert_reindented("a;\n\t\tb;", 2, 1, IndentStyle::Space(4), "a;\n    b;");
+    }
+
+    #[test]
+    fn first_line_is_only_reindented_after_a_newline() {
+        let root = parse("a;\n  b;");
+        let b = statement(&root, 1);
+
+        assert_eq!(
+            b.reindent(1, 0, IndentStyle::Space(2))
+                .text_with_trivia()
+                .to_string(),
+            "\nb;"
+        );
+
+        let a = statement(&root, 0);
+        assert_eq!(
+            a.reindent(0, 1, IndentStyle::Space(2))
+                .text_with_trivia()
+                .to_string(),
+            "a;"
+        );
+    }
+
+    #[test]
+    fn blank_lines_are_preserved() {
+        assert_reindented(
+            "a;\n\n  \nb;\n\n",
+            0,
+            1,
+            IndentStyle::Space(4),
+            "a;\n\n  \n    b;\n\n",
+        );
+    }
+
+    #[test]
+    fn multiline_comments() {
+        assert_reindented(
+            "a;\n/* x\n   y\n\n*/\nb;",
+            0,
+            1,
+            IndentStyle::Space(2),
+            "a;\n  /* x\n     y\n\n  */\n  b;",
+        );
+    }
+
+    #[test]
+    fn string_literals_are_left_untouched() {
+        assert_reindented(
+            "a;\nf(\"x\n  y\",\n  2);",
+            0,
+            1,
+            IndentStyle::Space(2),
+            "a;\n  f(\"x\n  y\",\n    2);",
+        );
+    }
+
+    #[test]
+    fn same_level_returns_node() {
+        let root = parse("a;\n  b;");
+
+        assert_eq!(root.reindent(1, 1, IndentStyle::Tab), root);
+    }
+}
@@ -1,7 +1,11 @@
 //! A module that exports utilities to rewrite a syntax trees.
 
+use rustc_hash::FxHashMap;
+use text_size::TextRange;
+
 use crate::syntax::{
     Language,
+    element::SyntaxElementKey,
     node::{SyntaxNode, SyntaxSlot},
     token::SyntaxToken,
 };
@@ -21,17 +25,35 @@ use crate::syntax::{
 /// # Examples
 ///
 /// Implementation of a rewritten that replaces all literal expression nodes that contain a number token
-/// with a bogus node.
+/// with a bogus node. The example requires the `testing` feature for the `raw_language` module.
+///
+#[cfg_attr(feature = "testing", doc = "```")]
+#[cfg_attr(not(feature = "testing"), doc = "```ignore")]
+/// use mini_rowan::raw_language::{RawLanguage, RawLanguageKind, parse};
+/// use mini_rowan::syntax::node::{SyntaxNode, SyntaxSlot};
+/// use mini_rowan::syntax::rewriter::{SyntaxRewriter, VisitNodeSignal};
 ///
-/// ```
 /// struct ReplaceNumberLiteralRewriter;
 ///
 /// impl SyntaxRewriter for ReplaceNumberLiteralRewriter {
-///
 ///     type Language = RawLanguage;
+///
+///     fn visit_node(&mut self, node: SyntaxNode<RawLanguage>) -> VisitNodeSignal<RawLanguage> {
+///         if node.kind() == RawLanguageKind::LITERAL_EXPRESSION {
+///             let bogus = SyntaxNode::new_detached(
+///                 RawLanguageKind::BOGUS_EXPRESSION,
+///                 node.slots().map(SyntaxSlot::into_syntax_element),
+///             );
+///             VisitNodeSignal::Replace(bogus)
+///         } else {
+///             VisitNodeSignal::Traverse(node)
+///         }
+///     }
 /// }
+///
+/// let root = ReplaceNumberLiteralRewriter.transform(parse("a + 1"));
+/// assert_eq!(root.text_with_trivia().to_string(), "a + 1");
 /// ```
-
 pub trait SyntaxRewriter {
     type Language: Language;
 
@@ -108,3 +130,68 @@ where
 
     parent
 }
+
+/// Rewriter that replaces tokens by their key.
+///
+/// Only traverses into nodes that contain a replaced token and reuses all other nodes as is.
+pub(crate) struct ReplaceTokens<L: Language> {
+    replacements: FxHashMap<SyntaxElementKey, SyntaxToken<L>>,
+    /// The ranges of the replaced tokens.
+    ranges: Vec<TextRange>,
+}
+
+impl<L: Language> ReplaceTokens<L> {
+    pub(crate) fn new() -> Self {
+        Self {
+            replacements: FxHashMap::default(),
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Replaces `original` with `replacement`.
+    pub(crate) fn insert(&mut self, original: &SyntaxToken<L>, replacement: SyntaxToken<L>) {
+        self.ranges.push(original.text_range());
+        self.replacements.insert(original.key(), replacement);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.replacements.is_empty()
+    }
+
+    /// Applies the replacements to `root`.
+    pub(crate) fn apply(mut self, root: SyntaxNode<L>) -> SyntaxNode<L> {
+        if self.is_empty() {
+            return root;
+        }
+
+        self.ranges.sort_by_key(|range| range.start());
+        self.transform(root)
+    }
+}
+
+impl<L: Language> SyntaxRewriter for ReplaceTokens<L> {
+    type Language = L;
+
+    fn visit_node(&mut self, node: SyntaxNode<L>) -> VisitNodeSignal<L> {
+        let range = node.text_range_with_trivia();
+        let first_after = self
+            .ranges
+            .partition_point(|changed| changed.end() <= range.start());
+
+        let contains_change = self
+            .ranges
+            .get(first_after)
+            .is_some_and(|changed| changed.start() < range.end());
+
+        if contains_change {
+            VisitNodeSignal::Traverse(node)
+        } else {
+            // Reuse the node as is
+            VisitNodeSignal::Replace(node)
+        }
+    }
+
+    fn visit_token(&mut self, token: SyntaxToken<L>) -> SyntaxToken<L> {
+        self.replacements.remove(&token.key()).unwrap_or(token)
+    }
+}
@@ -0,0 +1,460 @@
+//! Inspection and repair of the trivia that the parser skipped during error recovery.
+//!
+//! Parsers attach tokens that they can't place anywhere in the tree as [Skipped](crate::syntax::trivia::TriviaPieceKind::Skipped)
+//! trivia to the next token. [SyntaxNode::skipped_trivia] enumerates these pieces and
+//! [SyntaxNode::promote_skipped_trivia] turns them back into tokens inside of bogus nodes.
+
+use text_size::TextRange;
+
+use crate::{
+    syntax::{
+        Language, SyntaxKind,
+        element::SyntaxElement,
+        node::{SyntaxNode, SyntaxSlot},
+        token::SyntaxToken,
+        trivia::{SyntaxTriviaPiece, SyntaxTriviaPieceSkipped, TriviaPiece, TriviaPosition},
+    },
+    utility_types::Direction,
+};
+
+/// A skipped trivia piece returned by [SyntaxNode::skipped_trivia].
+#[derive(Debug, Clone)]
+pub struct SkippedTrivia<L: Language> {
+    piece: SyntaxTriviaPieceSkipped<L>,
+    token: SyntaxToken<L>,
+    position: TriviaPosition,
+}
+
+impl<L: Language> SkippedTrivia<L> {
+    pub fn piece(&self) -> &SyntaxTriviaPieceSkipped<L> {
+        &self.piece
+    }
+
+    /// Returns the skipped text.
+    pub fn text(&self) -> &str {
+        self.piece.text()
+    }
+
+    /// Returns the absolute range of the skipped text.
+    pub fn text_range(&self) -> TextRange {
+        self.piece.text_range()
+    }
+
+    /// Returns the token that owns the skipped piece.
+    pub fn token(&self) -> &SyntaxToken<L> {
+        &self.token
+    }
+
+    /// Returns whether the piece is part of the token's leading or trailing trivia.
+    pub fn position(&self) -> TriviaPosition {
+        self.position
+    }
+}
+
+impl<L: Language> SyntaxNode<L> {
+    /// Returns all skipped trivia pieces of the tokens in this node in source order.
+    pub fn skipped_trivia(&self) -> impl Iterator<Item = SkippedTrivia<L>> + use<L> {
+        self.descendants_tokens(Direction::Next).flat_map(|token| {
+            let leading = token
+                .leading_trivia()
+                .pieces()
+                .map(|piece| (piece, TriviaPosition::Leading));
+            let trailing = token
+                .trailing_trivia()
+                .pieces()
+                .map(|piece| (piece, TriviaPosition::Trailing));
+
+            leading
+                .chain(trailing)
+                .filter_map(|(piece, position)| {
+                    Some(SkippedTrivia {
+                        piece: piece.as_skipped()?,
+                        token: token.clone(),
+                        position,
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// Returns a copy of this node where every skipped trivia piece is promoted to a token.
+    ///
+    /// `token_kind` returns the kind of the token for a skipped piece, for example, by re-lexing
+    /// its text. The promoted tokens are inserted next to the token that owned the skipped piece:
+    /// before it for leading and after it for trailing trivia. The trivia between two skipped
+    /// pieces becomes the trailing trivia of the preceding or the leading trivia of the following token,
+    /// depending on whether the pieces were part of the leading or the trailing trivia.
+    ///
+    /// Every node that gains promoted tokens is converted to its bogus kind (see [SyntaxKind::to_bogus])
+    /// except root nodes, which keep their kind and slots. In a root node, the promoted tokens and
+    /// the token that owned the skipped pieces are wrapped in a bogus node that takes the token's slot.
+    /// The tokens promoted from the trivia of the root's `EOF` token are wrapped in a bogus node that
+    /// is appended to the last node preceding `EOF` instead, which converts that node to its bogus
+    /// kind unless it is a list. The root is converted to its bogus kind if there's no such node.
+    ///
+    /// Returns this node if it doesn't contain any skipped trivia.
+    #[must_use = "syntax elements are immutable, the result of update methods must be propagated to have any effect"]
+    pub fn promote_skipped_trivia<F>(&self, mut token_kind: F) -> Self
+    where
+        F: FnMut(&SyntaxTriviaPieceSkipped<L>) -> L::Kind,
+    {
+        promote_in_node(self, &mut token_kind).unwrap_or_else(|| self.clone())
+    }
+}
+
+/// Returns the node with all skipped trivia promoted or `None` if the node has no skipped trivia.
+fn promote_in_node<L, F>(node: &SyntaxNode<L>, token_kind: &mut F) -> Option<SyntaxNode<L>>
+where
+    L: Language,
+    F: FnMut(&SyntaxTriviaPieceSkipped<L>) -> L::Kind,
+{
+    let mut slots: Vec<Option<SyntaxElement<L>>> = Vec::new();
+    let mut changed = false;
+    let mut has_promoted_tokens = false;
+    // The slot of the root's `EOF` token and the bogus node with the tokens promoted from its trivia
+    let mut before_eof = None;
+
+    for slot in node.slots() {
+        match slot {
+            SyntaxSlot::Node(child) => match promote_in_node(&child, token_kind) {
+                Some(promoted) => {
+                    changed = true;
+                    slots.push(Some(promoted.into()));
+                }
+                None => slots.push(Some(child.into())),
+            },
+            SyntaxSlot::Token(token) => {
+                let has_skipped =
+                    token.leading_trivia().has_skipped() || token.trailing_trivia().has_skipped();
+
+                if !has_skipped {
+                    slots.push(Some(token.into()));
+                    continue;
+                }
+
+                changed = true;
+                let mut promoted = promote_token(&token, token_kind);
+
+                if !node.kind().is_root() {
+                    has_promoted_tokens = true;
+                    slots.extend(promoted.into_iter().map(Some));
+                } else if token.kind() == L::Kind::EOF {
+                    // `EOF` must remain the last token of the root. Its text is empty, which is why
+                    // moving all promoted tokens in front of it keeps the text of the tree.
+                    let index = token
+                        .leading_trivia()
+                        .pieces()
+                        .filter(|piece| piece.is_skipped())
+                        .count();
+                    let eof = promoted.remove(index);
+
+                    let bogus = SyntaxNode::new_detached(
+                        token.kind().to_bogus(),
+                        promoted.into_iter().map(Some),
+                    );
+                    before_eof = Some((slots.len(), bogus));
+                    slots.push(Some(eof));
+                } else {
+                    let bogus = SyntaxNode::new_detached(
+                        token.kind().to_bogus(),
+                        promoted.into_iter().map(Some),
+                    );
+                    slots.push(Some(bogus.into()));
+                }
+            }
+            SyntaxSlot::Empty { .. } => slots.push(None),
+        }
+    }
+
+    if !changed {
+        return None;
+    }
+
+    let mut kind = if has_promoted_tokens {
+        node.kind().to_bogus()
+    } else {
+        node.kind()
+    };
+
+    if let Some((eof_slot, bogus)) = before_eof {
+        // Add the promoted tokens to the last node preceding `EOF`, usually the root's list
+        let last_node = slots[..eof_slot]
+            .iter_mut()
+            .rev()
+            .find_map(|slot| match slot {
+                Some(SyntaxElement::Node(node)) => Some(node),
+                _ => None,
+            });
+
+        match last_node {
+            Some(last_node) => *last_node = append_child(last_node, bogus),
+            None => {
+                kind = kind.to_bogus();
+                slots.insert(eof_slot, Some(bogus.into()));
+            }
+        }
+    }
+
+    Some(SyntaxNode::new_detached(kind, slots))
+}
+
+/// Appends `child` to the elements of the list `node`, or to the slots of `node` converted to its
+/// bogus kind if `node` isn't a list.
+fn append_child<L: Language>(node: &SyntaxNode<L>, child: SyntaxNode<L>) -> SyntaxNode<L> {
+    let kind = if node.kind().is_list() {
+        node.kind()
+    } else {
+        node.kind().to_bogus()
+    };
+
+    let slots: Vec<_> = node
+        .slots()
+        .map(SyntaxSlot::into_syntax_element)
+        .chain(std::iter::once(Some(child.into())))
+        .collect();
+
+    SyntaxNode::new_detached(kind, slots)
+}
+
+/// Splits `token` into the promoted tokens for its skipped pieces and the token itself.
+fn promote_token<L, F>(token: &SyntaxToken<L>, token_kind: &mut F) -> Vec<SyntaxElement<L>>
+where
+    L: Language,
+    F: FnMut(&SyntaxTriviaPieceSkipped<L>) -> L::Kind,
+{
+    let mut tokens = Vec::new();
+    // The trivia pieces preceding the next token
+    let mut pending: Vec<SyntaxTriviaPiece<L>> = Vec::new();
+
+    for piece in token.leading_trivia().pieces() {
+        match piece.as_skipped() {
+            Some(skipped) => {
+                tokens.push(new_token(
+                    token_kind(&skipped),
+                    std::mem::take(&mut pending),
+                    skipped.text(),
+                    Vec::new(),
+                ));
+            }
+            None => pending.push(piece),
+        }
+    }
+
+    // The token itself, with the remaining leading trivia and its trailing trivia up to the first skipped piece
+    let mut trailing = token.trailing_trivia().pieces().peekable();
+    let mut own_trailing = Vec::new();
+    while let Some(piece) = trailing.next_if(|piece| !piece.is_skipped()) {
+        own_trailing.push(piece);
+    }
+
+    tokens.push(new_token(
+        token.kind(),
+        pending,
+        token.text_trimmed(),
+        own_trailing,
+    ));
+
+    // The skipped trailing pieces, each owning the trivia up to the next skipped piece
+    while let Some(piece) = trailing.next() {
+        let Some(skipped) = piece.as_skipped() else {
+            continue;
+        };
+
+        let mut own_trailing = Vec::new();
+        while let Some(piece) = trailing.next_if(|piece| !piece.is_skipped()) {
+            own_trailing.push(piece);
+        }
+
+        tokens.push(new_token(
+            token_kind(&skipped),
+            Vec::new(),
+            skipped.text(),
+            own_trailing,
+        ));
+    }
+
+    tokens
+}
+
+fn new_token<L: Language>(
+    kind: L::Kind,
+    leading: Vec<SyntaxTriviaPiece<L>>,
+    token_text: &str,
+    trailing: Vec<SyntaxTriviaPiece<L>>,
+) -> SyntaxElement<L> {
+    let mut text = String::new();
+    leading.iter().for_each(|piece| text.push_str(piece.text()));
+    text.push_str(token_text);
+    trailing
+        .iter()
+        .for_each(|piece| text.push_str(piece.text()));
+
+    let leading: Vec<TriviaPiece> = leading
+        .into_iter()
+        .map(SyntaxTriviaPiece::into_raw_piece)
+        .collect();
+    let trailing: Vec<TriviaPiece> = trailing
+        .into_iter()
+        .map(SyntaxTriviaPiece::into_raw_piece)
+        .collect();
+
+    SyntaxToken::new_detached(kind, &text, leading, trailing).into()
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::{
+        raw_language::{RawLanguage, RawLanguageKind, RawSyntaxTreeBuilder, parse},
+        syntax::trivia::TriviaPieceKind,
+    };
+
+    fn promote(root: &SyntaxNode<RawLanguage>) -> SyntaxNode<RawLanguage> {
+        let promoted = root.promote_skipped_trivia(|_| RawLanguageKind::BOGUS);
+
+        assert_eq!(promoted.text_with_trivia(), root.text_with_trivia());
+        assert_eq!(promoted.validate(), vec![]);
+        assert_eq!(promoted.skipped_trivia().count(), 0);
+        promoted
+    }
+
+    fn promoted_tokens(root: &SyntaxNode<RawLanguage>) -> Vec<String> {
+        root.descendants_tokens(Direction::Next)
+            .filter(|token| token.kind() == RawLanguageKind::BOGUS)
+            .map(|token| token.text().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn skipped_trivia_in_source_order() {
+        let root = parse("a $ + b;\n#");
+
+        let skipped: Vec<_> = root
+            .skipped_trivia()
+            .map(|skipped| {
+                (
+                    skipped.text().to_string(),
+                    skipped.token().kind(),
+                    skipped.position(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            skipped,
+            vec![
+                (
+                    "$".to_string(),
+                    RawLanguageKind::PLUS,
+                    TriviaPosition::Leading
+                ),
+                (
+                    "#".to_string(),
+                    RawLanguageKind::EOF,
+                    TriviaPosition::Leading
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn promote_without_skipped_trivia_returns_the_node() {
+        let root = parse("a + b;");
+
+        assert_eq!(promote(&root), root);
+    }
+
+    #[test]
+    fn promote_leading_skipped_trivia() {
+        let root = parse("a $ @+ b;");
+        let promoted = promote(&root);
+
+        assert_eq!(promoted_tokens(&promoted), vec!["$", " @"]);
+        assert!(
+            promoted
+                .descendants()
+                .any(|node| node.kind() == RawLanguageKind::BOGUS_EXPRESSION)
+        );
+        assert!(
+            !promoted
+                .descendants()
+                .any(|node| node.kind() == RawLanguageKind::BINARY_EXPRESSION)
+        );
+    }
+
+    #[test]
+    fn promote_trailing_skipped_trivia() {
+        use RawLanguageKind::*;
+
+        let mut builder = RawSyntaxTreeBuilder::new();
+        builder.start_node(ROOT);
+        builder.start_node(STATEMENT_LIST);
+        builder.start_node(EXPRESSION_STATEMENT);
+        builder.start_node(NAME_EXPRESSION);
+        builder.token(IDENT_TOKEN, "a");
+        builder.finish_node();
+        builder.token_with_trivia(
+            SEMICOLON,
+            "; $ // c",
+            &[],
+            &[
+                TriviaPiece::whitespace(1),
+                TriviaPiece::new(TriviaPieceKind::Skipped, 1),
+                TriviaPiece::whitespace(1),
+                TriviaPiece::single_line_comment(4),
+            ],
+        );
+        builder.finish_node();
+        builder.finish_node();
+        builder.token(EOF, "");
+        builder.finish_node();
+        let root = builder.finish();
+
+        assert_eq!(
+            root.skipped_trivia()
+                .map(|skipped| skipped.position())
+                .collect::<Vec<_>>(),
+            vec![TriviaPosition::Trailing]
+        );
+
+        let promoted = promote(&root);
+
+        assert_eq!(promoted_tokens(&promoted), vec!["$ // c"]);
+        let semicolon = promoted
+            .descendants_tokens(Direction::Next)
+            .find(|token| token.kind() == SEMICOLON)
+            .unwrap();
+        assert_eq!(semicolon.text(), "; ");
+        assert_eq!(semicolon.parent().unwrap().kind(), BOGUS_STATEMENT);
+    }
+
+    #[test]
+    fn promote_skipped_trivia_of_eof() {
+        let root = parse("a;\n$ #");
+        let promoted = promote(&root);
+
+        assert_eq!(promoted_tokens(&promoted), vec!["\n$", " #"]);
+        assert_eq!(promoted.kind(), RawLanguageKind::ROOT);
+
+        // The promoted tokens are appended to the statement list
+        let list = promoted.first_child().unwrap();
+        assert_eq!(list.kind(), RawLanguageKind::STATEMENT_LIST);
+        assert_eq!(
+            list.children().map(|node| node.kind()).collect::<Vec<_>>(),
+            vec![
+                RawLanguageKind::EXPRESSION_STATEMENT,
+                RawLanguageKind::BOGUS
+            ]
+        );
+        assert_eq!(promoted.last_token().unwrap().kind(), RawLanguageKind::EOF);
+    }
+
+    #[test]
+    fn promote_skipped_trivia_of_eof_in_empty_file() {
+        let root = parse("$");
+        let promoted = promote(&root);
+
+        assert_eq!(promoted_tokens(&promoted), vec!["$"]);
+        assert_eq!(promoted.last_token().unwrap().kind(), RawLanguageKind::EOF);
+    }
+}
@@ -0,0 +1,392 @@
+//! Detection of a file's line ending and indentation conventions.
+//!
+//! Rewrites that create new trivia should use the conventions of the file they edit.
+//! [SourceStyle::detect] determines them from the `Newline` and `Whitespace` trivia of a tree.
+
+use crate::{
+    syntax::{Language, node::SyntaxNode, reindent::IndentStyle},
+    utility_types::Direction,
+};
+
+/// A line ending sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    Crlf,
+    /// `\r`
+    Cr,
+}
+
+impl LineEnding {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+/// The number of occurrences of each [LineEnding] in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LineEndingCounts {
+    pub lf: usize,
+    pub crlf: usize,
+    pub cr: usize,
+}
+
+impl LineEndingCounts {
+    pub fn total(&self) -> usize {
+        self.lf + self.crlf + self.cr
+    }
+
+    /// Returns the most frequent line ending, preferring `\n` over `\r\n` over `\r` on ties.
+    /// Returns `None` if there are no line endings.
+    pub fn dominant(&self) -> Option<LineEnding> {
+        [
+            (LineEnding::Lf, self.lf),
+            (LineEnding::Crlf, self.crlf),
+            (LineEnding::Cr, self.cr),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        // `max_by_key` returns the last maximum, reverse to prefer the first
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(ending, _)| ending)
+    }
+
+    fn add(&mut self, text: &str) {
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' if chars.next_if_eq(&'\n').is_some() => self.crlf += 1,
+                '\r' => self.cr += 1,
+                '\n' => self.lf += 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The line endings used by a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineEndingStyle {
+    /// All lines end with `\n`.
+    Lf,
+    /// All lines end with `\r\n`.
+    Crlf,
+    /// All lines end with `\r`.
+    Cr,
+    /// The file uses different line endings.
+    Mixed(LineEndingCounts),
+}
+
+impl LineEndingStyle {
+    /// Returns the line ending used by the file or the most frequent one if the line endings are mixed.
+    pub fn dominant(&self) -> LineEnding {
+        match self {
+            LineEndingStyle::Lf => LineEnding::Lf,
+            LineEndingStyle::Crlf => LineEnding::Crlf,
+            LineEndingStyle::Cr => LineEnding::Cr,
+            LineEndingStyle::Mixed(counts) => counts.dominant().unwrap_or(LineEnding::Lf),
+        }
+    }
+
+    pub fn is_mixed(&self) -> bool {
+        matches!(self, LineEndingStyle::Mixed(_))
+    }
+
+    fn from_counts(counts: LineEndingCounts) -> Option<Self> {
+        let style = match counts {
+            LineEndingCounts {
+                lf: 0,
+                crlf: 0,
+                cr: 0,
+            } => return None,
+            LineEndingCounts { crlf: 0, cr: 0, .. } => LineEndingStyle::Lf,
+            LineEndingCounts { lf: 0, cr: 0, .. } => LineEndingStyle::Crlf,
+            LineEndingCounts { lf: 0, crlf: 0, .. } => LineEndingStyle::Cr,
+            counts => LineEndingStyle::Mixed(counts),
+        };
+
+        Some(style)
+    }
+}
+
+/// The line ending and indentation conventions of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceStyle {
+    line_ending: Option<LineEndingStyle>,
+    indent_style: Option<IndentStyle>,
+    ends_with_newline: bool,
+}
+
+impl SourceStyle {
+    /// Detects the conventions of the file rooted at `root` from its `Newline` and `Whitespace` trivia.
+    ///
+    /// The indentation style is the one used by the majority of indented lines. The width of
+    /// space indentation is the most common difference between the indentation of consecutive lines.
+    /// Line breaks inside of comments or tokens aren't considered.
+    pub fn detect<L: Language>(root: &SyntaxNode<L>) -> Self {
+        let mut detector = IndentDetector::default();
+        let mut line_endings = LineEndingCounts::default();
+        let mut ends_with_newline = false;
+
+        for token in root.descendants_tokens(Direction::Next) {
+            let leading = token.leading_trivia();
+            let trailing = token.trailing_trivia();
+            // `None` stands for the token's text
+            let token_text = (!token.text_trimmed().is_empty()).then_some(None);
+
+            let pieces = leading
+                .pieces()
+                .map(Some)
+                .chain(token_text)
+                .chain(trailing.pieces().map(Some));
+
+            for piece in pieces {
+                match piece {
+                    Some(piece) if piece.is_newline() => {
+                        line_endings.add(piece.text());
+                        detector.newline();
+                        ends_with_newline = true;
+                    }
+                    Some(piece) if piece.is_whitespace() => detector.whitespace(piece.text()),
+                    _ => {
+                        detector.content();
+                        ends_with_newline = false;
+                    }
+                }
+            }
+        }
+
+        Self {
+            line_ending: LineEndingStyle::from_counts(line_endings),
+            indent_style: detector.finish(),
+            ends_with_newline,
+        }
+    }
+
+    /// Returns the line endings of the file or `None` if the file has a single line.
+    pub fn line_ending(&self) -> Option<LineEndingStyle> {
+        self.line_ending
+    }
+
+    /// Returns the indentation of the file or `None` if no line is indented.
+    pub fn indent_style(&self) -> Option<IndentStyle> {
+        self.indent_style
+    }
+
+    /// Returns `true` if the last trivia of the file is a line break.
+    pub fn ends_with_newline(&self) -> bool {
+        self.ends_with_newline
+    }
+
+    /// Returns the line ending that new trivia should use: the file's dominant line ending or `\n`
+    /// for single line files.
+    pub fn newline(&self) -> &'static str {
+        self.line_ending
+            .map_or(LineEnding::Lf, |style| style.dominant())
+            .as_str()
+    }
+}
+
+#[derive(Debug)]
+struct IndentDetector {
+    /// Whether no content has been seen since the start of the current line.
+    at_line_start: bool,
+    /// The indentation of the current line.
+    indent: Option<String>,
+    /// The number of spaces indenting the previous non-blank line that isn't indented with tabs.
+    previous_width: usize,
+    tab_lines: usize,
+    space_lines: usize,
+    /// How often each difference between the indentation of two consecutive lines occurs, indexed by width.
+    width_deltas: Vec<usize>,
+}
+
+impl Default for IndentDetector {
+    fn default() -> Self {
+        Self {
+            at_line_start: true,
+            indent: None,
+            previous_width: 0,
+            tab_lines: 0,
+            space_lines: 0,
+            width_deltas: Vec::new(),
+        }
+    }
+}
+
+impl IndentDetector {
+    fn newline(&mut self) {
+        // Discards the indentation of blank lines
+        self.at_line_start = true;
+        self.indent = None;
+    }
+
+    fn whitespace(&mut self, text: &str) {
+        if self.at_line_start {
+            self.indent.get_or_insert_with(String::new).push_str(text);
+        }
+    }
+
+    fn content(&mut self) {
+        if !self.at_line_start {
+            return;
+        }
+
+        self.at_line_start = false;
+        let indent = self.indent.take().unwrap_or_default();
+
+        if indent.starts_with('\t') {
+            self.tab_lines += 1;
+            return;
+        }
+
+        let width = indent.len() - indent.trim_start_matches(' ').len();
+        if width > 0 {
+            self.space_lines += 1;
+        }
+
+        let delta = width.abs_diff(self.previous_width);
+        if delta > 0 {
+            if self.width_deltas.len() <= delta {
+                self.width_deltas.resize(delta + 1, 0);
+            }
+            self.width_deltas[delta] += 1;
+        }
+
+        self.previous_width = width;
+    }
+
+    fn finish(self) -> Option<IndentStyle> {
+        if self.tab_lines == 0 && self.space_lines == 0 {
+            return None;
+        }
+
+        if self.tab_lines > self.space_lines {
+            return Some(IndentStyle::Tab);
+        }
+
+        // The most common delta, preferring the smaller width on ties
+        let width = self
+            .width_deltas
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, count)| **count)
+            .map_or(0, |(width, _)| width);
+
+        Some(IndentStyle::Space(u8::try_from(width).unwrap_or(u8::MAX)))
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::parse;
+
+    fn detect(text: &str) -> SourceStyle {
+        SourceStyle::detect(&parse(text))
+    }
+
+    #[test]
+    fn uniform_line_endings() {
+        assert_eq!(detect("a;\nb;\n").line_ending(), Some(LineEndingStyle::Lf));
+        assert_eq!(
+            detect("a;\r\nb;\r\n").line_ending(),
+            Some(LineEndingStyle::Crlf)
+        );
+        assert_eq!(detect("a;\rb;").line_ending(), Some(LineEndingStyle::Cr));
+        assert_eq!(detect("a; b;").line_ending(), None);
+
+        assert_eq!(detect("a;\r\nb;").newline(), "\r\n");
+        assert_eq!(detect("a;").newline(), "\n");
+    }
+
+    #[test]
+    fn mixed_line_endings() {
+        let style = detect("a;\r\nb;\nc;\r\nd;\re;").line_ending().unwrap();
+
+        assert!(style.is_mixed());
+        assert_eq!(
+            style,
+            LineEndingStyle::Mixed(LineEndingCounts {
+                lf: 1,
+                crlf: 2,
+                cr: 1
+            })
+        );
+        assert_eq!(style.dominant(), LineEnding::Crlf);
+        assert_eq!(detect("a;\r\nb;\nc;\r\nd;\re;").newline(), "\r\n");
+    }
+
+    #[test]
+    fn dominant_prefers_lf_on_ties() {
+        let counts = |lf, crlf, cr| LineEndingCounts { lf, crlf, cr };
+
+        assert_eq!(counts(1, 1, 1).dominant(), Some(LineEnding::Lf));
+        assert_eq!(counts(0, 2, 2).dominant(), Some(LineEnding::Crlf));
+        assert_eq!(counts(1, 0, 2).dominant(), Some(LineEnding::Cr));
+        assert_eq!(counts(0, 0, 0).dominant(), None);
+        assert_eq!(counts(1, 2, 3).total(), 6);
+    }
+
+    #[test]
+    fn line_breaks_in_comments_are_ignored() {
+        let style = detect("a;\n/*\r\n\r\n*/\nb;");
+
+        assert_eq!(style.line_ending(), Some(LineEndingStyle::Lf));
+    }
+
+    #[test]
+    fn tab_indentation() {
+        let style = detect("f(\n\ta,\n\t\tb,\n    c\n);");
+
+        assert_eq!(style.indent_style(), Some(IndentStyle::Tab));
+    }
+
+    #[test]
+    fn space_indentation() {
+        let style = detect("f(\n  a,\n    b,\n  c,\n\td\n);");
+
+        assert_eq!(style.indent_style(), Some(IndentStyle::Space(2)));
+    }
+
+    #[test]
+    fn inferred_width() {
+        // The widths of the lines are 4, 8, 4, 0, the deltas 4, 4, 4, 4
+        let style = detect("f(\n    a(\n        b\n    )\n);");
+        assert_eq!(style.indent_style(), Some(IndentStyle::Space(4)));
+
+        // The deltas are 2, 2, 4; blank lines and their whitespace are ignored
+        let style = detect("f(\n  a,\n    \n\n    b,\n\nc\n);");
+        assert_eq!(style.indent_style(), Some(IndentStyle::Space(2)));
+
+        // Ties prefer the smaller width
+        let style = detect("f(\n  a,\n\n  b,\n      c\n);");
+        assert_eq!(style.indent_style(), Some(IndentStyle::Space(2)));
+    }
+
+    #[test]
+    fn no_indentation() {
+        assert_eq!(detect("a;\nb;").indent_style(), None);
+        assert_eq!(detect("a;\n    \nb;").indent_style(), None);
+        // Whitespace in the middle of a line isn't indentation
+        assert_eq!(detect("a;    b;").indent_style(), None);
+    }
+
+    #[test]
+    fn ends_with_newline() {
+        assert!(detect("a;\n").ends_with_newline());
+        assert!(detect("a; // c\r\n").ends_with_newline());
+        assert!(!detect("a;").ends_with_newline());
+        assert!(!detect("a;\n// c").ends_with_newline());
+        assert!(!detect("").ends_with_newline());
+    }
+}
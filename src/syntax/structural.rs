@@ -0,0 +1,287 @@
+//! Comparison and hashing of subtrees that ignores their trivia.
+//!
+//! Two nodes are structurally equal if they have the same kinds, the same slots (including empty slots),
+//! and their tokens have the same kinds, `text_trimmed`, and skipped trivia. Both operate on the green tree
+//! and don't create any red nodes.
+
+use std::hash::{Hash, Hasher};
+
+use crate::{
+    cursor,
+    green::{node::GreenNodeData, token::GreenTokenData, trivia::GreenTrivia},
+    syntax::{Language, node::SyntaxNode, trivia::TriviaPieceKind},
+    utility_types::NodeOrToken,
+};
+
+/// The trivia considered by [SyntaxNode::structurally_eq].
+///
+/// Skipped trivia is always compared because it is source text that the parser failed to place in the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TriviaMode {
+    /// Ignores all trivia except skipped trivia.
+    #[default]
+    Ignore,
+    /// Ignores whitespace and newlines but requires that every token has the same comments.
+    Comments,
+}
+
+impl<L: Language> SyntaxNode<L> {
+    /// Returns `true` if this node and `other` have the same structure and token texts,
+    /// ignoring the trivia as specified by `mode`.
+    ///
+    /// Nodes that share the same green node are equal without comparing their content.
+    pub fn structurally_eq(&self, other: &SyntaxNode<L>, mode: TriviaMode) -> bool {
+        let this = cursor::node::SyntaxNode::from(self.clone());
+        let other = cursor::node::SyntaxNode::from(other.clone());
+
+        nodes_eq(this.green(), other.green(), mode)
+    }
+
+    /// Returns a hash over the kinds of this node's descendants and the `text_trimmed` and skipped trivia of its tokens.
+    ///
+    /// Structurally equal nodes have the same hash, regardless of the [TriviaMode]. The hash is
+    /// stable across runs, processes, and platforms but may change between versions of this crate.
+    pub fn structural_hash(&self) -> u64 {
+        let raw = cursor::node::SyntaxNode::from(self.clone());
+        let mut hasher = StableHasher::default();
+        hash_node(raw.green(), &mut hasher);
+        hasher.finish()
+    }
+}
+
+fn nodes_eq(left: &GreenNodeData, right: &GreenNodeData, mode: TriviaMode) -> bool {
+    if std::ptr::eq(left, right) {
+        return true;
+    }
+
+    if left.kind() != right.kind() || left.slots().len() != right.slots().len() {
+        return false;
+    }
+
+    left.slots()
+        .zip(right.slots())
+        .all(|(left, right)| match (left.as_ref(), right.as_ref()) {
+            (None, None) => true,
+            (Some(NodeOrToken::Node(left)), Some(NodeOrToken::Node(right))) => {
+                nodes_eq(left, right, mode)
+            }
+            (Some(NodeOrToken::Token(left)), Some(NodeOrToken::Token(right))) => {
+                tokens_eq(left, right, mode)
+            }
+            _ => false,
+        })
+}
+
+fn tokens_eq(left: &GreenTokenData, right: &GreenTokenData, mode: TriviaMode) -> bool {
+    if std::ptr::eq(left, right) {
+        return true;
+    }
+
+    if left.kind() != right.kind() || left.text_trimmed() != right.text_trimmed() {
+        return false;
+    }
+
+    let significant = |kind: TriviaPieceKind| match mode {
+        TriviaMode::Ignore => kind.is_skipped(),
+        TriviaMode::Comments => kind.is_skipped() || kind.is_comment(),
+    };
+
+    significant_trivia(left, significant).eq(significant_trivia(right, significant))
+}
+
+/// Returns the kind and text of the pieces in the leading and trailing trivia of `token`
+/// for which `significant` returns `true`.
+fn significant_trivia<F>(
+    token: &GreenTokenData,
+    significant: F,
+) -> impl Iterator<Item = (TriviaPieceKind, &str)>
+where
+    F: Fn(TriviaPieceKind) -> bool,
+{
+    let text = token.text();
+    let trailing_start = text.len() - usize::from(token.trailing_trivia().text_len());
+
+    trivia_texts(text, 0, token.leading_trivia())
+        .chain(trivia_texts(text, trailing_start, token.trailing_trivia()))
+        .filter(move |(kind, _)| significant(*kind))
+}
+
+fn trivia_texts<'a>(
+    text: &'a str,
+    start: usize,
+    trivia: &'a GreenTrivia,
+) -> impl Iterator<Item = (TriviaPieceKind, &'a str)> {
+    trivia.pieces().iter().scan(start, move |offset, piece| {
+        let piece_start = *offset;
+        *offset += usize::from(piece.text_len());
+
+        Some((piece.kind(), &text[piece_start..*offset]))
+    })
+}
+
+fn hash_node(node: &GreenNodeData, hasher: &mut StableHasher) {
+    node.kind().hash(hasher);
+    (node.slots().len() as u32).hash(hasher);
+
+    for slot in node.slots() {
+        match slot.as_ref() {
+            None => 0u8.hash(hasher),
+            Some(NodeOrToken::Node(node)) => {
+                1u8.hash(hasher);
+                hash_node(node, hasher);
+            }
+            Some(NodeOrToken::Token(token)) => {
+                2u8.hash(hasher);
+                token.kind().hash(hasher);
+                token.text_trimmed().hash(hasher);
+
+                for (_, text) in significant_trivia(token, |kind| kind.is_skipped()) {
+                    text.hash(hasher);
+                }
+            }
+        }
+    }
+}
+
+/// A 64-bit FNV-1a hasher.
+///
+/// Unlike `FxHasher`, which operates on `usize` words, the result doesn't depend on the word size
+/// or the endianness of the platform.
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::raw_language::{RawLanguage, RawLanguageKind, parse};
+
+    fn eq(left: &str, right: &str, mode: TriviaMode) -> bool {
+        let (left, right) = (parse(left), parse(right));
+        let result = left.structurally_eq(&right, mode);
+
+        assert_eq!(result, right.structurally_eq(&left, mode));
+        if result {
+            assert_eq!(left.structural_hash(), right.structural_hash());
+        }
+        result
+    }
+
+    fn statement(root: &SyntaxNode<RawLanguage>, index: usize) -> SyntaxNode<RawLanguage> {
+        root.descendants()
+            .filter(|node| node.kind() == RawLanguageKind::EXPRESSION_STATEMENT)
+            .nth(index)
+            .unwrap()
+    }
+
+    #[test]
+    fn equal_across_whitespace() {
+        assert!(eq("a + b;", "a+b ;", TriviaMode::Ignore));
+        assert!(eq("a + b;\n", "\n  a\n+\tb;", TriviaMode::Ignore));
+        assert!(eq("a + b; // c", "a + b;", TriviaMode::Ignore));
+    }
+
+    #[test]
+    fn different_tokens_or_structure() {
+        assert!(!eq("a + b;", "a + c;", TriviaMode::Ignore));
+        assert!(!eq("a + b;", "a - b;", TriviaMode::Ignore));
+        assert!(!eq("a + b * c;", "(a + b) * c;", TriviaMode::Ignore));
+        // `f()` has an empty argument list, `f` has no arguments at all
+        assert!(!eq("f();", "f;", TriviaMode::Ignore));
+    }
+
+    #[test]
+    fn skipped_trivia_is_always_compared() {
+        assert!(!eq("a $ + b;", "a + b;", TriviaMode::Ignore));
+        assert!(!eq("a $ + b;", "a + $ b;", TriviaMode::Ignore));
+        assert!(!eq("a $ + b;", "a + b;", TriviaMode::Comments));
+        assert!(eq("a $ + b;", "a  $+ b;", TriviaMode::Ignore));
+
+        assert_ne!(
+            parse("a $ + b;").structural_hash(),
+            parse("a + b;").structural_hash()
+        );
+    }
+
+    #[test]
+    fn comments_mode() {
+        assert!(eq("a /* c */ + b;", "a /* c */+b;", TriviaMode::Comments));
+        assert!(eq("a + b; // c", "a + b;     // c", TriviaMode::Comments));
+
+        assert!(!eq("a /* c */ + b;", "a + b;", TriviaMode::Comments));
+        assert!(!eq(
+            "a /* c */ + b;",
+            "a /* d */ + b;",
+            TriviaMode::Comments
+        ));
+        // The comment belongs to a different token
+        assert!(!eq(
+            "a /* c */ + b;",
+            "a + /* c */ b;",
+            TriviaMode::Comments
+        ));
+
+        // Comments never change the hash
+        assert_eq!(
+            parse("a /* c */ + b;").structural_hash(),
+            parse("a + b;").structural_hash()
+        );
+    }
+
+    #[test]
+    fn same_green_node() {
+        let statement = statement(&parse("a + b;"), 0).detach();
+        let list = SyntaxNode::new_detached(
+            RawLanguageKind::STATEMENT_LIST,
+            [Some(statement.clone().into()), Some(statement.into())],
+        );
+        let (first, second) = (list.first_child().unwrap(), list.last_child().unwrap());
+
+        // Both statements share the same green node
+        let green = |node: &SyntaxNode<RawLanguage>| {
+            let raw = cursor::node::SyntaxNode::from(node.clone());
+            std::ptr::from_ref(raw.green())
+        };
+        assert_eq!(green(&first), green(&second));
+        assert!(first.structurally_eq(&second, TriviaMode::Comments));
+        assert!(first.structurally_eq(&first, TriviaMode::Comments));
+    }
+
+    #[test]
+    fn structural_hash_is_stable() {
+        assert_eq!(parse("a + b;").structural_hash(), 0x3504_88db_df24_6df6);
+    }
+}
@@ -158,7 +158,7 @@ impl<L: Language> SyntaxToken<L> {
         let leading = GreenTrivia::new(trivia);
 
         // Copy over token text and trailing trivia
-        let leading_len = self.raw.green().text_len();
+        let leading_len = self.raw.green().leading_trivia().text_len();
         token_text.push_str(&self.text()[usize::from(leading_len)..]);
 
         Self {
@@ -51,6 +51,13 @@ impl TriviaPieceKind {
     }
 }
 
+/// Whether a trivia piece is part of a token's leading or trailing trivia.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum TriviaPosition {
+    Leading,
+    Trailing,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct TriviaPiece {
     pub(crate) kind: TriviaPieceKind,
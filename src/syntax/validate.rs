@@ -0,0 +1,461 @@
+//! Validation of the structural invariants of a syntax tree.
+//!
+//! The cursor and the green tree trust that factories and rewriters produce well formed trees.
+//! [SyntaxNode::validate] re-checks these invariants after the fact and reports every violation
+//! it finds instead of silently producing wrong offsets or panicking later during a traversal.
+
+use std::fmt;
+
+use text_size::{TextRange, TextSize};
+
+use crate::{
+    cursor,
+    green::{
+        RawSyntaxKind,
+        node::{GreenNodeData, Slot},
+        token::GreenTokenData,
+    },
+    syntax::{Language, SyntaxKind, node::SyntaxNode},
+};
+
+/// A violation of a tree invariant found by [SyntaxNode::validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeViolation<L: Language> {
+    /// Slot indices leading from the validated node to the offending element.
+    pub path: Vec<u32>,
+    /// Kind of the offending element.
+    pub element_kind: L::Kind,
+    /// Absolute range of the offending element.
+    pub range: TextRange,
+    /// The violated invariant.
+    pub kind: TreeViolationKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeViolationKind {
+    /// The `text_len` stored on a node doesn't match the sum of the lengths of its slots.
+    TextLenMismatch {
+        stored: TextSize,
+        computed: TextSize,
+    },
+    /// The relative offset of a slot doesn't match the accumulated length of the preceding slots.
+    RelOffsetMismatch {
+        slot: u32,
+        stored: TextSize,
+        expected: TextSize,
+    },
+    /// The leading and trailing trivia of a token are longer than the token's text or
+    /// don't end on a character boundary.
+    TriviaLenMismatch {
+        leading: TextSize,
+        trailing: TextSize,
+        text_len: TextSize,
+    },
+    /// A list node that isn't a separated list contains an empty slot.
+    EmptySlotInList { slot: u32 },
+    /// A node or token has the `TOMBSTONE` kind.
+    Tombstone,
+    /// An `EOF` kind is used for a node, or for a token that isn't the last slot of a root node.
+    MisplacedEof,
+}
+
+impl<L: Language> fmt::Display for TreeViolation<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}@{:?} at {:?}: ",
+            self.element_kind, self.range, self.path
+        )?;
+
+        match &self.kind {
+            TreeViolationKind::TextLenMismatch { stored, computed } => write!(
+                f,
+                "stored text length {stored:?} doesn't match the slots' length {computed:?}"
+            ),
+            TreeViolationKind::RelOffsetMismatch {
+                slot,
+                stored,
+                expected,
+            } => write!(
+                f,
+                "slot {slot} has relative offset {stored:?} but the preceding slots end at {expected:?}"
+            ),
+            TreeViolationKind::TriviaLenMismatch {
+                leading,
+                trailing,
+                text_len,
+            } => write!(
+                f,
+                "leading ({leading:?}) and trailing ({trailing:?}) trivia don't fit the token's text of length {text_len:?}"
+            ),
+            TreeViolationKind::EmptySlotInList { slot } => {
+                write!(f, "list contains an empty slot at {slot}")
+            }
+            TreeViolationKind::Tombstone => write!(f, "element has the TOMBSTONE kind"),
+            TreeViolationKind::MisplacedEof => {
+                write!(f, "EOF is only allowed as the last token of a root node")
+            }
+        }
+    }
+}
+
+impl<L: Language> SyntaxNode<L> {
+    /// Checks the invariants of the subtree rooted at this node and returns all violations.
+    ///
+    /// The validation checks that:
+    /// * every node's text length equals the sum of its slots and the slots' relative offsets match
+    /// * the leading and trailing trivia of every token add up to at most the token's text
+    /// * list nodes only contain empty slots if their kind is a separated list, see [SyntaxKind::list_separator]
+    /// * no element has the `TOMBSTONE` kind and `EOF` only appears as the last token of a root node
+    ///
+    /// Returns an empty vec if the tree is well formed.
+    pub fn validate(&self) -> Vec<TreeViolation<L>> {
+        let raw = cursor::node::SyntaxNode::from(self.clone());
+        let mut validator = Validator {
+            violations: Vec::new(),
+            path: Vec::new(),
+        };
+
+        validator.visit_node(raw.green(), self.text_range_with_trivia().start());
+        validator.violations
+    }
+}
+
+struct Validator<L: Language> {
+    violations: Vec<TreeViolation<L>>,
+    path: Vec<u32>,
+}
+
+impl<L: Language> Validator<L> {
+    fn report(&mut self, kind: RawSyntaxKind, range: TextRange, violation: TreeViolationKind) {
+        self.violations.push(TreeViolation {
+            path: self.path.clone(),
+            element_kind: L::Kind::from_raw(kind),
+            range,
+            kind: violation,
+        });
+    }
+
+    fn visit_node(&mut self, node: &GreenNodeData, offset: TextSize) {
+        let kind = L::Kind::from_raw(node.kind());
+        let range = TextRange::at(offset, node.text_len());
+
+        if kind == L::Kind::TOMBSTONE {
+            self.report(node.kind(), range, TreeViolationKind::Tombstone);
+        } else if kind == L::Kind::EOF {
+            self.report(node.kind(), range, TreeViolationKind::MisplacedEof);
+        }
+
+        let mut computed = TextSize::from(0);
+
+        for (index, slot) in node.slots().enumerate() {
+            let index = index as u32;
+
+            if slot.rel_offset() != computed {
+                self.report(
+                    node.kind(),
+                    range,
+                    TreeViolationKind::RelOffsetMismatch {
+                        slot: index,
+                        stored: slot.rel_offset(),
+                        expected: computed,
+                    },
+                );
+            }
+
+            let child_offset = offset + slot.rel_offset();
+            self.path.push(index);
+
+            match slot {
+                Slot::Node { node: child, .. } => {
+                    self.visit_node(child, child_offset);
+                    computed += child.text_len();
+                }
+                Slot::Token { token, .. } => {
+                    let is_last = index as usize + 1 == node.slots().len();
+                    self.visit_token(token, child_offset, kind.is_root() && is_last);
+                    computed += token.text_len();
+                }
+                Slot::Empty { .. } => {}
+            }
+
+            self.path.pop();
+        }
+
+        if computed != node.text_len() {
+            self.report(
+                node.kind(),
+                range,
+                TreeViolationKind::TextLenMismatch {
+                    stored: node.text_len(),
+                    computed,
+                },
+            );
+        }
+
+        if kind.is_list() {
+            self.validate_list(node, range);
+        }
+    }
+
+    fn visit_token(&mut self, token: &GreenTokenData, offset: TextSize, eof_allowed: bool) {
+        let kind = L::Kind::from_raw(token.kind());
+        let range = TextRange::at(offset, token.text_len());
+
+        if kind == L::Kind::TOMBSTONE {
+            self.report(token.kind(), range, TreeViolationKind::Tombstone);
+        } else if kind == L::Kind::EOF && !eof_allowed {
+            self.report(token.kind(), range, TreeViolationKind::MisplacedEof);
+        }
+
+        let leading = token.leading_trivia().text_len();
+        let trailing = token.trailing_trivia().text_len();
+        let text_len = token.text_len();
+        let text = token.text();
+
+        let fits = leading
+            .checked_add(trailing)
+            .is_some_and(|trivia_len| trivia_len <= text_len)
+            && text.is_char_boundary(leading.into())
+            && text.is_char_boundary((text_len - trailing.min(text_len)).into());
+
+        if !fits {
+            self.report(
+                token.kind(),
+                range,
+                TreeViolationKind::TriviaLenMismatch {
+                    leading,
+                    trailing,
+                    text_len,
+                },
+            );
+        }
+    }
+
+    /// Empty slots are only valid in separated lists where they mark a missing element or separator.
+    fn validate_list(&mut self, node: &GreenNodeData, range: TextRange) {
+        if L::Kind::from_raw(node.kind()).list_separator().is_some() {
+            return;
+        }
+
+        for (index, slot) in node.slots().enumerate() {
+            if matches!(slot, Slot::Empty { .. }) {
+                self.report(
+                    node.kind(),
+                    range,
+                    TreeViolationKind::EmptySlotInList { slot: index as u32 },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::{
+        green::{node::GreenNode, token::GreenToken},
+        raw_language::{RawLanguage, RawLanguageKind, parse},
+        syntax::{
+            element::SyntaxElement,
+            token::SyntaxToken,
+            trivia::{TriviaPiece, TriviaPieceKind},
+        },
+    };
+
+    fn token(kind: RawLanguageKind, text: &str) -> Option<SyntaxElement<RawLanguage>> {
+        Some(SyntaxToken::new_detached(kind, text, [], []).into())
+    }
+
+    fn violation_kinds(node: &SyntaxNode<RawLanguage>) -> Vec<TreeViolationKind> {
+        node.validate()
+            .into_iter()
+            .map(|violation| violation.kind)
+            .collect()
+    }
+
+    #[test]
+    fn parsed_tree_is_valid() {
+        let root = parse("a + b; f(1, -2);\n// trailing\n");
+
+        assert_eq!(root.validate(), vec![]);
+    }
+
+    #[test]
+    fn text_len_mismatch() {
+        let green = GreenNode::new_unchecked(
+            RawLanguageKind::NAME_EXPRESSION.to_raw(),
+            TextSize::from(3),
+            vec![Slot::Token {
+                rel_offset: TextSize::from(0),
+                token: GreenToken::new_raw(RawLanguageKind::IDENT_TOKEN.to_raw(), "a"),
+            }],
+        );
+        let node = SyntaxNode::<RawLanguage>::new_root(green);
+
+        let violations = node.validate();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, Vec::<u32>::new());
+        assert_eq!(violations[0].range, TextRange::new(0.into(), 3.into()));
+        assert_eq!(
+            violations[0].kind,
+            TreeViolationKind::TextLenMismatch {
+                stored: TextSize::from(3),
+                computed: TextSize::from(1),
+            }
+        );
+    }
+
+    #[test]
+    fn rel_offset_mismatch() {
+        let ident = |text| GreenToken::new_raw(RawLanguageKind::IDENT_TOKEN.to_raw(), text);
+        let green = GreenNode::new_unchecked(
+            RawLanguageKind::BOGUS.to_raw(),
+            TextSize::from(3),
+            vec![
+                Slot::Token {
+                    rel_offset: TextSize::from(0),
+                    token: ident("ab"),
+                },
+                Slot::Token {
+                    rel_offset: TextSize::from(1),
+                    token: ident("c"),
+                },
+            ],
+        );
+        let node = SyntaxNode::<RawLanguage>::new_root(green);
+
+        assert_eq!(
+            violation_kinds(&node),
+            vec![TreeViolationKind::RelOffsetMismatch {
+                slot: 1,
+                stored: TextSize::from(1),
+                expected: TextSize::from(2),
+            }]
+        );
+    }
+
+    #[test]
+    fn trivia_len_mismatch() {
+        let ident = SyntaxToken::<RawLanguage>::new_detached(
+            RawLanguageKind::IDENT_TOKEN,
+            "a ",
+            [TriviaPiece::whitespace(2)],
+            [TriviaPiece::whitespace(1)],
+        );
+        let node = SyntaxNode::new_detached(RawLanguageKind::NAME_EXPRESSION, [Some(ident.into())]);
+
+        let violations = node.validate();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, vec![0]);
+        assert_eq!(violations[0].element_kind, RawLanguageKind::IDENT_TOKEN);
+        assert_eq!(
+            violations[0].kind,
+            TreeViolationKind::TriviaLenMismatch {
+                leading: TextSize::from(2),
+                trailing: TextSize::from(1),
+                text_len: TextSize::from(2),
+            }
+        );
+    }
+
+    #[test]
+    fn trivia_not_ending_on_char_boundary() {
+        let ident = SyntaxToken::<RawLanguage>::new_detached(
+            RawLanguageKind::IDENT_TOKEN,
+            "éa",
+            [TriviaPiece::new(TriviaPieceKind::Skipped, 1)],
+            [],
+        );
+        let node = SyntaxNode::new_detached(RawLanguageKind::NAME_EXPRESSION, [Some(ident.into())]);
+
+        assert!(matches!(
+            violation_kinds(&node).as_slice(),
+            [TreeViolationKind::TriviaLenMismatch { .. }]
+        ));
+    }
+
+    #[test]
+    fn empty_slot_in_list() {
+        let list =
+            SyntaxNode::<RawLanguage>::new_detached(RawLanguageKind::STATEMENT_LIST, [None, None]);
+
+        assert_eq!(
+            violation_kinds(&list),
+            vec![
+                TreeViolationKind::EmptySlotInList { slot: 0 },
+                TreeViolationKind::EmptySlotInList { slot: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn separated_list_allows_empty_slots() {
+        // `f(, ,)` where the elements are missing
+        let list = SyntaxNode::<RawLanguage>::new_detached(
+            RawLanguageKind::ARGUMENT_LIST,
+            [None, token(RawLanguageKind::COMMA, ","), None],
+        );
+        assert_eq!(list.validate(), vec![]);
+
+        // A missing element and separator
+        let list =
+            SyntaxNode::<RawLanguage>::new_detached(RawLanguageKind::ARGUMENT_LIST, [None, None]);
+        assert_eq!(list.validate(), vec![]);
+    }
+
+    #[test]
+    fn tombstone() {
+        let node = SyntaxNode::<RawLanguage>::new_detached(
+            RawLanguageKind::BOGUS,
+            [
+                token(RawLanguageKind::TOMBSTONE, "a"),
+                Some(SyntaxNode::new_detached(RawLanguageKind::TOMBSTONE, []).into()),
+            ],
+        );
+
+        let violations = node.validate();
+        assert_eq!(
+            violations
+                .iter()
+                .map(|violation| (violation.path.clone(), violation.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (vec![0], TreeViolationKind::Tombstone),
+                (vec![1], TreeViolationKind::Tombstone),
+            ]
+        );
+    }
+
+    #[test]
+    fn misplaced_eof() {
+        let node = SyntaxNode::<RawLanguage>::new_detached(
+            RawLanguageKind::BOGUS,
+            [
+                token(RawLanguageKind::EOF, ""),
+                Some(SyntaxNode::new_detached(RawLanguageKind::EOF, []).into()),
+            ],
+        );
+
+        assert_eq!(
+            violation_kinds(&node),
+            vec![
+                TreeViolationKind::MisplacedEof,
+                TreeViolationKind::MisplacedEof
+            ]
+        );
+
+        // EOF is only allowed as the last slot of a root node
+        let root = SyntaxNode::<RawLanguage>::new_detached(
+            RawLanguageKind::ROOT,
+            [
+                token(RawLanguageKind::EOF, ""),
+                token(RawLanguageKind::EOF, ""),
+            ],
+        );
+        let violations = root.validate();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, vec![0]);
+        assert_eq!(violations[0].kind, TreeViolationKind::MisplacedEof);
+    }
+}
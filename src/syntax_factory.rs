@@ -12,6 +12,7 @@ use crate::{
 };
 pub mod parsed_children;
 pub mod raw_syntax;
+pub mod schema;
 
 /// Factory for creating syntax nodes of a particular kind.
 pub trait SyntaxFactory: Debug {
@@ -0,0 +1,616 @@
+//! A [SyntaxFactory] whose node shapes come from a table built at runtime instead of generated code.
+//!
+//! A [SyntaxSchema] describes, per node kind, the kinds allowed in each slot or the elements and
+//! separators of a list. A [SchemaLanguage] returns its schema from [SchemaLanguage::syntax_schema].
+//! [SchemaSyntaxFactory] matches the [ParsedChildren] of a node against the schema of its kind the
+//! same way a generated factory does. [SlotSchema] exposes the named slots of a language to tools
+//! like AST explorers.
+//!
+#![cfg_attr(feature = "testing", doc = "```")]
+#![cfg_attr(not(feature = "testing"), doc = "```ignore")]
+//! use std::sync::OnceLock;
+//!
+//! use mini_rowan::{
+//!     ast::SyntaxKindSet,
+//!     raw_language::{RawLanguage, RawLanguageKind},
+//!     syntax_factory::schema::{KindSchema, SchemaSlot, SyntaxSchema},
+//! };
+//!
+//! fn schema() -> &'static SyntaxSchema<RawLanguage> {
+//!     static SCHEMA: OnceLock<SyntaxSchema<RawLanguage>> = OnceLock::new();
+//!
+//!     SCHEMA.get_or_init(|| {
+//!         SyntaxSchema::new().with_kind(
+//!             RawLanguageKind::LITERAL_EXPRESSION,
+//!             KindSchema::Node {
+//!                 slots: vec![SchemaSlot::required(
+//!                     "value",
+//!                     SyntaxKindSet::of(RawLanguageKind::NUMBER_TOKEN),
+//!                 )],
+//!             },
+//!         )
+//!     })
+//! }
+//!
+//! assert!(schema().get(RawLanguageKind::LITERAL_EXPRESSION).is_some());
+//! ```
+
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+use crate::{
+    ast::SyntaxKindSet,
+    green::RawSyntaxKind,
+    syntax::{Language, SyntaxKind},
+    syntax_factory::{SyntaxFactory, parsed_children::ParsedChildren, raw_syntax::RawSyntaxNode},
+};
+
+/// A language whose node shapes are described by a [SyntaxSchema].
+pub trait SchemaLanguage: Language + 'static {
+    /// Returns the schema of the language.
+    ///
+    /// The schema must not change once nodes have been created because the node cache
+    /// assumes that [SyntaxFactory::make_syntax] is idempotent.
+    fn syntax_schema() -> &'static SyntaxSchema<Self>;
+}
+
+/// The shape of the nodes of a kind.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KindSchema<L: Language> {
+    /// A node with a fixed number of slots.
+    Node { slots: Vec<SchemaSlot<L>> },
+    /// A list of nodes.
+    List { element: SyntaxKindSet<L> },
+    /// A list of nodes separated by `separator` tokens.
+    SeparatedList {
+        element: SyntaxKindSet<L>,
+        separator: L::Kind,
+        allow_trailing: bool,
+    },
+}
+
+/// A slot of a [KindSchema::Node].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaSlot<L: Language> {
+    /// The field name of the slot, for example `condition`.
+    pub name: Cow<'static, str>,
+    /// The kinds of the elements allowed in this slot.
+    pub kinds: SyntaxKindSet<L>,
+    /// Whether the grammar allows omitting this slot.
+    ///
+    /// The factory leaves a missing optional slot empty and converts a node with a missing required
+    /// slot to its bogus kind.
+    pub optional: bool,
+}
+
+impl<L: Language> SchemaSlot<L> {
+    pub fn required(name: impl Into<Cow<'static, str>>, kinds: SyntaxKindSet<L>) -> Self {
+        Self {
+            name: name.into(),
+            kinds,
+            optional: false,
+        }
+    }
+
+    pub fn optional(name: impl Into<Cow<'static, str>>, kinds: SyntaxKindSet<L>) -> Self {
+        Self {
+            name: name.into(),
+            kinds,
+            optional: true,
+        }
+    }
+}
+
+/// A language that describes the named slots of its nodes.
+///
+/// Implemented for every [SchemaLanguage]. Generated languages implement it with static tables.
+/// Languages also return it from [Language::slot_schema] so that code that works with any
+/// language, like the `Debug` output of nodes, can read the slot names.
+pub trait SlotSchema: Language + 'static {
+    /// Returns the slots of `kind` nodes, or `None` if `kind` isn't a node with a fixed set of slots.
+    fn slots(kind: Self::Kind) -> Option<&'static [SchemaSlot<Self>]>;
+
+    /// Returns the index of the slot called `name` in `kind` nodes.
+    fn slot_index(kind: Self::Kind, name: &str) -> Option<usize> {
+        Self::slots(kind)?.iter().position(|slot| slot.name == name)
+    }
+
+    /// Returns the name of the slot at `index` in `kind` nodes.
+    fn slot_name(kind: Self::Kind, index: usize) -> Option<&'static str> {
+        Self::slots(kind)?.get(index).map(|slot| &*slot.name)
+    }
+}
+
+impl<L: SchemaLanguage> SlotSchema for L {
+    fn slots(kind: Self::Kind) -> Option<&'static [SchemaSlot<Self>]> {
+        match L::syntax_schema().get(kind)? {
+            KindSchema::Node { slots } => Some(slots),
+            KindSchema::List { .. } | KindSchema::SeparatedList { .. } => None,
+        }
+    }
+}
+
+/// The slot lookups of a [SlotSchema] for code that only knows that `L` is a [Language].
+///
+/// It can only be created from a [SlotSchema] implementation, which stays the single source of
+/// the slot names.
+pub struct ErasedSlotSchema<L: Language> {
+    slot_index: fn(L::Kind, &str) -> Option<usize>,
+    slot_name: fn(L::Kind, usize) -> Option<&'static str>,
+}
+
+impl<L: SlotSchema> ErasedSlotSchema<L> {
+    pub fn new() -> Self {
+        Self {
+            slot_index: L::slot_index,
+            slot_name: L::slot_name,
+        }
+    }
+}
+
+impl<L: Language> ErasedSlotSchema<L> {
+    /// Returns the index of the slot called `name` in `kind` nodes, see [SlotSchema::slot_index].
+    pub fn slot_index(&self, kind: L::Kind, name: &str) -> Option<usize> {
+        (self.slot_index)(kind, name)
+    }
+
+    /// Returns the name of the slot at `index` in `kind` nodes, see [SlotSchema::slot_name].
+    pub fn slot_name(&self, kind: L::Kind, index: usize) -> Option<&'static str> {
+        (self.slot_name)(kind, index)
+    }
+}
+
+impl<L: SlotSchema> Default for ErasedSlotSchema<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Language> Clone for ErasedSlotSchema<L> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<L: Language> Copy for ErasedSlotSchema<L> {}
+
+impl<L: Language> fmt::Debug for ErasedSlotSchema<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ErasedSlotSchema")
+    }
+}
+
+/// The shapes of the node kinds of a language.
+///
+/// Kinds without a schema, like tokens and bogus nodes, keep all their children as is.
+#[derive(Clone, PartialEq)]
+pub struct SyntaxSchema<L: Language> {
+    /// The schemas indexed by the raw kind
+    kinds: Vec<Option<KindSchema<L>>>,
+}
+
+impl<L: Language> SyntaxSchema<L> {
+    pub fn new() -> Self {
+        Self { kinds: Vec::new() }
+    }
+
+    /// Sets the schema of `kind`, returning the previous schema if any.
+    pub fn insert(&mut self, kind: L::Kind, schema: KindSchema<L>) -> Option<KindSchema<L>> {
+        let index = usize::from(kind.to_raw().0);
+
+        if index >= self.kinds.len() {
+            self.kinds.resize_with(index + 1, || None);
+        }
+
+        self.kinds[index].replace(schema)
+    }
+
+    /// Returns the schema with `kind` set to `schema`.
+    pub fn with_kind(mut self, kind: L::Kind, schema: KindSchema<L>) -> Self {
+        self.insert(kind, schema);
+        self
+    }
+
+    pub fn get(&self, kind: L::Kind) -> Option<&KindSchema<L>> {
+        self.kinds
+            .get(usize::from(kind.to_raw().0))
+            .and_then(Option::as_ref)
+    }
+
+    /// Iterates over the kinds that have a schema and their schemas.
+    pub fn iter(&self) -> impl Iterator<Item = (L::Kind, &KindSchema<L>)> {
+        self.kinds.iter().enumerate().filter_map(|(index, schema)| {
+            let kind = L::Kind::from_raw(RawSyntaxKind(index as u16));
+            schema.as_ref().map(|schema| (kind, schema))
+        })
+    }
+}
+
+impl<L: Language> Default for SyntaxSchema<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Language> fmt::Debug for SyntaxSchema<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// A [SyntaxFactory] that creates the nodes of `L` according to [SchemaLanguage::syntax_schema].
+///
+/// The children of a [KindSchema::Node] are assigned to the slots in order. A child that doesn't
+/// match the current slot leaves that slot empty and is tried against the next slot. The node
+/// becomes [SyntaxKind::to_bogus] if a required slot stays empty or children remain after the last
+/// slot. Lists are validated with
+/// [SyntaxFactory::make_node_list_syntax] and [SyntaxFactory::make_separated_list_syntax].
+pub struct SchemaSyntaxFactory<L>(PhantomData<L>);
+
+impl<L> fmt::Debug for SchemaSyntaxFactory<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SchemaSyntaxFactory")
+    }
+}
+
+impl<L: SchemaLanguage> SyntaxFactory for SchemaSyntaxFactory<L> {
+    type Kind = L::Kind;
+
+    fn make_syntax(kind: L::Kind, children: ParsedChildren<L::Kind>) -> RawSyntaxNode<L::Kind> {
+        match L::syntax_schema().get(kind) {
+            Some(KindSchema::Node { slots }) => make_node_syntax(kind, children, slots),
+            Some(KindSchema::List { element }) => {
+                Self::make_node_list_syntax(kind, children, |kind| element.matches(kind))
+            }
+            Some(KindSchema::SeparatedList {
+                element,
+                separator,
+                allow_trailing,
+            }) => Self::make_separated_list_syntax(
+                kind,
+                children,
+                |kind| element.matches(kind),
+                *separator,
+                *allow_trailing,
+            ),
+            None => RawSyntaxNode::new(kind, children.into_iter().map(Some)),
+        }
+    }
+}
+
+fn make_node_syntax<L: Language>(
+    kind: L::Kind,
+    children: ParsedChildren<L::Kind>,
+    slots: &[SchemaSlot<L>],
+) -> RawSyntaxNode<L::Kind> {
+    let mut elements = (&children).into_iter().peekable();
+    let present: Vec<bool> = slots
+        .iter()
+        .map(|slot| {
+            elements
+                .next_if(|element| slot.kinds.matches(element.kind()))
+                .is_some()
+        })
+        .collect();
+
+    let complete = slots
+        .iter()
+        .zip(&present)
+        .all(|(slot, present)| *present || slot.optional);
+
+    if !complete || elements.next().is_some() {
+        return RawSyntaxNode::new(kind.to_bogus(), children.into_iter().map(Some));
+    }
+
+    let mut children = children.into_iter();
+
+    RawSyntaxNode::new(
+        kind,
+        present
+            .into_iter()
+            .map(|present| if present { children.next() } else { None }),
+    )
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::sync::OnceLock;
+
+    use super::*;
+    use crate::{
+        ast::AstNode,
+        cursor,
+        green::{element::GreenElement, node::GreenNode, token::GreenToken},
+        raw_language::{
+            RawLanguageKind::{self, *},
+            parse,
+        },
+        syntax::node::SyntaxNode,
+        utility_types::NodeOrToken,
+    };
+
+    /// A language that reuses the kinds of the raw language but only describes its nodes with a schema.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    struct SchemaOnlyLanguage;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct SchemaOnlyRoot;
+
+    impl AstNode for SchemaOnlyRoot {
+        type Language = SchemaOnlyLanguage;
+
+        const KIND_SET: SyntaxKindSet<SchemaOnlyLanguage> =
+            SyntaxKindSet::from_raw(RawSyntaxKind(RawLanguageKind::ROOT as u16));
+
+        fn can_cast(kind: RawLanguageKind) -> bool {
+            kind == RawLanguageKind::ROOT
+        }
+    }
+
+    impl Language for SchemaOnlyLanguage {
+        type Kind = RawLanguageKind;
+        type Root = SchemaOnlyRoot;
+
+        fn slot_schema() -> Option<ErasedSlotSchema<Self>> {
+            Some(ErasedSlotSchema::new())
+        }
+    }
+
+    impl SchemaLanguage for SchemaOnlyLanguage {
+        fn syntax_schema() -> &'static SyntaxSchema<Self> {
+            static SCHEMA: OnceLock<SyntaxSchema<SchemaOnlyLanguage>> = OnceLock::new();
+
+            SCHEMA.get_or_init(|| {
+                SyntaxSchema::new()
+                    .with_kind(
+                        ROOT,
+                        KindSchema::Node {
+                            slots: vec![
+                                SchemaSlot::required(
+                                    "statements",
+                                    SyntaxKindSet::of(STATEMENT_LIST),
+                                ),
+                                SchemaSlot::required("eof_token", SyntaxKindSet::of(EOF)),
+                            ],
+                        },
+                    )
+                    .with_kind(
+                        EXPRESSION_STATEMENT,
+                        KindSchema::Node {
+                            slots: vec![
+                                SchemaSlot::required(
+                                    "expression",
+                                    SyntaxKindSet::of(NAME_EXPRESSION),
+                                ),
+                                SchemaSlot::optional(
+                                    "semicolon_token",
+                                    SyntaxKindSet::of(SEMICOLON),
+                                ),
+                            ],
+                        },
+                    )
+                    .with_kind(
+                        STATEMENT_LIST,
+                        KindSchema::List {
+                            element: SyntaxKindSet::of(EXPRESSION_STATEMENT),
+                        },
+                    )
+                    .with_kind(
+                        ARGUMENT_LIST,
+                        KindSchema::SeparatedList {
+                            element: SyntaxKindSet::of(NAME_EXPRESSION),
+                            separator: COMMA,
+                            allow_trailing: false,
+                        },
+                    )
+            })
+        }
+    }
+
+    fn token(kind: RawLanguageKind, text: &str) -> GreenElement {
+        NodeOrToken::Token(GreenToken::new_raw(kind.to_raw(), text))
+    }
+
+    fn node(kind: RawLanguageKind, children: Vec<GreenElement>) -> GreenElement {
+        NodeOrToken::Node(GreenNode::new(
+            kind.to_raw(),
+            children.into_iter().map(Some),
+        ))
+    }
+
+    fn name(text: &str) -> GreenElement {
+        node(NAME_EXPRESSION, vec![token(IDENT_TOKEN, text)])
+    }
+
+    /// Creates a `kind` node from `children` with the [SchemaSyntaxFactory] and returns its kind
+    /// and the kinds of its slots.
+    fn make_syntax(
+        kind: RawLanguageKind,
+        children: Vec<GreenElement>,
+    ) -> (RawLanguageKind, Vec<Option<RawLanguageKind>>) {
+        let mut children: Vec<(u64, GreenElement)> =
+            children.into_iter().map(|child| (0, child)).collect();
+        let node = SchemaSyntaxFactory::<SchemaOnlyLanguage>::make_syntax(
+            kind,
+            ParsedChildren::new(&mut children, 0),
+        );
+        let node = SyntaxNode::<SchemaOnlyLanguage>::new_root(node.into_green());
+        let slots = node.slots().map(|slot| slot.kind()).collect();
+
+        (node.kind(), slots)
+    }
+
+    fn parse_schema_only(text: &str) -> SyntaxNode<SchemaOnlyLanguage> {
+        let root = cursor::node::SyntaxNode::from(parse(text));
+        SyntaxNode::new_root(root.green().to_owned())
+    }
+
+    #[test]
+    fn alternate_debug_labels_slots_from_schema() {
+        let root = parse_schema_only("a;");
+
+        // `NAME_EXPRESSION` has no schema and its slots are unlabeled
+        assert_eq!(
+            format!("{root:#?}"),
+            r#"0: ROOT@0..2
+ 0: statements: STATEMENT_LIST@0..2
+  0: EXPRESSION_STATEMENT@0..2
+   0: expression: NAME_EXPRESSION@0..1
+    0: IDENT_TOKEN&0..1 "a"[] []
+   1: semicolon_token: SEMICOLON&1..2 ";"[] []
+ 1: eof_token: EOF&2..2 ""[] []
+"#
+        );
+    }
+
+    #[test]
+    fn slot_schema_reads_syntax_schema() {
+        let root = parse_schema_only("a;");
+        let statement = root
+            .descendants()
+            .find(|node| node.kind() == RawLanguageKind::EXPRESSION_STATEMENT)
+            .unwrap();
+
+        assert_eq!(statement.slot_name(1), Some("semicolon_token"));
+        assert_eq!(
+            statement
+                .slot_by_name("semicolon_token")
+                .and_then(|slot| slot.kind()),
+            Some(RawLanguageKind::SEMICOLON)
+        );
+        assert!(SchemaOnlyLanguage::slots(RawLanguageKind::STATEMENT_LIST).is_none());
+    }
+
+    #[test]
+    fn erased_slot_schema_reads_slot_schema() {
+        let schema = SchemaOnlyLanguage::slot_schema().unwrap();
+
+        assert_eq!(
+            schema.slot_name(EXPRESSION_STATEMENT, 0),
+            Some("expression")
+        );
+        assert_eq!(schema.slot_name(EXPRESSION_STATEMENT, 2), None);
+        assert_eq!(
+            schema.slot_index(EXPRESSION_STATEMENT, "semicolon_token"),
+            Some(1)
+        );
+        assert_eq!(schema.slot_index(STATEMENT_LIST, "expression"), None);
+    }
+
+    #[test]
+    fn node_slots_are_matched_in_order() {
+        assert_eq!(
+            make_syntax(EXPRESSION_STATEMENT, vec![name("a"), token(SEMICOLON, ";")]),
+            (
+                EXPRESSION_STATEMENT,
+                vec![Some(NAME_EXPRESSION), Some(SEMICOLON)]
+            )
+        );
+    }
+
+    #[test]
+    fn missing_optional_slot_is_empty() {
+        assert_eq!(
+            make_syntax(EXPRESSION_STATEMENT, vec![name("a")]),
+            (EXPRESSION_STATEMENT, vec![Some(NAME_EXPRESSION), None])
+        );
+    }
+
+    #[test]
+    fn missing_required_slot_converts_to_bogus() {
+        assert_eq!(
+            make_syntax(EXPRESSION_STATEMENT, vec![token(SEMICOLON, ";")]),
+            (BOGUS_STATEMENT, vec![Some(SEMICOLON)])
+        );
+    }
+
+    #[test]
+    fn unexpected_child_converts_to_bogus() {
+        assert_eq!(
+            make_syntax(
+                EXPRESSION_STATEMENT,
+                vec![name("a"), token(SEMICOLON, ";"), token(SEMICOLON, ";")]
+            ),
+            (
+                BOGUS_STATEMENT,
+                vec![Some(NAME_EXPRESSION), Some(SEMICOLON), Some(SEMICOLON)]
+            )
+        );
+        assert_eq!(
+            make_syntax(
+                EXPRESSION_STATEMENT,
+                vec![node(LITERAL_EXPRESSION, vec![token(NUMBER_TOKEN, "1")])]
+            ),
+            (BOGUS_STATEMENT, vec![Some(LITERAL_EXPRESSION)])
+        );
+    }
+
+    #[test]
+    fn list_shape() {
+        let statement = || node(EXPRESSION_STATEMENT, vec![name("a")]);
+
+        assert_eq!(
+            make_syntax(STATEMENT_LIST, vec![statement(), statement()]),
+            (
+                STATEMENT_LIST,
+                vec![Some(EXPRESSION_STATEMENT), Some(EXPRESSION_STATEMENT)]
+            )
+        );
+        assert_eq!(
+            make_syntax(STATEMENT_LIST, Vec::new()),
+            (STATEMENT_LIST, Vec::new())
+        );
+        assert_eq!(
+            make_syntax(STATEMENT_LIST, vec![statement(), name("b")]),
+            (
+                STATEMENT_LIST.to_bogus(),
+                vec![Some(EXPRESSION_STATEMENT), Some(NAME_EXPRESSION)]
+            )
+        );
+    }
+
+    #[test]
+    fn separated_list_shape() {
+        assert_eq!(
+            make_syntax(ARGUMENT_LIST, vec![name("a"), token(COMMA, ","), name("b")]),
+            (
+                ARGUMENT_LIST,
+                vec![Some(NAME_EXPRESSION), Some(COMMA), Some(NAME_EXPRESSION)]
+            )
+        );
+
+        // A missing element and a trailing separator in a list that doesn't allow one get empty slots
+        assert_eq!(
+            make_syntax(
+                ARGUMENT_LIST,
+                vec![token(COMMA, ","), name("b"), token(COMMA, ",")]
+            ),
+            (
+                ARGUMENT_LIST,
+                vec![None, Some(COMMA), Some(NAME_EXPRESSION), Some(COMMA), None]
+            )
+        );
+
+        assert_eq!(
+            make_syntax(
+                ARGUMENT_LIST,
+                vec![name("a"), token(SEMICOLON, ";"), name("b")]
+            ),
+            (
+                ARGUMENT_LIST.to_bogus(),
+                vec![
+                    Some(NAME_EXPRESSION),
+                    Some(SEMICOLON),
+                    Some(NAME_EXPRESSION)
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn kinds_without_schema_keep_their_children() {
+        assert_eq!(
+            make_syntax(CALL_ARGUMENTS, vec![token(L_PAREN, "("), name("a")]),
+            (CALL_ARGUMENTS, vec![Some(L_PAREN), Some(NAME_EXPRESSION)])
+        );
+    }
+}
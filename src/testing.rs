@@ -0,0 +1,230 @@
+//! Helpers to snapshot test syntax trees.
+//!
+//! The main entry point is the [`assert_tree_snapshot!`](crate::assert_tree_snapshot) macro which
+//! renders a tree into a canonical, line based dump (including all trivia, empty slots and bogus nodes)
+//! and compares it against an expected file on disk.
+//!
+//! Set the [`UPDATE_SNAPSHOTS_ENV`] environment variable to write the actual output to the snapshot
+//! file instead of comparing it.
+//!
+//! The [generator] module produces arbitrary trees from a seed and the [properties] module checks
+//! that the different tree traversals agree with each other on any tree.
+
+use std::{
+    fmt::{self, Write},
+    fs,
+    path::Path,
+};
+
+use crate::syntax::{
+    Language, SyntaxKind,
+    node::{SyntaxNode, SyntaxSlot},
+    token::SyntaxToken,
+    trivia::{SyntaxTrivia, TriviaPieceKind},
+};
+
+pub mod generator;
+pub mod properties;
+
+/// Name of the environment variable that, when set, makes snapshot assertions
+/// overwrite the expected files with the actual output.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "UPDATE_SNAPSHOTS";
+
+/// Asserts that the canonical dump of a syntax tree matches the snapshot stored at `path` and that
+/// the tree's `text_with_trivia()` is identical to `source`, the text the tree was created from.
+///
+/// `path` is relative to the `CARGO_MANIFEST_DIR` of the crate invoking the macro.
+///
+/// ```ignore
+/// let root = parse("1 + 2");
+/// assert_tree_snapshot!(root, "tests/snapshots/binary.snap", "1 + 2");
+/// ```
+#[macro_export]
+macro_rules! assert_tree_snapshot {
+    ($node:expr, $path:expr, $source:expr $(,)?) => {
+        $crate::testing::assert_tree_snapshot(
+            &$node,
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+            ::std::convert::AsRef::<str>::as_ref(&$source),
+        )
+    };
+}
+
+/// Implementation of [`assert_tree_snapshot!`](crate::assert_tree_snapshot).
+///
+/// ## Panics
+/// If the tree doesn't round-trip to `source` or if the rendered tree doesn't match the snapshot.
+#[track_caller]
+pub fn assert_tree_snapshot<L: Language>(
+    node: &SyntaxNode<L>,
+    path: impl AsRef<Path>,
+    source: &str,
+) {
+    let text = node.text_with_trivia().to_string();
+    if text != source {
+        panic!(
+            "the tree's text doesn't match the input it was created from:\n{}",
+            line_diff(source, &text)
+        );
+    }
+
+    assert_snapshot(&render_tree(node), path);
+}
+
+/// Compares `actual` with the content of the file at `path`, or writes `actual` to `path`
+/// if the [`UPDATE_SNAPSHOTS_ENV`] environment variable is set.
+///
+/// ## Panics
+/// If the file doesn't exist or its content differs from `actual`.
+#[track_caller]
+pub fn assert_snapshot(actual: &str, path: impl AsRef<Path>) {
+    let path = path.as_ref();
+
+    if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap_or_else(|error| {
+                panic!("failed to create directory {}: {error}", parent.display())
+            });
+        }
+        fs::write(path, actual)
+            .unwrap_or_else(|error| panic!("failed to write {}: {error}", path.display()));
+        return;
+    }
+
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => expected.replace("\r\n", "\n"),
+        Err(error) => panic!(
+            "failed to read snapshot {}: {error}\nRun with `{UPDATE_SNAPSHOTS_ENV}=1` to create it.",
+            path.display()
+        ),
+    };
+
+    if expected != actual {
+        panic!(
+            "snapshot {} doesn't match the actual output:\n{}\nRun with `{UPDATE_SNAPSHOTS_ENV}=1` to update it.",
+            path.display(),
+            line_diff(&expected, actual)
+        );
+    }
+}
+
+/// Renders the tree rooted at `node` in its canonical snapshot format.
+///
+/// Every slot is printed on its own line, prefixed by its slot index and indented by its depth.
+/// Nodes print their kind and range, tokens additionally print their text and all their leading
+/// and trailing trivia pieces. Empty slots print as `(empty)` and bogus nodes are suffixed with `(bogus)`.
+pub fn render_tree<L: Language>(node: &SyntaxNode<L>) -> String {
+    let mut out = String::new();
+    write_node_line(&mut out, node).unwrap();
+
+    let mut stack = vec![node.slots()];
+
+    while let Some(slots) = stack.last_mut() {
+        let Some(slot) = slots.next() else {
+            stack.pop();
+            continue;
+        };
+
+        let depth = stack.len();
+        for _ in 0..depth {
+            out.push_str("  ");
+        }
+
+        match slot {
+            SyntaxSlot::Node(node) => {
+                write!(out, "{}: ", node.index()).unwrap();
+                write_node_line(&mut out, &node).unwrap();
+                stack.push(node.slots());
+            }
+            SyntaxSlot::Token(token) => {
+                write!(out, "{}: ", token.index()).unwrap();
+                write_token_line(&mut out, &token).unwrap();
+            }
+            SyntaxSlot::Empty { index } => {
+                writeln!(out, "{index}: (empty)").unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+fn write_node_line<L: Language>(out: &mut String, node: &SyntaxNode<L>) -> fmt::Result {
+    write!(out, "{:?}@{:?}", node.kind(), node.text_range_with_trivia())?;
+    if node.kind().is_bogus() {
+        write!(out, " (bogus)")?;
+    }
+    writeln!(out)
+}
+
+fn write_token_line<L: Language>(out: &mut String, token: &SyntaxToken<L>) -> fmt::Result {
+    write!(
+        out,
+        "{:?}@{:?} {:?} ",
+        token.kind(),
+        token.text_range(),
+        token.text_trimmed()
+    )?;
+    write_trivia(out, &token.leading_trivia())?;
+    write!(out, " ")?;
+    write_trivia(out, &token.trailing_trivia())?;
+    writeln!(out)
+}
+
+fn write_trivia<L: Language>(out: &mut String, trivia: &SyntaxTrivia<L>) -> fmt::Result {
+    write!(out, "[")?;
+    for (index, piece) in trivia.pieces().enumerate() {
+        if index > 0 {
+            write!(out, ", ")?;
+        }
+        let name = match piece.kind() {
+            TriviaPieceKind::Newline => "Newline",
+            TriviaPieceKind::Whitespace => "Whitespace",
+            TriviaPieceKind::SingleLineComment => "SingleLineComment",
+            TriviaPieceKind::MultiLineComment => "MultiLineComment",
+            TriviaPieceKind::Skipped => "Skipped",
+        };
+        write!(out, "{name}({:?})", piece.text())?;
+    }
+    write!(out, "]")
+}
+
+/// Returns a line diff between `expected` and `actual`.
+///
+/// Lines only present in `expected` are prefixed with `-`, lines only present in `actual` with `+`,
+/// and unchanged lines with a space.
+pub fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+
+    // Length of the longest common subsequence of `expected[i..]` and `actual[j..]`
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            writeln!(out, " {}", expected[i]).unwrap();
+            i += 1;
+            j += 1;
+        } else if j < actual.len() && (i == expected.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            writeln!(out, "+{}", actual[j]).unwrap();
+            j += 1;
+        } else {
+            writeln!(out, "-{}", expected[i]).unwrap();
+            i += 1;
+        }
+    }
+
+    out
+}
@@ -0,0 +1,388 @@
+//! Seeded generator for arbitrary [RawLanguage] trees.
+//!
+//! The generated trees are well formed (they pass [SyntaxNode::validate]) but otherwise contain
+//! everything a real parser can produce: random trivia, empty slots for missing children and
+//! bogus nodes. The same seed always produces the same tree, which makes failures reproducible.
+
+use crate::{
+    raw_language::{RawLanguage, RawLanguageKind, RawSyntaxTreeBuilder},
+    syntax::{
+        SyntaxKind,
+        node::SyntaxNode,
+        trivia::{TriviaPiece, TriviaPieceKind},
+    },
+};
+
+/// A small and fast pseudo random number generator (SplitMix64).
+///
+/// Not suitable for anything but testing.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in the range `0..upper`.
+    ///
+    /// ## Panics
+    /// If `upper` is zero.
+    pub fn below(&mut self, upper: usize) -> usize {
+        assert!(upper > 0, "upper bound must be greater than zero");
+        (self.next_u64() % upper as u64) as usize
+    }
+
+    /// Returns `true` with a probability of `percent` out of 100.
+    pub fn chance(&mut self, percent: u32) -> bool {
+        self.below(100) < percent as usize
+    }
+
+    /// Returns a random element of `items`.
+    ///
+    /// ## Panics
+    /// If `items` is empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// Controls the shape of the trees produced by the [TreeGenerator].
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    /// Maximum nesting of expressions.
+    pub max_depth: usize,
+    /// Maximum number of statements in the root's statement list.
+    pub max_statements: usize,
+    /// Maximum number of arguments of a call expression.
+    pub max_arguments: usize,
+    /// Probability in percent that a token has leading or trailing trivia.
+    pub trivia_percent: u32,
+    /// Probability in percent that a child is omitted, resulting in an empty slot.
+    pub empty_slot_percent: u32,
+    /// Probability in percent that a statement or expression is a bogus node.
+    pub bogus_percent: u32,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_statements: 5,
+            max_arguments: 3,
+            trivia_percent: 40,
+            empty_slot_percent: 10,
+            bogus_percent: 5,
+        }
+    }
+}
+
+/// Generates arbitrary well-formed [RawLanguage] trees from a seed.
+///
+/// ```ignore
+/// let mut generator = TreeGenerator::new(42);
+/// let root = generator.generate();
+/// assert!(root.validate().is_empty());
+/// ```
+#[derive(Debug)]
+pub struct TreeGenerator {
+    rng: Rng,
+    config: GeneratorConfig,
+    builder: RawSyntaxTreeBuilder,
+    /// Whether the previous token ended with a single line comment and the next token must
+    /// start on a new line.
+    needs_newline: bool,
+}
+
+impl TreeGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self::with_config(seed, GeneratorConfig::default())
+    }
+
+    pub fn with_config(seed: u64, config: GeneratorConfig) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            config,
+            builder: RawSyntaxTreeBuilder::new(),
+            needs_newline: false,
+        }
+    }
+
+    /// Returns the random number generator to derive further random decisions from the same seed,
+    /// for example, which node of the generated tree to modify.
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    /// Generates a new tree. Calling `generate` multiple times returns different trees.
+    pub fn generate(&mut self) -> SyntaxNode<RawLanguage> {
+        self.needs_newline = false;
+
+        self.builder.start_node(RawLanguageKind::ROOT);
+        self.builder.start_node(RawLanguageKind::STATEMENT_LIST);
+
+        let statements = self.rng.below(self.config.max_statements + 1);
+        for _ in 0..statements {
+            self.statement();
+        }
+
+        self.builder.finish_node();
+
+        let mut text = String::new();
+        let leading = self.leading_trivia(&mut text);
+        self.builder
+            .token_with_trivia(RawLanguageKind::EOF, &text, &leading, &[]);
+
+        self.builder.finish_node();
+
+        std::mem::take(&mut self.builder).finish()
+    }
+
+    fn statement(&mut self) {
+        if self.rng.chance(self.config.bogus_percent) {
+            self.builder.start_node(RawLanguageKind::BOGUS_STATEMENT);
+            self.bogus_content();
+            self.builder.finish_node();
+            return;
+        }
+
+        self.builder
+            .start_node(RawLanguageKind::EXPRESSION_STATEMENT);
+
+        if !self.is_missing() {
+            self.expression(0);
+        }
+
+        if self.rng.chance(50) {
+            self.token(RawLanguageKind::SEMICOLON);
+        }
+
+        self.builder.finish_node();
+    }
+
+    fn expression(&mut self, depth: usize) {
+        const NESTED: [RawLanguageKind; 6] = [
+            RawLanguageKind::LITERAL_EXPRESSION,
+            RawLanguageKind::NAME_EXPRESSION,
+            RawLanguageKind::BINARY_EXPRESSION,
+            RawLanguageKind::UNARY_EXPRESSION,
+            RawLanguageKind::PAREN_EXPRESSION,
+            RawLanguageKind::CALL_EXPRESSION,
+        ];
+
+        let kind = if self.rng.chance(self.config.bogus_percent) {
+            RawLanguageKind::BOGUS_EXPRESSION
+        } else if depth >= self.config.max_depth {
+            *self.rng.choose(&NESTED[..2])
+        } else {
+            *self.rng.choose(&NESTED)
+        };
+
+        self.builder.start_node(kind);
+
+        match kind {
+            RawLanguageKind::LITERAL_EXPRESSION => self.token(RawLanguageKind::NUMBER_TOKEN),
+            RawLanguageKind::NAME_EXPRESSION => self.token(RawLanguageKind::IDENT_TOKEN),
+            RawLanguageKind::BINARY_EXPRESSION => {
+                self.optional_expression(depth + 1);
+                let operator = *self.rng.choose(&[
+                    RawLanguageKind::PLUS,
+                    RawLanguageKind::MINUS,
+                    RawLanguageKind::STAR,
+                    RawLanguageKind::SLASH,
+                ]);
+                self.token(operator);
+                self.optional_expression(depth + 1);
+            }
+            RawLanguageKind::UNARY_EXPRESSION => {
+                let operator = *self
+                    .rng
+                    .choose(&[RawLanguageKind::PLUS, RawLanguageKind::MINUS]);
+                self.token(operator);
+                self.optional_expression(depth + 1);
+            }
+            RawLanguageKind::PAREN_EXPRESSION => {
+                self.token(RawLanguageKind::L_PAREN);
+                self.optional_expression(depth + 1);
+                if !self.is_missing() {
+                    self.token(RawLanguageKind::R_PAREN);
+                }
+            }
+            RawLanguageKind::CALL_EXPRESSION => {
+                self.optional_expression(depth + 1);
+                self.call_arguments(depth + 1);
+            }
+            _ => self.bogus_content(),
+        }
+
+        self.builder.finish_node();
+    }
+
+    fn optional_expression(&mut self, depth: usize) {
+        if !self.is_missing() {
+            self.expression(depth);
+        }
+    }
+
+    fn call_arguments(&mut self, depth: usize) {
+        self.builder.start_node(RawLanguageKind::CALL_ARGUMENTS);
+        self.token(RawLanguageKind::L_PAREN);
+        self.builder.start_node(RawLanguageKind::ARGUMENT_LIST);
+
+        let arguments = self.rng.below(self.config.max_arguments + 1);
+        for index in 0..arguments {
+            if index > 0 && !self.is_missing() {
+                self.token(RawLanguageKind::COMMA);
+            }
+
+            self.optional_expression(depth);
+        }
+
+        if arguments > 0 && self.rng.chance(20) {
+            self.token(RawLanguageKind::COMMA);
+        }
+
+        self.builder.finish_node();
+        if !self.is_missing() {
+            self.token(RawLanguageKind::R_PAREN);
+        }
+        self.builder.finish_node();
+    }
+
+    /// Adds one to three random tokens, optionally followed by a nested expression.
+    fn bogus_content(&mut self) {
+        const TOKENS: [RawLanguageKind; 10] = [
+            RawLanguageKind::NUMBER_TOKEN,
+            RawLanguageKind::IDENT_TOKEN,
+            RawLanguageKind::PLUS,
+            RawLanguageKind::MINUS,
+            RawLanguageKind::STAR,
+            RawLanguageKind::SLASH,
+            RawLanguageKind::L_PAREN,
+            RawLanguageKind::R_PAREN,
+            RawLanguageKind::COMMA,
+            RawLanguageKind::SEMICOLON,
+        ];
+
+        for _ in 0..=self.rng.below(3) {
+            let kind = *self.rng.choose(&TOKENS);
+            self.token(kind);
+        }
+
+        if self.rng.chance(30) {
+            self.expression(self.config.max_depth);
+        }
+    }
+
+    fn is_missing(&mut self) -> bool {
+        self.rng.chance(self.config.empty_slot_percent)
+    }
+
+    fn token(&mut self, kind: RawLanguageKind) {
+        let mut text = String::new();
+        let leading = self.leading_trivia(&mut text);
+
+        match kind {
+            RawLanguageKind::NUMBER_TOKEN => {
+                for _ in 0..=self.rng.below(3) {
+                    text.push(*self.rng.choose(&['0', '1', '2', '7', '9']));
+                }
+            }
+            RawLanguageKind::IDENT_TOKEN => {
+                let name = *self
+                    .rng
+                    .choose(&["a", "foo", "bar_1", "x", "é", "名前", "ß𝔘"]);
+                text.push_str(name);
+            }
+            _ => text.push_str(
+                SyntaxKind::to_string(&kind).expect("punctuation tokens have a static text"),
+            ),
+        }
+
+        let trailing = self.trailing_trivia(&mut text);
+
+        self.builder
+            .token_with_trivia(kind, &text, &leading, &trailing);
+    }
+
+    fn leading_trivia(&mut self, text: &mut String) -> Vec<TriviaPiece> {
+        let mut pieces = Vec::new();
+
+        if std::mem::take(&mut self.needs_newline) {
+            push_piece(&mut pieces, text, TriviaPieceKind::Newline, "\n");
+        }
+
+        if !self.rng.chance(self.config.trivia_percent) {
+            return pieces;
+        }
+
+        for _ in 0..=self.rng.below(3) {
+            let (kind, piece_text) = *self.rng.choose(&[
+                (TriviaPieceKind::Whitespace, " "),
+                (TriviaPieceKind::Whitespace, "\t  "),
+                (TriviaPieceKind::Newline, "\n"),
+                (TriviaPieceKind::Newline, "\r\n"),
+                (TriviaPieceKind::SingleLineComment, "// comment"),
+                (TriviaPieceKind::SingleLineComment, "/* ü */"),
+                (TriviaPieceKind::MultiLineComment, "/* a\n b */"),
+                (TriviaPieceKind::Skipped, "$"),
+                (TriviaPieceKind::Skipped, "€"),
+            ]);
+
+            push_piece(&mut pieces, text, kind, piece_text);
+
+            if piece_text.starts_with("//") {
+                push_piece(&mut pieces, text, TriviaPieceKind::Newline, "\n");
+            }
+        }
+
+        pieces
+    }
+
+    /// Trailing trivia never contains a line break and a `//` comment is always its last piece.
+    fn trailing_trivia(&mut self, text: &mut String) -> Vec<TriviaPiece> {
+        let mut pieces = Vec::new();
+
+        if !self.rng.chance(self.config.trivia_percent) {
+            return pieces;
+        }
+
+        for _ in 0..=self.rng.below(2) {
+            let (kind, piece_text) = *self.rng.choose(&[
+                (TriviaPieceKind::Whitespace, " "),
+                (TriviaPieceKind::Whitespace, "\t"),
+                (TriviaPieceKind::SingleLineComment, "/* c */"),
+                (TriviaPieceKind::SingleLineComment, "// trailing"),
+            ]);
+
+            push_piece(&mut pieces, text, kind, piece_text);
+
+            if piece_text.starts_with("//") {
+                self.needs_newline = true;
+                break;
+            }
+        }
+
+        pieces
+    }
+}
+
+fn push_piece(
+    pieces: &mut Vec<TriviaPiece>,
+    text: &mut String,
+    kind: TriviaPieceKind,
+    piece_text: &str,
+) {
+    text.push_str(piece_text);
+    pieces.push(TriviaPiece::new(kind, piece_text.len() as u32));
+}
@@ -0,0 +1,520 @@
+//! Property checks that assert that the different ways of traversing and querying a tree agree
+//! with each other.
+//!
+//! The checks are meant to be run on many generated trees (see [TreeGenerator]) and on trees
+//! returned by rewriters or other tree manipulations, to catch inconsistencies in offsets or
+//! the green tree's structure.
+
+use std::fmt;
+
+use text_size::{TextRange, TextSize};
+
+use crate::{
+    raw_language::RawLanguage,
+    syntax::{Language, node::SyntaxNode, token::SyntaxToken},
+    testing::{
+        generator::{GeneratorConfig, TreeGenerator},
+        render_tree,
+    },
+    utility_types::{Direction, NodeOrToken, TokenAtOffset, WalkEvent},
+};
+
+/// A failed property check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyFailure {
+    /// Name of the violated property.
+    pub property: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for PropertyFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.property, self.message)
+    }
+}
+
+/// Runs all property checks on the tree rooted at `root` and returns the failures.
+///
+/// The checks verify that:
+/// * the tree passes [SyntaxNode::validate]
+/// * the text of all tokens returned by `descendants_tokens` adds up to `text_with_trivia`
+/// * `preorder` enters and leaves every node returned by `descendants` and every node's text matches its range
+/// * `token_at_offset` returns the tokens whose ranges touch the offset, for every offset
+/// * `covering_element` returns the deepest element containing the range, for the range of
+///   every element and a set of ranges spanning multiple tokens
+pub fn check_properties<L: Language>(root: &SyntaxNode<L>) -> Vec<PropertyFailure> {
+    let mut failures = Vec::new();
+    let tokens: Vec<_> = root.descendants_tokens(Direction::Next).collect();
+
+    for violation in root.validate() {
+        failures.push(failure("validate", violation.to_string()));
+    }
+
+    check_text(root, &tokens, &mut failures);
+    check_preorder(root, &tokens, &mut failures);
+    check_token_at_offset(root, &tokens, &mut failures);
+    check_covering_element(root, &tokens, &mut failures);
+
+    failures
+}
+
+/// Asserts that the tree rooted at `root` passes all [check_properties].
+///
+/// ## Panics
+/// If any property check fails. The message includes the rendered tree.
+#[track_caller]
+pub fn assert_properties<L: Language>(root: &SyntaxNode<L>) {
+    let failures = check_properties(root);
+
+    if !failures.is_empty() {
+        panic!("{}", failure_report(root, &failures));
+    }
+}
+
+/// Generates a tree for every seed in `seeds` and asserts that it passes all property checks
+/// after applying `transform`.
+///
+/// Pass the identity function as `transform` to test the tree traversals, or a rewrite to test
+/// that the rewrite produces consistent trees.
+///
+/// ## Panics
+/// If a property check fails. The message includes the failing seed to reproduce the failure
+/// with [TreeGenerator::with_config].
+#[track_caller]
+pub fn check_generated_trees<F>(
+    seeds: std::ops::Range<u64>,
+    config: &GeneratorConfig,
+    mut transform: F,
+) where
+    F: FnMut(SyntaxNode<RawLanguage>, &mut TreeGenerator) -> SyntaxNode<RawLanguage>,
+{
+    for seed in seeds {
+        let mut generator = TreeGenerator::with_config(seed, config.clone());
+        let root = generator.generate();
+        let root = transform(root, &mut generator);
+
+        let failures = check_properties(&root);
+
+        if !failures.is_empty() {
+            panic!("seed {seed}: {}", failure_report(&root, &failures));
+        }
+    }
+}
+
+fn failure_report<L: Language>(root: &SyntaxNode<L>, failures: &[PropertyFailure]) -> String {
+    let mut report = String::from("property checks failed:\n");
+
+    for failure in failures {
+        report.push_str(&format!("  {failure}\n"));
+    }
+
+    report.push_str("tree:\n");
+    report.push_str(&render_tree(root));
+    report
+}
+
+fn failure(property: &'static str, message: String) -> PropertyFailure {
+    PropertyFailure { property, message }
+}
+
+fn check_text<L: Language>(
+    root: &SyntaxNode<L>,
+    tokens: &[SyntaxToken<L>],
+    failures: &mut Vec<PropertyFailure>,
+) {
+    let text = root.text_with_trivia().to_string();
+    let tokens_text: String = tokens.iter().map(|token| token.text()).collect();
+
+    if text != tokens_text {
+        failures.push(failure(
+            "text_with_trivia",
+            format!("tree text {text:?} doesn't match the text of its tokens {tokens_text:?}"),
+        ));
+    }
+
+    let mut offset = root.text_range_with_trivia().start();
+
+    for token in tokens {
+        if token.text_range().start() != offset {
+            failures.push(failure(
+                "descendants_tokens",
+                format!(
+                    "token {:?}@{:?} doesn't start where the previous token ends ({offset:?})",
+                    token.kind(),
+                    token.text_range()
+                ),
+            ));
+        }
+
+        offset = token.text_range().end();
+    }
+
+    if offset != root.text_range_with_trivia().end() {
+        failures.push(failure(
+            "descendants_tokens",
+            format!(
+                "the last token ends at {offset:?} but the tree ends at {:?}",
+                root.text_range_with_trivia().end()
+            ),
+        ));
+    }
+}
+
+fn check_preorder<L: Language>(
+    root: &SyntaxNode<L>,
+    tokens: &[SyntaxToken<L>],
+    failures: &mut Vec<PropertyFailure>,
+) {
+    let text = root.text_with_trivia().to_string();
+    let root_start = root.text_range_with_trivia().start();
+    let mut stack = Vec::new();
+    let mut entered = Vec::new();
+
+    for event in root.preorder() {
+        match event {
+            WalkEvent::Enter(node) => {
+                let range = node.text_range_with_trivia() - root_start;
+                let node_text = node.text_with_trivia().to_string();
+
+                if text.get(std::ops::Range::<usize>::from(range)) != Some(node_text.as_str()) {
+                    failures.push(failure(
+                        "preorder",
+                        format!(
+                            "text of {:?}@{:?} doesn't match the tree's text in that range",
+                            node.kind(),
+                            node.text_range_with_trivia()
+                        ),
+                    ));
+                }
+
+                if let Some(parent) = stack.last()
+                    && node.parent().as_ref() != Some(parent)
+                {
+                    failures.push(failure(
+                        "preorder",
+                        format!(
+                            "{:?}@{:?} is entered inside of a node that isn't its parent",
+                            node.kind(),
+                            node.text_range_with_trivia()
+                        ),
+                    ));
+                }
+
+                stack.push(node.clone());
+                entered.push(node);
+            }
+            WalkEvent::Leave(node) => {
+                if stack.pop().as_ref() != Some(&node) {
+                    failures.push(failure(
+                        "preorder",
+                        format!(
+                            "leaving {:?}@{:?} that isn't the last entered node",
+                            node.kind(),
+                            node.text_range_with_trivia()
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    if !stack.is_empty() {
+        failures.push(failure(
+            "preorder",
+            format!("{} nodes were entered but never left", stack.len()),
+        ));
+    }
+
+    let descendants: Vec<_> = root.descendants().collect();
+    if entered != descendants {
+        failures.push(failure(
+            "preorder",
+            format!(
+                "preorder entered {} nodes but descendants returned {} nodes",
+                entered.len(),
+                descendants.len()
+            ),
+        ));
+    }
+
+    let preorder_tokens: Vec<_> = root
+        .preorder_with_tokens(Direction::Next)
+        .filter_map(|event| match event {
+            WalkEvent::Enter(NodeOrToken::Token(token)) => Some(token),
+            _ => None,
+        })
+        .collect();
+
+    if preorder_tokens != tokens {
+        failures.push(failure(
+            "preorder",
+            format!(
+                "preorder_with_tokens visited {} tokens but descendants_tokens returned {} tokens",
+                preorder_tokens.len(),
+                tokens.len()
+            ),
+        ));
+    }
+}
+
+fn check_token_at_offset<L: Language>(
+    root: &SyntaxNode<L>,
+    tokens: &[SyntaxToken<L>],
+    failures: &mut Vec<PropertyFailure>,
+) {
+    let range = root.text_range_with_trivia();
+    let text = root.text_with_trivia().to_string();
+
+    for (relative, _) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        let offset = range.start() + TextSize::try_from(relative).unwrap();
+
+        let mut touching = tokens.iter().filter(|token| {
+            let token_range = token.text_range();
+            !token_range.is_empty() && token_range.contains_inclusive(offset)
+        });
+
+        let expected = match (touching.next(), touching.next()) {
+            (None, _) => TokenAtOffset::None,
+            (Some(token), None) => TokenAtOffset::Single(token.clone()),
+            (Some(left), Some(right)) => TokenAtOffset::Between(left.clone(), right.clone()),
+        };
+
+        let actual = root.token_at_offset(offset);
+
+        let matches = match (&expected, &actual) {
+            (TokenAtOffset::None, TokenAtOffset::None) => true,
+            (TokenAtOffset::Single(expected), TokenAtOffset::Single(actual)) => expected == actual,
+            (
+                TokenAtOffset::Between(expected_left, expected_right),
+                TokenAtOffset::Between(actual_left, actual_right),
+            ) => expected_left == actual_left && expected_right == actual_right,
+            _ => false,
+        };
+
+        if !matches {
+            failures.push(failure(
+                "token_at_offset",
+                format!(
+                    "expected {:?} but got {:?} at offset {offset:?}",
+                    describe_tokens(expected),
+                    describe_tokens(actual)
+                ),
+            ));
+        }
+    }
+}
+
+fn describe_tokens<L: Language>(tokens: TokenAtOffset<SyntaxToken<L>>) -> Vec<String> {
+    tokens
+        .map(|token| format!("{:?}@{:?}", token.kind(), token.text_range()))
+        .collect()
+}
+
+fn check_covering_element<L: Language>(
+    root: &SyntaxNode<L>,
+    tokens: &[SyntaxToken<L>],
+    failures: &mut Vec<PropertyFailure>,
+) {
+    let non_empty: Vec<_> = tokens
+        .iter()
+        .map(SyntaxToken::text_range)
+        .filter(|range| !range.is_empty())
+        .collect();
+
+    let mut ranges: Vec<TextRange> = root
+        .descendants()
+        .map(|node| node.text_range_with_trivia())
+        .chain(non_empty.iter().copied())
+        .filter(|range| !range.is_empty())
+        .collect();
+
+    // Ranges spanning from the middle of one token to the middle of another token
+    for window in non_empty.windows(3) {
+        let start = window[0].start() + TextSize::from(u32::from(window[0].len()) / 2);
+        let end = window[2].start() + TextSize::from(u32::from(window[2].len()) / 2);
+        ranges.push(TextRange::new(start, end.max(start)));
+    }
+
+    for range in ranges {
+        let covering = root.covering_element(range);
+
+        let covering_range = match &covering {
+            NodeOrToken::Node(node) => node.text_range_with_trivia(),
+            NodeOrToken::Token(token) => token.text_range(),
+        };
+
+        if !covering_range.contains_range(range) {
+            failures.push(failure(
+                "covering_element",
+                format!("covering element {covering_range:?} doesn't contain {range:?}"),
+            ));
+            continue;
+        }
+
+        if let NodeOrToken::Node(node) = &covering {
+            let deeper = node.children_with_tokens().find(|child| {
+                let child_range = match child {
+                    NodeOrToken::Node(node) => node.text_range_with_trivia(),
+                    NodeOrToken::Token(token) => token.text_range(),
+                };
+
+                child_range.contains_range(range)
+            });
+
+            if let Some(deeper) = deeper {
+                failures.push(failure(
+                    "covering_element",
+                    format!(
+                        "{:?}@{covering_range:?} covers {range:?} but its child {:?} covers it too",
+                        node.kind(),
+                        deeper.kind()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::{
+        raw_language::RawLanguageKind,
+        syntax::{SyntaxKind, element::SyntaxElement, trivia::TriviaPieceKind},
+        testing::generator::Rng,
+    };
+
+    /// Replaces a random range of slots of a random node with copies of random elements of the tree.
+    fn splice_random_slots(
+        root: SyntaxNode<RawLanguage>,
+        rng: &mut Rng,
+    ) -> SyntaxNode<RawLanguage> {
+        let nodes: Vec<_> = root
+            .descendants()
+            .filter(|node| !node.kind().is_root())
+            .collect();
+        let elements: Vec<SyntaxElement<RawLanguage>> = nodes
+            .iter()
+            .cloned()
+            .map(NodeOrToken::Node)
+            .chain(
+                root.descendants_tokens(Direction::Next)
+                    .filter(|token| token.kind() != RawLanguageKind::EOF)
+                    .map(NodeOrToken::Token),
+            )
+            .collect();
+
+        if nodes.is_empty() {
+            return root;
+        }
+
+        let node = rng.choose(&nodes).clone();
+        let slot_count = node.slots().len();
+        let start = rng.below(slot_count + 1);
+        let end = start + rng.below(slot_count - start + 1);
+        // Lists other than separated lists can't contain empty slots
+        let allow_empty = !node.kind().is_list() || node.kind().list_separator().is_some();
+
+        let replace_with: Vec<_> = (0..rng.below(4))
+            .map(|_| {
+                if allow_empty && rng.chance(20) {
+                    None
+                } else {
+                    Some(rng.choose(&elements).clone())
+                }
+            })
+            .collect();
+
+        let spliced = node.splice_slots(start..end, replace_with);
+        spliced.ancestors().last().unwrap_or(spliced)
+    }
+
+    /// Replaces the leading and trailing trivia of random tokens with random trivia pieces.
+    fn rewrite_random_trivia(
+        mut root: SyntaxNode<RawLanguage>,
+        rng: &mut Rng,
+    ) -> SyntaxNode<RawLanguage> {
+        const LEADING: [(TriviaPieceKind, &str); 5] = [
+            (TriviaPieceKind::Whitespace, "  "),
+            (TriviaPieceKind::Newline, "\n"),
+            (TriviaPieceKind::MultiLineComment, "/* a\nb */"),
+            (TriviaPieceKind::Skipped, "@"),
+            (TriviaPieceKind::Whitespace, "\t"),
+        ];
+        const TRAILING: [(TriviaPieceKind, &str); 3] = [
+            (TriviaPieceKind::Whitespace, " "),
+            (TriviaPieceKind::MultiLineComment, "/* c */"),
+            (TriviaPieceKind::Skipped, "é"),
+        ];
+
+        let token_count = root.descendants_tokens(Direction::Next).count();
+
+        for index in 0..token_count {
+            if !rng.chance(50) {
+                continue;
+            }
+
+            let token = root.descendants_tokens(Direction::Next).nth(index).unwrap();
+            let leading: Vec<_> = (0..rng.below(4)).map(|_| *rng.choose(&LEADING)).collect();
+            let trailing: Vec<_> = (0..rng.below(3)).map(|_| *rng.choose(&TRAILING)).collect();
+            let rewritten = token
+                .with_leading_trivia(leading)
+                .with_trailing_trivia(trailing);
+
+            root = root.replace_child(token.into(), rewritten.into()).unwrap();
+        }
+
+        root
+    }
+
+    #[test]
+    fn generated_trees_pass_property_checks() {
+        check_generated_trees(0..200, &GeneratorConfig::default(), |root, _| root);
+    }
+
+    #[test]
+    fn generated_trees_with_trivia_and_bogus_nodes_pass_property_checks() {
+        let config = GeneratorConfig {
+            trivia_percent: 90,
+            empty_slot_percent: 30,
+            bogus_percent: 30,
+            ..GeneratorConfig::default()
+        };
+
+        check_generated_trees(0..200, &config, |root, _| root);
+    }
+
+    #[test]
+    fn spliced_trees_pass_property_checks() {
+        let config = GeneratorConfig {
+            empty_slot_percent: 30,
+            ..GeneratorConfig::default()
+        };
+
+        check_generated_trees(0..200, &config, |root, generator| {
+            let root = splice_random_slots(root, generator.rng());
+            splice_random_slots(root, generator.rng())
+        });
+    }
+
+    #[test]
+    fn trees_with_rewritten_trivia_pass_property_checks() {
+        check_generated_trees(0..200, &GeneratorConfig::default(), |root, generator| {
+            rewrite_random_trivia(root, generator.rng())
+        });
+    }
+
+    #[test]
+    fn detached_subtrees_pass_property_checks() {
+        for seed in 0..50 {
+            let root = TreeGenerator::new(seed).generate();
+
+            for node in root.descendants() {
+                assert_properties(&node.clone_subtree());
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Checks the code generated by `#[derive(SyntaxKind)]` for a sample kind enum.
+
+#![cfg(feature = "macros")]
+
+use mini_rowan::{
+    ast::{AstNode, SyntaxKindSet},
+    green::RawSyntaxKind,
+    syntax::{Language, SyntaxKind},
+};
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SyntaxKind)]
+#[syntax_kind(language = TestLanguage)]
+enum TestKind {
+    #[tombstone]
+    TOMBSTONE,
+    #[eof]
+    EOF,
+    #[token("+")]
+    PLUS,
+    #[token(",")]
+    COMMA,
+    IDENT,
+    #[trivia]
+    WHITESPACE,
+    #[root]
+    ROOT,
+    #[list]
+    STATEMENT_LIST,
+    #[separated_list(COMMA)]
+    ARGUMENT_LIST,
+    #[kind_set(EXPRESSIONS, BINARY)]
+    #[to_bogus(BOGUS_EXPRESSION)]
+    BINARY_EXPRESSION,
+    #[kind_set(EXPRESSIONS)]
+    #[to_bogus(BOGUS_EXPRESSION)]
+    NAME_EXPRESSION,
+    #[bogus]
+    BOGUS,
+    #[bogus]
+    #[kind_set(EXPRESSIONS)]
+    BOGUS_EXPRESSION,
+}
+
+use TestKind::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct TestLanguage;
+
+impl Language for TestLanguage {
+    type Kind = TestKind;
+    type Root = TestRoot;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TestRoot;
+
+impl AstNode for TestRoot {
+    type Language = TestLanguage;
+
+    const KIND_SET: SyntaxKindSet<TestLanguage> =
+        SyntaxKindSet::from_raw(RawSyntaxKind(ROOT as u16));
+
+    fn can_cast(kind: TestKind) -> bool {
+        kind == ROOT
+    }
+}
+
+fn kinds(set: SyntaxKindSet<TestLanguage>) -> Vec<TestKind> {
+    set.iter().collect()
+}
+
+#[test]
+fn raw_kinds_are_variant_indexes() {
+    assert_eq!(TestKind::KIND_COUNT, 13);
+    assert_eq!(TestKind::TOMBSTONE, TOMBSTONE);
+    assert_eq!(TestKind::EOF, EOF);
+
+    for (index, kind) in TestKind::VARIANTS.into_iter().enumerate() {
+        let raw = RawSyntaxKind(index as u16);
+        assert_eq!(kind.to_raw(), raw);
+        assert_eq!(TestKind::from_raw(raw), kind);
+    }
+}
+
+#[test]
+#[should_panic(expected = "raw kind 13 is out of bounds")]
+fn from_raw_panics_for_out_of_bounds_kinds() {
+    TestKind::from_raw(RawSyntaxKind(13));
+}
+
+#[test]
+fn names_round_trip() {
+    assert_eq!(TestKind::NAMES.len(), TestKind::VARIANTS.len());
+    assert_eq!(BINARY_EXPRESSION.name(), "BINARY_EXPRESSION");
+
+    for kind in TestKind::VARIANTS {
+        assert_eq!(TestKind::from_name(kind.name()), Some(kind));
+    }
+
+    assert_eq!(TestKind::from_name("binary_expression"), None);
+    assert_eq!(TestKind::from_name("MISSING"), None);
+}
+
+#[test]
+fn to_bogus() {
+    assert_eq!(BINARY_EXPRESSION.to_bogus(), BOGUS_EXPRESSION);
+    assert_eq!(NAME_EXPRESSION.to_bogus(), BOGUS_EXPRESSION);
+    assert_eq!(IDENT.to_bogus(), BOGUS);
+    assert_eq!(STATEMENT_LIST.to_bogus(), BOGUS);
+
+    assert_eq!(BOGUS.to_bogus(), BOGUS);
+    assert_eq!(BOGUS_EXPRESSION.to_bogus(), BOGUS_EXPRESSION);
+    assert!(BOGUS.is_bogus());
+    assert!(BOGUS_EXPRESSION.is_bogus());
+    assert!(!BINARY_EXPRESSION.is_bogus());
+}
+
+#[test]
+fn token_text() {
+    assert_eq!(PLUS.to_string(), Some("+"));
+    assert_eq!(COMMA.to_string(), Some(","));
+    assert_eq!(IDENT.to_string(), None);
+    assert_eq!(BINARY_EXPRESSION.to_string(), None);
+}
+
+#[test]
+fn lists_roots_and_trivia() {
+    assert!(STATEMENT_LIST.is_list());
+    assert!(ARGUMENT_LIST.is_list());
+    assert!(!BINARY_EXPRESSION.is_list());
+
+    assert_eq!(STATEMENT_LIST.list_separator(), None);
+    assert_eq!(ARGUMENT_LIST.list_separator(), Some(COMMA));
+    assert_eq!(BINARY_EXPRESSION.list_separator(), None);
+
+    assert!(ROOT.is_root());
+    assert!(!STATEMENT_LIST.is_root());
+
+    assert!(WHITESPACE.is_trivia());
+    assert!(!IDENT.is_trivia());
+}
+
+#[test]
+fn kind_set_constants() {
+    assert_eq!(kinds(TestKind::TOKEN_KINDS), [PLUS, COMMA]);
+    assert_eq!(kinds(TestKind::LIST_KINDS), [STATEMENT_LIST, ARGUMENT_LIST]);
+    assert_eq!(kinds(TestKind::BOGUS_KINDS), [BOGUS, BOGUS_EXPRESSION]);
+    assert_eq!(kinds(TestKind::TRIVIA_KINDS), [WHITESPACE]);
+    assert_eq!(
+        kinds(TestKind::EXPRESSIONS),
+        [BINARY_EXPRESSION, NAME_EXPRESSION, BOGUS_EXPRESSION]
+    );
+    assert_eq!(kinds(TestKind::BINARY), [BINARY_EXPRESSION]);
+}
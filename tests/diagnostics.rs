@@ -0,0 +1,54 @@
+//! Leak checks for the object counts in [mini_rowan::diagnostics].
+//!
+//! The `countme` counters are global for the whole process. The checks live in their own test
+//! binary with a single test so that no other test allocates trees while they run.
+
+#![cfg(all(feature = "countme", feature = "testing"))]
+
+use std::panic::{self, AssertUnwindSafe};
+
+use mini_rowan::{
+    diagnostics::{assert_no_leaks, enable_counting, live_objects},
+    raw_language::{RawLanguageKind, parse},
+};
+
+#[test]
+fn dropping_a_tree_frees_all_objects() {
+    enable_counting(true);
+    assert!(!live_objects().has_live(), "{}", live_objects());
+
+    let root = parse("a + b; // comment\nf(1, -2);\n");
+    let call = root
+        .descendants()
+        .find(|node| node.kind() == RawLanguageKind::CALL_EXPRESSION)
+        .unwrap();
+
+    let alive = live_objects();
+    assert!(alive.green_nodes.live > 0);
+    assert!(alive.green_tokens.live > 0);
+    assert!(alive.green_trivia.live > 0);
+    assert!(alive.syntax_elements.live > 0);
+
+    drop(root);
+    // The call expression keeps the whole tree alive
+    assert!(live_objects().green_nodes.live > 0);
+
+    drop(call);
+    let after = live_objects();
+    assert!(!after.has_live(), "{after}");
+    assert!(after.green_nodes.total >= alive.green_nodes.live);
+
+    assert_no_leaks(|| {
+        let root = parse("a;");
+        let _ = root.descendants().count();
+    });
+
+    let mut leaked = None;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        assert_no_leaks(|| leaked = Some(parse("a;")));
+    }));
+    assert!(result.is_err());
+
+    drop(leaked);
+    assert!(!live_objects().has_live(), "{}", live_objects());
+}